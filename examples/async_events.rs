@@ -0,0 +1,48 @@
+use futures::StreamExt;
+use mpris::AsyncPlayerFinder;
+use std::time::{Duration, Instant};
+
+fn main() {
+    let runtime = tokio_crate::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Could not start Tokio runtime");
+
+    runtime.block_on(show_events());
+}
+
+async fn show_events() {
+    let player = AsyncPlayerFinder::new()
+        .expect("Could not connect to D-Bus")
+        .find_active()
+        .await
+        .expect("Could not find active player");
+
+    println!(
+        "Showing event stream for player {}...\n(Exit with Ctrl-C)\n",
+        player.identity()
+    );
+
+    let mut events = player.events().await.expect("Could not start event stream");
+    let start = Instant::now();
+
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) => println!("{}: {:#?}", format_elapsed(start.elapsed()), event),
+            Err(err) => {
+                println!("D-Bus error: {}. Aborting.", err);
+                break;
+            }
+        }
+    }
+
+    println!("Event stream ended.");
+}
+
+fn format_elapsed(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let minutes = seconds / 60;
+    let seconds_left = seconds - (60 * minutes);
+    let ms = duration.subsec_millis();
+    format!("{:02}:{:02}.{:3}", minutes, seconds_left, ms)
+}
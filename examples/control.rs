@@ -287,7 +287,7 @@ fn cycle_loop_status(player: &Player) -> Result<(), mpris::DBusError> {
 
 fn change_volume(player: &Player, diff: f64) -> Result<(), mpris::DBusError> {
     let current_volume = player.get_volume()?;
-    let new_volume = (current_volume + diff).max(0.0).min(1.0);
+    let new_volume = (current_volume + diff).clamp(0.0, 1.0);
     player.set_volume(new_volume)
 }
 
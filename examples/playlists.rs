@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use mpris::{PlayerFinder, PlaylistOrdering};
+
+fn main() {
+    match print_playlists() {
+        Ok(_) => {}
+        Err(error) => {
+            println!("Error: {}", error);
+            for (i, cause) in error.chain().skip(1).enumerate() {
+                print!("{}", "  ".repeat(i + 1));
+                println!("Caused by: {}", cause);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_playlists() -> Result<()> {
+    let player = PlayerFinder::new()
+        .context("Could not connect to D-Bus")?
+        .find_active()
+        .context("Could not find any player")?;
+
+    println!(
+        "Found {identity} (on bus {bus_name})",
+        bus_name = player.bus_name(),
+        identity = player.identity(),
+    );
+
+    if !player.supports_playlists() {
+        println!("Player does not support the Playlists interface.");
+        return Ok(());
+    }
+
+    let count = player
+        .get_playlist_count()
+        .context("Could not get playlist count")?;
+    let active = player
+        .get_active_playlist()
+        .context("Could not get active playlist")?;
+
+    println!("Playlists ({}):\n", count);
+    for playlist in player
+        .get_playlists(0, count, PlaylistOrdering::Alphabetical, false)
+        .context("Could not load playlists")?
+    {
+        let marker = if Some(&playlist) == active.as_ref() {
+            "▶"
+        } else {
+            " "
+        };
+        println!("{} {} ({})", marker, playlist.name(), playlist.id());
+    }
+
+    Ok(())
+}
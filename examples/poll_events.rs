@@ -0,0 +1,51 @@
+use mpris::{Event, PlayerFinder};
+
+// Shows how to read the events of a player from an event loop of your own, here a plain
+// `poll(2)` loop, instead of blocking on the event iterator.
+fn main() {
+    let finder = PlayerFinder::new().expect("Could not connect to D-Bus");
+    let player = finder.find_active().expect("Could not find active player");
+
+    println!(
+        "Polling events of player {}...\n(Exit with Ctrl-C)\n",
+        player.identity()
+    );
+
+    let mut events = player.events().expect("Could not start event stream");
+
+    loop {
+        // Dispatch before polling too, as messages that arrived during method calls were already
+        // read from the file descriptors.
+        finder.dispatch_ready();
+        loop {
+            match events.try_next() {
+                Ok(Some(Event::PlayerShutDown)) => {
+                    println!("Player quit.");
+                    return;
+                }
+                Ok(Some(event)) => println!("{:#?}", event),
+                Ok(None) => break,
+                Err(err) => {
+                    println!("D-Bus error: {}. Aborting.", err);
+                    return;
+                }
+            }
+        }
+
+        let mut fds: Vec<libc::pollfd> = finder
+            .watch_fds()
+            .iter()
+            .map(|watch| watch.to_pollfd())
+            .collect();
+
+        // A real application would add its own file descriptors here.
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, 1000) };
+        if ready < 0 {
+            println!("Polling failed. Aborting.");
+            return;
+        }
+        if ready == 0 {
+            println!("Nothing happened in the last second.");
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use mpris::{Metadata, MetadataValue, MprisServer, PlaybackStatus, PlayerBackend};
+
+const TRACK_LENGTH: Duration = Duration::from_secs(180);
+
+/// A pretend player that "plays" a single track of silence.
+struct SilencePlayer {
+    status: PlaybackStatus,
+    /// Position of the track when playback was last started, paused or seeked.
+    position: Duration,
+    /// When playback was last started or seeked, if playing.
+    playing_since: Option<Instant>,
+    volume: f64,
+}
+
+impl SilencePlayer {
+    fn set_status(&mut self, status: PlaybackStatus) {
+        self.position = self.position();
+        self.playing_since = match status {
+            PlaybackStatus::Playing => Some(Instant::now()),
+            PlaybackStatus::Paused | PlaybackStatus::Stopped => None,
+        };
+        if status == PlaybackStatus::Stopped {
+            self.position = Duration::from_secs(0);
+        }
+        self.status = status;
+    }
+}
+
+impl PlayerBackend for SilencePlayer {
+    fn identity(&self) -> String {
+        String::from("Silence Player")
+    }
+
+    fn playback_status(&self) -> PlaybackStatus {
+        self.status
+    }
+
+    fn metadata(&self) -> Metadata {
+        let mut values = HashMap::new();
+        values.insert(
+            String::from("mpris:trackid"),
+            MetadataValue::from("/org/example/SilencePlayer/Track/1"),
+        );
+        values.insert(String::from("xesam:title"), MetadataValue::from("Silence"));
+        values.insert(
+            String::from("xesam:artist"),
+            MetadataValue::from(vec![MetadataValue::from("Nobody")]),
+        );
+        values.insert(
+            String::from("mpris:length"),
+            MetadataValue::I64(TRACK_LENGTH.as_micros() as i64),
+        );
+        Metadata::from(values)
+    }
+
+    fn position(&self) -> Duration {
+        let elapsed = self
+            .playing_since
+            .map(|since| since.elapsed())
+            .unwrap_or_default();
+        (self.position + elapsed).min(TRACK_LENGTH)
+    }
+
+    fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    fn set_volume(&mut self, volume: f64) {
+        println!("Volume changed to {:.0}%", volume * 100.0);
+        self.volume = volume;
+    }
+
+    fn can_control(&self) -> bool {
+        true
+    }
+
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    fn play(&mut self) {
+        println!("Playing");
+        self.set_status(PlaybackStatus::Playing);
+    }
+
+    fn pause(&mut self) {
+        println!("Paused");
+        self.set_status(PlaybackStatus::Paused);
+    }
+
+    fn play_pause(&mut self) {
+        match self.status {
+            PlaybackStatus::Playing => self.pause(),
+            PlaybackStatus::Paused | PlaybackStatus::Stopped => self.play(),
+        }
+    }
+
+    fn stop(&mut self) {
+        println!("Stopped");
+        self.set_status(PlaybackStatus::Stopped);
+    }
+
+    fn seek(&mut self, offset_in_microseconds: i64) {
+        let position = self.position().as_micros() as i64 + offset_in_microseconds;
+        self.position = Duration::from_micros(position.max(0) as u64).min(TRACK_LENGTH);
+        self.playing_since = self.playing_since.map(|_| Instant::now());
+        println!("Seeked to {}s", self.position.as_secs());
+    }
+}
+
+fn main() {
+    match serve() {
+        Ok(_) => {}
+        Err(error) => {
+            println!("Error: {}", error);
+            for (i, cause) in error.chain().skip(1).enumerate() {
+                print!("{}", "  ".repeat(i + 1));
+                println!("Caused by: {}", cause);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn serve() -> Result<()> {
+    let player = SilencePlayer {
+        status: PlaybackStatus::Stopped,
+        position: Duration::from_secs(0),
+        playing_since: None,
+        volume: 1.0,
+    };
+    let mut server =
+        MprisServer::new("silence", player).context("Could not register MPRIS server")?;
+
+    println!(
+        "Serving on {}. Control it with any MPRIS client, or press Ctrl-C to quit.",
+        server.bus_name()
+    );
+
+    loop {
+        server
+            .process(1000)
+            .context("Could not process incoming calls")?;
+
+        // Stop at the end of the track, and tell clients about it.
+        if server.backend().position() >= TRACK_LENGTH {
+            server.backend_mut().stop();
+            server
+                .notify_properties_changed()
+                .context("Could not announce changes")?;
+        }
+    }
+}
@@ -136,8 +136,6 @@ fn add_track(player: &Player) -> Result<()> {
             .add_track(&uri, &track_id, false)
             .map_err(Error::from),
         Ok(None) => player.add_track_at_start(&uri, false).map_err(Error::from),
-        Err(err) => Err(err)
-            .context("Failed to select track")
-            .map_err(Error::from),
+        Err(err) => Err(err).context("Failed to select track"),
     }
 }
@@ -34,6 +34,7 @@ for spec in "$root"/mpris-spec/spec/org.mpris.*.xml; do
 #![allow(unknown_lints)]
 #![allow(clippy::all)]
 #![allow(
+    dead_code,
     missing_debug_implementations,
     missing_copy_implementations,
     trivial_casts,
@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::find::{find_active_player, FindingError};
+use crate::pooled_connection::{ActivityLog, NameChange, NameChangeQueue, PooledConnection};
+use crate::{DBusError, Player, PlayerId};
+
+/// Keeps track of which [`Player`] the user interacted with last.
+///
+/// Created by calling [`PlayerFinder::track_active`](crate::PlayerFinder::track_active).
+///
+/// A player counts as active whenever it changes its playback status or its track, is sent a
+/// command through this connection, or appears on the bus. This is the same ordering that
+/// `playerctld` uses, so the "current" player follows whatever the user touched last, even if
+/// several players are playing at once.
+///
+/// The tracker only learns about activity while it exists, so create it early and keep it around.
+/// Activity is picked up whenever any of its methods are called; nothing happens in the
+/// background.
+#[derive(Debug)]
+pub struct ActivePlayerTracker {
+    connection: Rc<PooledConnection>,
+    timeout_ms: i32,
+    name_changes: Rc<NameChangeQueue>,
+    activity: Rc<ActivityLog>,
+
+    /// Running players, in the order they were found.
+    players: RefCell<Vec<PlayerId>>,
+}
+
+impl ActivePlayerTracker {
+    pub(crate) fn new(connection: Rc<PooledConnection>, timeout_ms: i32) -> ActivePlayerTracker {
+        ActivePlayerTracker {
+            name_changes: connection.watch_names(),
+            activity: connection.log_activity(),
+            connection,
+            timeout_ms,
+            players: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Starts tracking the players on the given bus names, without counting them as active.
+    pub(crate) fn with_buses(self, buses: Vec<String>) -> ActivePlayerTracker {
+        for bus_name in buses {
+            if let Some(unique_name) = self.connection.determine_unique_name(bus_name.as_str()) {
+                self.players
+                    .borrow_mut()
+                    .push(PlayerId::new(bus_name, unique_name));
+            }
+        }
+        self
+    }
+
+    /// Returns the [`PlayerId`]s of all running players, the most recently active one first.
+    ///
+    /// Players that have not been active since the tracker was created come last, in the order
+    /// they were found.
+    pub fn players(&self) -> Vec<PlayerId> {
+        self.update();
+
+        let activity = self.activity.borrow();
+        let mut players = self.players.borrow().clone();
+        // The sort is stable, so inactive players keep their order.
+        players.sort_by(|a, b| {
+            let a = activity.get(a.unique_name());
+            let b = activity.get(b.unique_name());
+            b.cmp(&a)
+        });
+        players
+    }
+
+    /// Returns when the given player was last active, or [`None`] if it has not been active since
+    /// the tracker was created or is no longer running.
+    pub fn last_active(&self, id: &PlayerId) -> Option<Instant> {
+        self.update();
+
+        if self.players.borrow().contains(id) {
+            self.activity.borrow().get(id.unique_name()).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the [`Player`] that was active most recently.
+    ///
+    /// If no player has been active since the tracker was created, the "active" player is guessed
+    /// like [`PlayerFinder::find_active`](crate::PlayerFinder::find_active) does. Returns
+    /// [`NoPlayerFound`](FindingError::NoPlayerFound) if no player is running.
+    pub fn find_active(&self) -> Result<Player, FindingError> {
+        let players = self.players();
+
+        if let Some(id) = players.first() {
+            if self.activity.borrow().contains_key(id.unique_name()) {
+                return Ok(self.player(id)?);
+            }
+        }
+
+        match find_active_player(
+            players.iter().map(|id| self.player(id)),
+            Player::get_playback_status,
+            Player::get_metadata,
+        )? {
+            Some(player) => Ok(player),
+            None => Err(FindingError::NoPlayerFound),
+        }
+    }
+
+    fn player(&self, id: &PlayerId) -> Result<Player, DBusError> {
+        Player::for_pooled_connection(
+            self.connection.clone(),
+            id.bus_name().to_string(),
+            self.timeout_ms,
+        )
+    }
+
+    /// Applies the players that appeared or quit since the last call.
+    fn update(&self) {
+        self.connection.process_pending_messages();
+
+        loop {
+            let change = self.name_changes.borrow_mut().pop_front();
+            match change {
+                Some(NameChange::Acquired { bus_name }) => {
+                    if let Some(unique_name) =
+                        self.connection.determine_unique_name(bus_name.as_str())
+                    {
+                        self.connection.record_activity(&unique_name);
+                        let mut players = self.players.borrow_mut();
+                        players.retain(|id| id.bus_name() != bus_name);
+                        players.push(PlayerId::new(bus_name, unique_name));
+                    }
+                }
+                Some(NameChange::Lost {
+                    bus_name,
+                    unique_name,
+                }) => self
+                    .players
+                    .borrow_mut()
+                    .retain(|id| id.bus_name() != bus_name || id.unique_name() != unique_name),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::tests::serve;
+    use crate::PlayerFinder;
+
+    #[test]
+    fn it_orders_players_by_activity() {
+        let (first_bus, first_handle) = serve("mpris_rs_test_active_first");
+        let (second_bus, second_handle) = serve("mpris_rs_test_active_second");
+        let finder = PlayerFinder::new().unwrap();
+        let tracker = finder.track_active().unwrap();
+
+        let position = |bus_name: &str| {
+            tracker
+                .players()
+                .iter()
+                .position(|id| id.bus_name() == bus_name)
+                .unwrap()
+        };
+        let first = tracker
+            .player(&tracker.players()[position(&first_bus)])
+            .unwrap();
+        let second = tracker
+            .player(&tracker.players()[position(&second_bus)])
+            .unwrap();
+        assert_eq!(tracker.last_active(&first.id()), None);
+
+        // Neither call makes the player announce any activity of its own.
+        second.set_volume(0.5).unwrap();
+        assert!(position(&second_bus) < position(&first_bus));
+        first.next().unwrap();
+        assert!(position(&first_bus) < position(&second_bus));
+        assert!(tracker.last_active(&first.id()) > tracker.last_active(&second.id()));
+
+        second.quit().unwrap();
+        second_handle.join().unwrap();
+        // The bus announces the quit on its own time.
+        for _ in 0..100 {
+            if tracker.last_active(&second.id()).is_none() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(tracker.last_active(&second.id()), None);
+        assert!(tracker
+            .players()
+            .iter()
+            .all(|id| id.bus_name() != second_bus));
+
+        first.quit().unwrap();
+        first_handle.join().unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::event::EventState;
+use crate::pooled_connection::{EventSubscription, NameChange, NameChangeQueue, PooledConnection};
+use crate::{Event, EventError, Player, PlayerId};
+
+/// Iterator that blocks forever until any [`Player`] on the bus has an [`Event`].
+///
+/// Created by calling [`PlayerFinder::all_player_events`](crate::PlayerFinder::all_player_events).
+///
+/// This works like a [`PlayerEvents`](crate::PlayerEvents) iterator for every player at once, on
+/// a single connection. Each [`Event`] is paired with the [`PlayerId`] of the player that it
+/// happened to. Players that start after the iterator was created are picked up automatically,
+/// and players that quit are dropped after emitting one last [`Event::PlayerShutDown`].
+///
+/// Errors while reading the state of one player are returned without stopping the iteration,
+/// which never ends on its own.
+#[derive(Debug)]
+pub struct AllPlayerEvents {
+    connection: Rc<PooledConnection>,
+    name_changes: Rc<NameChangeQueue>,
+    timeout_ms: i32,
+
+    /// All players that are being watched, in the order they were found.
+    players: Vec<WatchedPlayer>,
+
+    /// Queued up items found after the last signal.
+    buffer: VecDeque<Result<(PlayerId, Event), EventError>>,
+}
+
+#[derive(Debug)]
+struct WatchedPlayer {
+    player: Player,
+    state: EventState,
+    events: Rc<EventSubscription>,
+}
+
+impl AllPlayerEvents {
+    pub(crate) fn new(connection: Rc<PooledConnection>, timeout_ms: i32) -> AllPlayerEvents {
+        AllPlayerEvents {
+            name_changes: connection.watch_names(),
+            connection,
+            timeout_ms,
+            players: Vec::new(),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Starts watching the players on the given bus names.
+    pub(crate) fn with_players(mut self, buses: Vec<String>) -> AllPlayerEvents {
+        for bus_name in buses {
+            self.add_player(bus_name);
+        }
+        self
+    }
+
+    /// Returns the watched [`Player`] with the given [`PlayerId`], if it is still running.
+    pub fn player(&self, id: &PlayerId) -> Option<&Player> {
+        self.players()
+            .find(|player| player.unique_name() == id.unique_name())
+    }
+
+    /// Returns an iterator over all [`Player`]s that are currently being watched.
+    pub fn players(&self) -> impl Iterator<Item = &Player> + '_ {
+        self.players.iter().map(|watched| &watched.player)
+    }
+
+    fn add_player(&mut self, bus_name: String) {
+        if self.players().any(|player| player.bus_name() == bus_name) {
+            return;
+        }
+
+        let watched =
+            Player::for_pooled_connection(self.connection.clone(), bus_name, self.timeout_ms)
+                .and_then(|player| {
+                    let events = player.subscribe_events();
+                    let state = EventState::new(&player)?;
+                    Ok(WatchedPlayer {
+                        player,
+                        state,
+                        events,
+                    })
+                });
+        match watched {
+            Ok(watched) => self.players.push(watched),
+            Err(error) => self.buffer.push_back(Err(error.into())),
+        }
+    }
+
+    /// Stops watching the player that used to own the given names, if it is still being watched.
+    fn remove_player(&mut self, bus_name: &str, unique_name: &str) {
+        if let Some(index) = self.players.iter().position(|watched| {
+            watched.player.bus_name() == bus_name && watched.player.unique_name() == unique_name
+        }) {
+            let watched = self.players.remove(index);
+            self.buffer
+                .push_back(Ok((watched.player.id(), Event::PlayerShutDown)));
+        }
+    }
+
+    fn apply_name_changes(&mut self) {
+        loop {
+            let change = self.name_changes.borrow_mut().pop_front();
+            match change {
+                Some(NameChange::Acquired { bus_name }) => {
+                    self.add_player(bus_name);
+                }
+                Some(NameChange::Lost {
+                    bus_name,
+                    unique_name,
+                }) => self.remove_player(&bus_name, &unique_name),
+                None => break,
+            }
+        }
+    }
+
+    fn apply_pending_events(&mut self) {
+        let mut index = 0;
+        while index < self.players.len() {
+            let watched = &mut self.players[index];
+            let events = watched.events.take_events();
+            let shut_down = if events.is_empty() {
+                false
+            } else {
+                match watched.state.apply_events(&watched.player, events) {
+                    Ok(shut_down) => shut_down,
+                    Err(error) => {
+                        self.buffer.push_back(Err(error));
+                        false
+                    }
+                }
+            };
+
+            let id = watched.player.id();
+            while let Some(event) = watched.state.pop_event() {
+                self.buffer.push_back(Ok((id.clone(), event)));
+            }
+
+            if shut_down {
+                self.players.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl Iterator for AllPlayerEvents {
+    type Item = Result<(PlayerId, Event), EventError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(item);
+            }
+
+            self.apply_name_changes();
+            self.apply_pending_events();
+
+            if self.buffer.is_empty() {
+                self.connection.process_events_blocking_until_received();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve};
+    use crate::PlayerFinder;
+
+    #[test]
+    fn it_reads_events_of_all_players() {
+        let (bus_name, handle) = serve("mpris_rs_test_all_events");
+        let finder = PlayerFinder::new().expect("Could not connect to D-Bus");
+        let mut events = finder.all_player_events().unwrap();
+        assert!(events.players().any(|player| player.bus_name() == bus_name));
+
+        let player = connect(bus_name.clone());
+        player.play().unwrap();
+
+        // Other tests start and stop players on the same bus, so skip their events and errors.
+        let mut next_event = || loop {
+            if let Ok((id, event)) = events.next().unwrap() {
+                if id.bus_name() == bus_name {
+                    assert_eq!(id, player.id());
+                    return event;
+                }
+            }
+        };
+
+        match next_event() {
+            Event::Playing => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        player.quit().unwrap();
+        match next_event() {
+            Event::PlayerShutDown => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        handle.join().unwrap();
+        assert!(events.player(&player.id()).is_none());
+    }
+}
@@ -0,0 +1,13 @@
+//! Async versions of [`PlayerFinder`](crate::PlayerFinder), [`Player`](crate::Player) and
+//! [`PlayerEvents`](crate::PlayerEvents), built on a non-blocking D-Bus connection driven by
+//! [Tokio](https://tokio.rs).
+//!
+//! Only available when the `tokio` feature is enabled.
+
+mod event;
+mod find;
+mod player;
+
+pub use self::event::AsyncPlayerEvents;
+pub use self::find::AsyncPlayerFinder;
+pub use self::player::AsyncPlayer;
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use dbus::channel::{MatchingReceiver, Token};
+use dbus::message::{MatchRule, MessageType};
+use dbus::nonblock::{MsgMatch, SyncConnection};
+use dbus::Message;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::stream::{self, Select, Stream, StreamExt};
+use futures::FutureExt;
+
+use super::AsyncPlayer;
+use crate::event::{EventState, Reloaded};
+use crate::player::MPRIS2_PATH;
+use crate::pooled_connection::MprisMessage;
+use crate::{DBusError, Event, EventError};
+
+type Messages = Select<UnboundedReceiver<Message>, UnboundedReceiver<Message>>;
+
+/// Async version of [`PlayerEvents`](crate::PlayerEvents): a [`Stream`] of the [`Event`]s of an
+/// [`AsyncPlayer`].
+///
+/// The stream ends when the player shuts down, after one last [`Event::PlayerShutDown`] event. It
+/// also ends if the D-Bus connection is lost.
+///
+/// Dropping the stream unsubscribes from the player's signals.
+pub struct AsyncPlayerEvents {
+    stream: Pin<Box<dyn Stream<Item = Result<Event, EventError>> + Send>>,
+    connection: Arc<SyncConnection>,
+    /// The subscriptions feeding the stream. The stream ends if these are dropped.
+    matches: Vec<MsgMatch>,
+}
+
+/// The state that is carried between items of the stream.
+struct State {
+    player: AsyncPlayer,
+
+    /// Signals from the player, and from the bus about the player's name.
+    messages: Messages,
+
+    /// Queued up events and the state that new signals are diffed against.
+    events: EventState,
+
+    /// Set when the player shut down or the connection was lost.
+    finished: bool,
+}
+
+impl AsyncPlayerEvents {
+    pub(crate) async fn new(player: AsyncPlayer) -> Result<AsyncPlayerEvents, DBusError> {
+        let connection = player.connection().clone();
+
+        // Everything the player sends on the MPRIS path. `MprisMessage::try_parse` picks out the
+        // relevant signals.
+        let signals_rule = MatchRule::new()
+            .with_type(MessageType::Signal)
+            .with_sender(player.unique_name().to_owned())
+            .with_path(MPRIS2_PATH);
+        let name_owner_rule = MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged")
+            .with_sender("org.freedesktop.DBus");
+
+        let (signals_match, signals) = connection.add_match(signals_rule).await?.msg_stream();
+        let mut matches = vec![signals_match];
+
+        let name_owner = match connection.add_match(name_owner_rule).await {
+            Ok(name_owner_match) => {
+                let (name_owner_match, name_owner) = name_owner_match.msg_stream();
+                matches.push(name_owner_match);
+                name_owner
+            }
+            Err(error) => {
+                remove_matches(&connection, &matches);
+                return Err(error.into());
+            }
+        };
+
+        // Drop the subscriptions again if the initial state cannot be read.
+        let initial_state = async {
+            let last_progress = player.get_progress().await?;
+            let root_capabilities = player.get_root_capabilities().await?;
+            let track_list = player.checked_get_track_list().await?;
+            let playlists = player.get_playlists_state().await?;
+            Ok::<_, DBusError>((last_progress, root_capabilities, track_list, playlists))
+        };
+        let (last_progress, root_capabilities, track_list, playlists) = match initial_state.await {
+            Ok(state) => state,
+            Err(error) => {
+                remove_matches(&connection, &matches);
+                return Err(error);
+            }
+        };
+
+        let state = State {
+            player,
+            messages: stream::select(signals, name_owner),
+            events: EventState::from_parts(last_progress, root_capabilities, track_list, playlists),
+            finished: false,
+        };
+
+        let stream = stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_event() {
+                    return Some((Ok(event), state));
+                }
+
+                if state.finished {
+                    return None;
+                }
+
+                if let Err(error) = state.read_events().await {
+                    return Some((Err(error), state));
+                }
+            }
+        });
+
+        Ok(AsyncPlayerEvents {
+            stream: Box::pin(stream),
+            connection,
+            matches,
+        })
+    }
+}
+
+impl State {
+    /// Waits for the next signal from the player, then processes all signals that are ready.
+    async fn read_events(&mut self) -> Result<(), EventError> {
+        let mut events = Vec::new();
+        let mut next = match self.messages.next().await {
+            Some(message) => Some(message),
+            None => {
+                // The connection was lost.
+                self.finished = true;
+                return Ok(());
+            }
+        };
+        while let Some(message) = next {
+            let event = MprisMessage::try_parse(message).and_then(MprisMessage::into_event);
+            if let Some((unique_name, event)) = event {
+                if unique_name == self.player.unique_name() {
+                    events.push(event);
+                }
+            }
+            next = self.messages.next().now_or_never().flatten();
+        }
+
+        let reloads = match self.events.apply_mpris_events(events) {
+            Some(reloads) => reloads,
+            None => {
+                self.finished = true;
+                return Ok(());
+            }
+        };
+
+        let mut reloaded = Reloaded::default();
+        if reloads.root {
+            reloaded.root_capabilities = Some(self.player.get_root_capabilities().await?);
+        }
+        if reloads.progress {
+            reloaded.progress = Some(self.player.get_progress().await?);
+        }
+        if reloads.playlists {
+            reloaded.playlists = self.player.get_playlists_state().await?;
+        }
+
+        if self.events.apply_reloaded(&reloads, reloaded) {
+            let new_tracks = self.player.checked_get_track_list().await?;
+            self.events.apply_track_list(new_tracks);
+        }
+
+        Ok(())
+    }
+}
+
+impl Stream for AsyncPlayerEvents {
+    type Item = Result<Event, EventError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
+impl Drop for AsyncPlayerEvents {
+    fn drop(&mut self) {
+        remove_matches(&self.connection, &self.matches);
+    }
+}
+
+impl fmt::Debug for AsyncPlayerEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPlayerEvents").finish_non_exhaustive()
+    }
+}
+
+/// Unsubscribes from signals in the background.
+///
+/// Outside of a Tokio runtime only the local callbacks are removed; the bus keeps sending the
+/// signals until the connection is closed.
+fn remove_matches(connection: &Arc<SyncConnection>, matches: &[MsgMatch]) {
+    let tokens: Vec<Token> = matches.iter().map(MsgMatch::token).collect();
+    match tokio_crate::runtime::Handle::try_current() {
+        Ok(handle) => {
+            let connection = connection.clone();
+            handle.spawn(async move {
+                for token in tokens {
+                    let _ = connection.remove_match(token).await;
+                }
+            });
+        }
+        Err(_) => {
+            for token in tokens {
+                let _ = connection.stop_receive(token);
+            }
+        }
+    }
+}
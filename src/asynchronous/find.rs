@@ -0,0 +1,163 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::nonblock::{Proxy, SyncConnection};
+
+use super::AsyncPlayer;
+use crate::find::{FindingError, LIST_NAMES_TIMEOUT_MS};
+use crate::player::{DEFAULT_TIMEOUT_MS, MPRIS2_PREFIX};
+use crate::{DBusError, PlaybackStatus};
+
+/// Async version of [`PlayerFinder`](crate::PlayerFinder).
+///
+/// This type uses a non-blocking D-Bus connection that has to be driven by a Tokio runtime. All
+/// found [`AsyncPlayer`]s share the connection of the finder.
+#[derive(Clone)]
+pub struct AsyncPlayerFinder {
+    connection: Arc<SyncConnection>,
+    player_timeout_ms: i32,
+}
+
+impl AsyncPlayerFinder {
+    /// Creates a new [`AsyncPlayerFinder`] with a new non-blocking connection to the session bus.
+    ///
+    /// The connection is driven by a task spawned on the current Tokio runtime, which stops when
+    /// the connection is lost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn new() -> Result<Self, DBusError> {
+        let (resource, connection) = dbus_tokio::connection::new_session_sync()?;
+        tokio_crate::spawn(async move {
+            // Only completes when the connection is lost. There is no one to report that to, and
+            // all pending and future calls on the connection will fail anyway.
+            let _ = resource.await;
+        });
+        Ok(AsyncPlayerFinder::for_connection(connection))
+    }
+
+    /// Create a new [`AsyncPlayerFinder`] with the given non-blocking connection.
+    ///
+    /// You are responsible for driving the connection, e.g. by spawning the `IOResource` returned
+    /// by `dbus_tokio::connection::new_session_sync`.
+    ///
+    /// **Note:** The connection is configured to deliver signals to every matching subscription,
+    /// which the event streams of players rely on.
+    pub fn for_connection(connection: Arc<SyncConnection>) -> Self {
+        connection.set_signal_match_mode(true);
+        AsyncPlayerFinder {
+            connection,
+            player_timeout_ms: DEFAULT_TIMEOUT_MS,
+        }
+    }
+
+    /// Get the D-Bus communication timeout used for found [`AsyncPlayer`]s.
+    pub fn player_timeout_ms(&self) -> i32 {
+        self.player_timeout_ms
+    }
+
+    /// Set the D-Bus communication timeout used for found [`AsyncPlayer`]s.
+    pub fn set_player_timeout_ms(&mut self, timeout_ms: i32) {
+        self.player_timeout_ms = timeout_ms;
+    }
+
+    /// Find all available [`AsyncPlayer`]s in the connection.
+    ///
+    /// See [`PlayerFinder::find_all`](crate::PlayerFinder::find_all).
+    pub async fn find_all(&self) -> Result<Vec<AsyncPlayer>, FindingError> {
+        let mut players = Vec::new();
+        for bus_name in self.all_player_buses().await? {
+            players.push(self.player(bus_name).await?);
+        }
+        Ok(players)
+    }
+
+    /// Try to find the first available [`AsyncPlayer`] in the connection.
+    ///
+    /// See [`PlayerFinder::find_first`](crate::PlayerFinder::find_first).
+    pub async fn find_first(&self) -> Result<AsyncPlayer, FindingError> {
+        match self.all_player_buses().await?.into_iter().next() {
+            Some(bus_name) => Ok(self.player(bus_name).await?),
+            None => Err(FindingError::NoPlayerFound),
+        }
+    }
+
+    /// Try to find the "active" [`AsyncPlayer`] in the connection.
+    ///
+    /// Players are preferred in the same order as in
+    /// [`PlayerFinder::find_active`](crate::PlayerFinder::find_active): the first playing one,
+    /// then the first paused one, then the first one with a track, and finally the first one.
+    pub async fn find_active(&self) -> Result<AsyncPlayer, FindingError> {
+        let mut first_paused: Option<AsyncPlayer> = None;
+        let mut first_with_track: Option<AsyncPlayer> = None;
+        let mut first_found: Option<AsyncPlayer> = None;
+
+        for bus_name in self.all_player_buses().await? {
+            let player = self.player(bus_name).await?;
+            let player_status = player.get_playback_status().await?;
+
+            if player_status == PlaybackStatus::Playing {
+                return Ok(player);
+            }
+
+            if first_paused.is_none() && player_status == PlaybackStatus::Paused {
+                first_paused.replace(player);
+            } else if first_with_track.is_none() && !player.get_metadata().await?.is_empty() {
+                first_with_track.replace(player);
+            } else if first_found.is_none() {
+                first_found.replace(player);
+            }
+        }
+
+        first_paused
+            .or(first_with_track)
+            .or(first_found)
+            .ok_or(FindingError::NoPlayerFound)
+    }
+
+    /// Try to find an [`AsyncPlayer`] by its MPRIS `Identity`, compared case-insensitively.
+    ///
+    /// See [`PlayerFinder::find_by_name`](crate::PlayerFinder::find_by_name).
+    pub async fn find_by_name(&self, name: &str) -> Result<AsyncPlayer, FindingError> {
+        for bus_name in self.all_player_buses().await? {
+            let player = self.player(bus_name).await?;
+            if player.identity().to_lowercase() == name.to_lowercase() {
+                return Ok(player);
+            }
+        }
+        Err(FindingError::NoPlayerFound)
+    }
+
+    async fn player(&self, bus_name: String) -> Result<AsyncPlayer, DBusError> {
+        AsyncPlayer::new(self.connection.clone(), bus_name, self.player_timeout_ms).await
+    }
+
+    async fn all_player_buses(&self) -> Result<Vec<String>, DBusError> {
+        let proxy = Proxy::new(
+            "org.freedesktop.DBus",
+            "/",
+            Duration::from_millis(LIST_NAMES_TIMEOUT_MS as u64),
+            &*self.connection,
+        );
+        let (names,): (Vec<String>,) = proxy
+            .method_call("org.freedesktop.DBus", "ListNames", ())
+            .await?;
+
+        let mut all_busses = names
+            .into_iter()
+            .filter(|name| name.starts_with(MPRIS2_PREFIX))
+            .collect::<Vec<String>>();
+        all_busses.sort_by_key(|a| a.to_lowercase());
+        Ok(all_busses)
+    }
+}
+
+impl fmt::Debug for AsyncPlayerFinder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPlayerFinder")
+            .field("player_timeout_ms", &self.player_timeout_ms)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,1052 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::arg::{Append, Arg, Get, ReadAll};
+use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
+use dbus::nonblock::{Proxy, SyncConnection};
+use dbus::strings::Path;
+
+use super::AsyncPlayerEvents;
+use crate::event::PlaylistsState;
+use crate::extensions::DurationExtensions;
+use crate::metadata::Value;
+use crate::player::{handle_optional_property, MPRIS2_PATH, MPRIS2_PREFIX};
+use crate::uri::{check_uri, OpenUriError};
+use crate::{
+    Capabilities, DBusError, LoopStatus, Metadata, PlaybackStatus, Playlist, PlaylistOrdering,
+    Progress, TrackID, TrackList,
+};
+
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_LIST_INTERFACE: &str = "org.mpris.MediaPlayer2.TrackList";
+const PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+
+/// Async version of [`Player`](crate::Player).
+///
+/// Every query and command is an `async fn` that does not block the thread while waiting for the
+/// player to reply. The methods behave exactly like their namesakes on
+/// [`Player`](crate::Player), so see the documentation over there for the details.
+///
+/// An [`AsyncPlayer`] is cheap to clone, and can be shared between tasks and threads.
+#[derive(Clone)]
+pub struct AsyncPlayer {
+    connection: Arc<SyncConnection>,
+    bus_name: String,
+    unique_name: String,
+    identity: String,
+    timeout_ms: i32,
+    has_tracklist_interface: bool,
+    has_playlists_interface: bool,
+}
+
+impl AsyncPlayer {
+    /// Create a new [`AsyncPlayer`] using a non-blocking D-Bus connection and an address.
+    ///
+    /// Most likely you want to use [`AsyncPlayerFinder`](super::AsyncPlayerFinder) instead.
+    pub async fn new(
+        connection: Arc<SyncConnection>,
+        bus_name: String,
+        timeout_ms: i32,
+    ) -> Result<AsyncPlayer, DBusError> {
+        let mut player = AsyncPlayer {
+            connection,
+            bus_name,
+            unique_name: String::new(),
+            identity: String::new(),
+            timeout_ms,
+            has_tracklist_interface: false,
+            has_playlists_interface: false,
+        };
+
+        player.identity = player.get(ROOT_INTERFACE, "Identity").await?;
+
+        let bus_proxy = Proxy::new(
+            "org.freedesktop.DBus",
+            "/",
+            player.timeout(),
+            &*player.connection,
+        );
+        let unique_name: Result<(String,), _> = bus_proxy
+            .method_call("org.freedesktop.DBus", "GetNameOwner", (&player.bus_name,))
+            .await;
+        player.unique_name = unique_name.map(|(name,)| name).map_err(|_| {
+            DBusError::Miscellaneous(String::from(
+                "Could not determine player's unique name. Did it exit during initialization?",
+            ))
+        })?;
+
+        // See `introspect` in the `player` module for why this is a substring search.
+        let interfaces: Result<(String,), _> = player
+            .call("org.freedesktop.DBus.Introspectable", "Introspect", ())
+            .await;
+        let interfaces = interfaces.map(|(xml,)| xml).unwrap_or_default();
+        player.has_tracklist_interface = interfaces.contains(TRACK_LIST_INTERFACE);
+        player.has_playlists_interface = interfaces.contains(PLAYLISTS_INTERFACE);
+
+        Ok(player)
+    }
+
+    /// Returns the current D-Bus communication timeout (in milliseconds).
+    pub fn dbus_timeout_ms(&self) -> i32 {
+        self.timeout_ms
+    }
+
+    /// Change the D-Bus communication timeout.
+    pub fn set_dbus_timeout_ms(&mut self, timeout_ms: i32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Returns the player's D-Bus bus name.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// Returns the player's bus name without the MPRIS2 prefix.
+    ///
+    /// See [`Player::bus_name_trimmed`](crate::Player::bus_name_trimmed).
+    pub fn bus_name_trimmed(&self) -> &str {
+        self.bus_name().trim_start_matches(MPRIS2_PREFIX)
+    }
+
+    /// Returns the player's unique D-Bus bus name (usually something like `:1.1337`).
+    pub fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+
+    /// Returns the player's MPRIS `Identity`.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Returns [`true`] if the player implements the `org.mpris.MediaPlayer2.TrackList`
+    /// interface.
+    pub fn supports_track_lists(&self) -> bool {
+        self.has_tracklist_interface
+    }
+
+    /// Returns [`true`] if the player implements the `org.mpris.MediaPlayer2.Playlists`
+    /// interface.
+    pub fn supports_playlists(&self) -> bool {
+        self.has_playlists_interface
+    }
+
+    /// Returns a [`Stream`](futures::Stream) of [`Event`](crate::Event)s of this player.
+    ///
+    /// See [`AsyncPlayerEvents`].
+    pub async fn events(&self) -> Result<AsyncPlayerEvents, DBusError> {
+        AsyncPlayerEvents::new(self.clone()).await
+    }
+
+    /// Checks if the player is still connected.
+    pub async fn is_running(&self) -> bool {
+        let bus_proxy = Proxy::new(
+            "org.freedesktop.DBus",
+            "/",
+            self.timeout(),
+            &*self.connection,
+        );
+        let has_owner: Result<(bool,), _> = bus_proxy
+            .method_call("org.freedesktop.DBus", "NameHasOwner", (&self.bus_name,))
+            .await;
+        has_owner.map(|(has_owner,)| has_owner).unwrap_or(false)
+    }
+
+    /// See [`Player::get_capabilities`](crate::Player::get_capabilities).
+    pub async fn get_capabilities(&self) -> Result<Capabilities, DBusError> {
+        let root = self.get_root_capabilities().await?;
+        let player = self.get_all_properties(PLAYER_INTERFACE).await?;
+        Ok(root | Capabilities::from_player_properties(&player))
+    }
+
+    //
+    // org.mpris.MediaPlayer2
+    //
+
+    /// See [`Player::get_desktop_entry`](crate::Player::get_desktop_entry).
+    pub async fn get_desktop_entry(&self) -> Result<Option<String>, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "DesktopEntry").await
+    }
+
+    /// See [`Player::get_supported_mime_types`](crate::Player::get_supported_mime_types).
+    pub async fn get_supported_mime_types(&self) -> Result<Vec<String>, DBusError> {
+        self.get(ROOT_INTERFACE, "SupportedMimeTypes").await
+    }
+
+    /// See [`Player::get_supported_uri_schemes`](crate::Player::get_supported_uri_schemes).
+    pub async fn get_supported_uri_schemes(&self) -> Result<Vec<String>, DBusError> {
+        self.get(ROOT_INTERFACE, "SupportedUriSchemes").await
+    }
+
+    /// See [`Player::get_has_track_list`](crate::Player::get_has_track_list).
+    pub async fn get_has_track_list(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "HasTrackList").await
+    }
+
+    /// See [`Player::can_raise`](crate::Player::can_raise).
+    pub async fn can_raise(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "CanRaise").await
+    }
+
+    /// See [`Player::can_quit`](crate::Player::can_quit).
+    pub async fn can_quit(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "CanQuit").await
+    }
+
+    /// See [`Player::can_set_fullscreen`](crate::Player::can_set_fullscreen).
+    pub async fn can_set_fullscreen(&self) -> Result<bool, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "CanSetFullscreen")
+            .await
+            .map(|o| o.unwrap_or(false))
+    }
+
+    /// See [`Player::get_fullscreen`](crate::Player::get_fullscreen).
+    pub async fn get_fullscreen(&self) -> Result<Option<bool>, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "Fullscreen").await
+    }
+
+    /// See [`Player::set_fullscreen`](crate::Player::set_fullscreen).
+    pub async fn set_fullscreen(&self, new_state: bool) -> Result<bool, DBusError> {
+        handle_optional_property(self.set(ROOT_INTERFACE, "Fullscreen", new_state).await)
+            .map(|o| o.is_some())
+    }
+
+    /// See [`Player::raise`](crate::Player::raise).
+    pub async fn raise(&self) -> Result<(), DBusError> {
+        self.call(ROOT_INTERFACE, "Raise", ()).await
+    }
+
+    /// See [`Player::checked_raise`](crate::Player::checked_raise).
+    pub async fn checked_raise(&self) -> Result<bool, DBusError> {
+        if self.can_raise().await? {
+            self.raise().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::quit`](crate::Player::quit).
+    pub async fn quit(&self) -> Result<(), DBusError> {
+        self.call(ROOT_INTERFACE, "Quit", ()).await
+    }
+
+    /// See [`Player::checked_quit`](crate::Player::checked_quit).
+    pub async fn checked_quit(&self) -> Result<bool, DBusError> {
+        if self.can_quit().await? {
+            self.quit().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    //
+    // org.mpris.MediaPlayer2.Player
+    //
+
+    /// See [`Player::get_playback_status`](crate::Player::get_playback_status).
+    pub async fn get_playback_status(&self) -> Result<PlaybackStatus, DBusError> {
+        self.get::<String>(PLAYER_INTERFACE, "PlaybackStatus")
+            .await?
+            .parse()
+            .map_err(DBusError::from)
+    }
+
+    /// See [`Player::get_metadata`](crate::Player::get_metadata).
+    pub async fn get_metadata(&self) -> Result<Metadata, DBusError> {
+        self.get::<HashMap<String, Value>>(PLAYER_INTERFACE, "Metadata")
+            .await
+            .map(Metadata::from)
+    }
+
+    /// See [`Player::get_position`](crate::Player::get_position).
+    pub async fn get_position(&self) -> Result<Duration, DBusError> {
+        self.get_position_in_microseconds()
+            .await
+            .map(Duration::from_micros_ext)
+    }
+
+    /// See [`Player::checked_get_position`](crate::Player::checked_get_position).
+    pub async fn checked_get_position(&self) -> Result<Option<Duration>, DBusError> {
+        if self.has_position().await? {
+            Ok(Some(self.get_position().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::get_position_in_microseconds`](crate::Player::get_position_in_microseconds).
+    pub async fn get_position_in_microseconds(&self) -> Result<u64, DBusError> {
+        self.get::<i64>(PLAYER_INTERFACE, "Position")
+            .await
+            .map(|p| p as u64)
+    }
+
+    /// See [`Player::set_position`](crate::Player::set_position).
+    pub async fn set_position(
+        &self,
+        track_id: TrackID,
+        position: &Duration,
+    ) -> Result<(), DBusError> {
+        self.set_position_in_microseconds(track_id, DurationExtensions::as_micros(position))
+            .await
+    }
+
+    /// See [`Player::checked_set_position`](crate::Player::checked_set_position).
+    pub async fn checked_set_position(
+        &self,
+        track_id: TrackID,
+        position: &Duration,
+    ) -> Result<bool, DBusError> {
+        if self.can_control().await? && self.has_position().await? {
+            self.set_position(track_id, position).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::set_position_in_microseconds`](crate::Player::set_position_in_microseconds).
+    pub async fn set_position_in_microseconds(
+        &self,
+        track_id: TrackID,
+        position_in_us: u64,
+    ) -> Result<(), DBusError> {
+        self.call(
+            PLAYER_INTERFACE,
+            "SetPosition",
+            (track_id.as_path(), position_in_us as i64),
+        )
+        .await
+    }
+
+    /// See [`Player::get_playback_rate`](crate::Player::get_playback_rate).
+    pub async fn get_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "Rate").await
+    }
+
+    /// See [`Player::checked_get_playback_rate`](crate::Player::checked_get_playback_rate).
+    pub async fn checked_get_playback_rate(&self) -> Result<Option<f64>, DBusError> {
+        if self.has_playback_rate().await? {
+            Ok(Some(self.get_playback_rate().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_playback_rate`](crate::Player::set_playback_rate).
+    pub async fn set_playback_rate(&self, rate: f64) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Rate", rate).await
+    }
+
+    /// See [`Player::checked_set_playback_rate`](crate::Player::checked_set_playback_rate).
+    pub async fn checked_set_playback_rate(&self, rate: f64) -> Result<bool, DBusError> {
+        if self.can_control().await? && self.has_playback_rate().await? {
+            self.set_playback_rate(rate).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_minimum_playback_rate`](crate::Player::get_minimum_playback_rate).
+    pub async fn get_minimum_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "MinimumRate").await
+    }
+
+    /// See [`Player::get_maximum_playback_rate`](crate::Player::get_maximum_playback_rate).
+    pub async fn get_maximum_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "MaximumRate").await
+    }
+
+    /// See [`Player::get_valid_playback_rate_range`](crate::Player::get_valid_playback_rate_range).
+    pub async fn get_valid_playback_rate_range(&self) -> Result<Range<f64>, DBusError> {
+        let min = self.get_minimum_playback_rate().await?;
+        let max = self.get_maximum_playback_rate().await?;
+        Ok(min..max)
+    }
+
+    /// See [`Player::get_shuffle`](crate::Player::get_shuffle).
+    pub async fn get_shuffle(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "Shuffle").await
+    }
+
+    /// See [`Player::checked_get_shuffle`](crate::Player::checked_get_shuffle).
+    pub async fn checked_get_shuffle(&self) -> Result<Option<bool>, DBusError> {
+        if self.can_shuffle().await? {
+            Ok(Some(self.get_shuffle().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_shuffle`](crate::Player::set_shuffle).
+    pub async fn set_shuffle(&self, state: bool) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Shuffle", state).await
+    }
+
+    /// See [`Player::checked_set_shuffle`](crate::Player::checked_set_shuffle).
+    pub async fn checked_set_shuffle(&self, state: bool) -> Result<bool, DBusError> {
+        if self.can_control().await? && self.can_shuffle().await? {
+            self.set_shuffle(state).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_loop_status`](crate::Player::get_loop_status).
+    pub async fn get_loop_status(&self) -> Result<LoopStatus, DBusError> {
+        self.get::<String>(PLAYER_INTERFACE, "LoopStatus")
+            .await?
+            .parse()
+            .map_err(DBusError::from)
+    }
+
+    /// See [`Player::checked_get_loop_status`](crate::Player::checked_get_loop_status).
+    pub async fn checked_get_loop_status(&self) -> Result<Option<LoopStatus>, DBusError> {
+        if self.can_loop().await? {
+            Ok(Some(self.get_loop_status().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_loop_status`](crate::Player::set_loop_status).
+    pub async fn set_loop_status(&self, status: LoopStatus) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "LoopStatus", status.dbus_value())
+            .await
+    }
+
+    /// See [`Player::checked_set_loop_status`](crate::Player::checked_set_loop_status).
+    pub async fn checked_set_loop_status(&self, status: LoopStatus) -> Result<bool, DBusError> {
+        if self.can_control().await? && self.can_loop().await? {
+            self.set_loop_status(status).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_volume`](crate::Player::get_volume).
+    pub async fn get_volume(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "Volume").await
+    }
+
+    /// See [`Player::checked_get_volume`](crate::Player::checked_get_volume).
+    pub async fn checked_get_volume(&self) -> Result<Option<f64>, DBusError> {
+        if self.has_volume().await? {
+            Ok(Some(self.get_volume().await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_volume`](crate::Player::set_volume).
+    pub async fn set_volume(&self, value: f64) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Volume", value.max(0.0)).await
+    }
+
+    /// See [`Player::checked_set_volume`](crate::Player::checked_set_volume).
+    pub async fn checked_set_volume(&self, volume: f64) -> Result<bool, DBusError> {
+        if self.can_control().await? && self.has_volume().await? {
+            self.set_volume(volume).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::can_control`](crate::Player::can_control).
+    pub async fn can_control(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanControl").await
+    }
+
+    /// See [`Player::can_go_next`](crate::Player::can_go_next).
+    pub async fn can_go_next(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanGoNext").await
+    }
+
+    /// See [`Player::can_go_previous`](crate::Player::can_go_previous).
+    pub async fn can_go_previous(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanGoPrevious").await
+    }
+
+    /// See [`Player::can_pause`](crate::Player::can_pause).
+    pub async fn can_pause(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanPause").await
+    }
+
+    /// See [`Player::can_play`](crate::Player::can_play).
+    pub async fn can_play(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanPlay").await
+    }
+
+    /// See [`Player::can_seek`](crate::Player::can_seek).
+    pub async fn can_seek(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanSeek").await
+    }
+
+    /// See [`Player::can_stop`](crate::Player::can_stop).
+    pub async fn can_stop(&self) -> Result<bool, DBusError> {
+        self.can_control().await
+    }
+
+    /// See [`Player::can_set_playback_rate`](crate::Player::can_set_playback_rate).
+    pub async fn can_set_playback_rate(&self) -> Result<bool, DBusError> {
+        self.get_valid_playback_rate_range()
+            .await
+            .map(|range| range.start < 1.0 || range.end > 1.0)
+    }
+
+    /// See [`Player::can_shuffle`](crate::Player::can_shuffle).
+    pub async fn can_shuffle(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Shuffle").await
+    }
+
+    /// See [`Player::can_loop`](crate::Player::can_loop).
+    pub async fn can_loop(&self) -> Result<bool, DBusError> {
+        self.has_player_property("LoopStatus").await
+    }
+
+    /// See [`Player::has_playback_rate`](crate::Player::has_playback_rate).
+    pub async fn has_playback_rate(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Rate").await
+    }
+
+    /// See [`Player::has_position`](crate::Player::has_position).
+    pub async fn has_position(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Position").await
+    }
+
+    /// See [`Player::has_volume`](crate::Player::has_volume).
+    pub async fn has_volume(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Volume").await
+    }
+
+    /// See [`Player::play_pause`](crate::Player::play_pause).
+    pub async fn play_pause(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "PlayPause", ()).await
+    }
+
+    /// See [`Player::checked_play_pause`](crate::Player::checked_play_pause).
+    pub async fn checked_play_pause(&self) -> Result<bool, DBusError> {
+        if self.can_pause().await? {
+            self.play_pause().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::play`](crate::Player::play).
+    pub async fn play(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Play", ()).await
+    }
+
+    /// See [`Player::checked_play`](crate::Player::checked_play).
+    pub async fn checked_play(&self) -> Result<bool, DBusError> {
+        if self.can_play().await? {
+            self.play().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::pause`](crate::Player::pause).
+    pub async fn pause(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Pause", ()).await
+    }
+
+    /// See [`Player::checked_pause`](crate::Player::checked_pause).
+    pub async fn checked_pause(&self) -> Result<bool, DBusError> {
+        if self.can_pause().await? {
+            self.pause().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::stop`](crate::Player::stop).
+    pub async fn stop(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Stop", ()).await
+    }
+
+    /// See [`Player::checked_stop`](crate::Player::checked_stop).
+    pub async fn checked_stop(&self) -> Result<bool, DBusError> {
+        if self.can_stop().await? {
+            self.stop().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::next`](crate::Player::next).
+    pub async fn next(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Next", ()).await
+    }
+
+    /// See [`Player::checked_next`](crate::Player::checked_next).
+    pub async fn checked_next(&self) -> Result<bool, DBusError> {
+        if self.can_go_next().await? {
+            self.next().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::previous`](crate::Player::previous).
+    pub async fn previous(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Previous", ()).await
+    }
+
+    /// See [`Player::checked_previous`](crate::Player::checked_previous).
+    pub async fn checked_previous(&self) -> Result<bool, DBusError> {
+        if self.can_go_previous().await? {
+            self.previous().await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek`](crate::Player::seek).
+    pub async fn seek(&self, offset_in_microseconds: i64) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Seek", (offset_in_microseconds,))
+            .await
+    }
+
+    /// See [`Player::checked_seek`](crate::Player::checked_seek).
+    pub async fn checked_seek(&self, offset_in_microseconds: i64) -> Result<bool, DBusError> {
+        if self.can_seek().await? {
+            self.seek(offset_in_microseconds).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek_forwards`](crate::Player::seek_forwards).
+    pub async fn seek_forwards(&self, offset: &Duration) -> Result<(), DBusError> {
+        self.seek(DurationExtensions::as_micros(offset) as i64)
+            .await
+    }
+
+    /// See [`Player::checked_seek_forwards`](crate::Player::checked_seek_forwards).
+    pub async fn checked_seek_forwards(&self, offset: &Duration) -> Result<bool, DBusError> {
+        if self.can_seek().await? {
+            self.seek_forwards(offset).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek_backwards`](crate::Player::seek_backwards).
+    pub async fn seek_backwards(&self, offset: &Duration) -> Result<(), DBusError> {
+        self.seek(-(DurationExtensions::as_micros(offset) as i64))
+            .await
+    }
+
+    /// See [`Player::checked_seek_backwards`](crate::Player::checked_seek_backwards).
+    pub async fn checked_seek_backwards(&self, offset: &Duration) -> Result<bool, DBusError> {
+        if self.can_seek().await? {
+            self.seek_backwards(offset).await.map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::open_uri`](crate::Player::open_uri).
+    pub async fn open_uri(&self, uri: &str) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "OpenUri", (uri,)).await
+    }
+
+    /// See [`Player::checked_open_uri`](crate::Player::checked_open_uri).
+    pub async fn checked_open_uri(&self, uri: &str) -> Result<(), OpenUriError> {
+        check_uri(
+            uri,
+            &self.get_supported_uri_schemes().await?,
+            &self.get_supported_mime_types().await?,
+        )?;
+        self.open_uri(uri).await.map_err(OpenUriError::from)
+    }
+
+    //
+    // org.mpris.MediaPlayer2.TrackList
+    //
+
+    /// See [`Player::get_track_list`](crate::Player::get_track_list).
+    pub async fn get_track_list(&self) -> Result<TrackList, DBusError> {
+        self.get::<Vec<Path<'static>>>(TRACK_LIST_INTERFACE, "Tracks")
+            .await
+            .map(TrackList::from)
+    }
+
+    /// See [`Player::checked_get_track_list`](crate::Player::checked_get_track_list).
+    pub async fn checked_get_track_list(&self) -> Result<Option<TrackList>, DBusError> {
+        if self.supports_track_lists() && self.get_has_track_list().await? {
+            self.get_track_list().await.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::can_edit_tracks`](crate::Player::can_edit_tracks).
+    pub async fn can_edit_tracks(&self) -> Result<bool, DBusError> {
+        self.get(TRACK_LIST_INTERFACE, "CanEditTracks").await
+    }
+
+    /// See [`Player::checked_can_edit_tracks`](crate::Player::checked_can_edit_tracks).
+    pub async fn checked_can_edit_tracks(&self) -> bool {
+        if self.supports_track_lists() {
+            self.can_edit_tracks().await.unwrap_or(false)
+        } else {
+            false
+        }
+    }
+
+    /// See [`Player::get_tracks_metadata`](crate::Player::get_tracks_metadata).
+    pub async fn get_tracks_metadata(
+        &self,
+        track_ids: &[TrackID],
+    ) -> Result<Vec<Metadata>, DBusError> {
+        let paths: Vec<Path<'_>> = track_ids.iter().map(TrackID::as_path).collect();
+        let (metadata,): (Vec<HashMap<String, Value>>,) = self
+            .call(TRACK_LIST_INTERFACE, "GetTracksMetadata", (paths,))
+            .await?;
+
+        if metadata.len() == track_ids.len() {
+            Ok(metadata.into_iter().map(Metadata::from).collect())
+        } else {
+            Err(DBusError::Miscellaneous(format!(
+                "Expected {} tracks, but got {} tracks returned.",
+                track_ids.len(),
+                metadata.len()
+            )))
+        }
+    }
+
+    /// See [`Player::get_track_metadata`](crate::Player::get_track_metadata).
+    pub async fn get_track_metadata(&self, track_id: &TrackID) -> Result<Metadata, DBusError> {
+        let mut result = self
+            .get_tracks_metadata(std::slice::from_ref(track_id))
+            .await?;
+        result.pop().ok_or_else(|| {
+            DBusError::Miscellaneous(format!("Player gave no Metadata for {}", track_id))
+        })
+    }
+
+    /// See [`Player::go_to`](crate::Player::go_to).
+    pub async fn go_to(&self, track_id: &TrackID) -> Result<(), DBusError> {
+        self.call(TRACK_LIST_INTERFACE, "GoTo", (track_id.as_path(),))
+            .await
+    }
+
+    /// See [`Player::add_track`](crate::Player::add_track).
+    pub async fn add_track(
+        &self,
+        uri: &str,
+        after: &TrackID,
+        set_as_current: bool,
+    ) -> Result<(), DBusError> {
+        self.call(
+            TRACK_LIST_INTERFACE,
+            "AddTrack",
+            (uri, after.as_path(), set_as_current),
+        )
+        .await
+    }
+
+    /// See [`Player::add_track_at_start`](crate::Player::add_track_at_start).
+    pub async fn add_track_at_start(
+        &self,
+        uri: &str,
+        set_as_current: bool,
+    ) -> Result<(), DBusError> {
+        let no_track = Path::from(crate::track_list::NO_TRACK);
+        self.call(
+            TRACK_LIST_INTERFACE,
+            "AddTrack",
+            (uri, no_track, set_as_current),
+        )
+        .await
+    }
+
+    /// See [`Player::remove_track`](crate::Player::remove_track).
+    pub async fn remove_track(&self, track_id: &TrackID) -> Result<(), DBusError> {
+        self.call(TRACK_LIST_INTERFACE, "RemoveTrack", (track_id.as_path(),))
+            .await
+    }
+
+    //
+    // org.mpris.MediaPlayer2.Playlists
+    //
+
+    /// See [`Player::get_playlists`](crate::Player::get_playlists).
+    pub async fn get_playlists(
+        &self,
+        index: u32,
+        max_count: u32,
+        ordering: PlaylistOrdering,
+        reverse: bool,
+    ) -> Result<Vec<Playlist>, DBusError> {
+        let (playlists,): (Vec<(Path<'static>, String, String)>,) = self
+            .call(
+                PLAYLISTS_INTERFACE,
+                "GetPlaylists",
+                (index, max_count, ordering.dbus_value(), reverse),
+            )
+            .await?;
+        Ok(playlists.into_iter().map(Playlist::from).collect())
+    }
+
+    /// See [`Player::activate_playlist`](crate::Player::activate_playlist).
+    pub async fn activate_playlist(&self, playlist: &Playlist) -> Result<(), DBusError> {
+        self.call(
+            PLAYLISTS_INTERFACE,
+            "ActivatePlaylist",
+            (playlist.as_path(),),
+        )
+        .await
+    }
+
+    /// See [`Player::get_playlist_count`](crate::Player::get_playlist_count).
+    pub async fn get_playlist_count(&self) -> Result<u32, DBusError> {
+        self.get(PLAYLISTS_INTERFACE, "PlaylistCount").await
+    }
+
+    /// See [`Player::get_playlist_orderings`](crate::Player::get_playlist_orderings).
+    pub async fn get_playlist_orderings(&self) -> Result<Vec<PlaylistOrdering>, DBusError> {
+        self.get::<Vec<String>>(PLAYLISTS_INTERFACE, "Orderings")
+            .await?
+            .iter()
+            .map(|ordering| ordering.parse().map_err(DBusError::from))
+            .collect()
+    }
+
+    /// See [`Player::get_active_playlist`](crate::Player::get_active_playlist).
+    pub async fn get_active_playlist(&self) -> Result<Option<Playlist>, DBusError> {
+        let (valid, playlist) = self
+            .get::<(bool, (Path<'static>, String, String))>(PLAYLISTS_INTERFACE, "ActivePlaylist")
+            .await?;
+        if valid {
+            Ok(Some(Playlist::from(playlist)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    //
+    // Internals
+    //
+
+    async fn get_all_properties(
+        &self,
+        interface: &str,
+    ) -> Result<HashMap<String, Value>, DBusError> {
+        let (properties,): (HashMap<String, Value>,) = self
+            .call("org.freedesktop.DBus.Properties", "GetAll", (interface,))
+            .await?;
+        Ok(properties)
+    }
+
+    /// Reads all properties of the `org.mpris.MediaPlayer2.Player` interface at once.
+    pub(crate) async fn get_progress(&self) -> Result<Progress, DBusError> {
+        self.get_all_properties(PLAYER_INTERFACE)
+            .await
+            .and_then(Progress::from_properties)
+    }
+
+    /// Reads the capabilities of the `org.mpris.MediaPlayer2` interface.
+    pub(crate) async fn get_root_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_all_properties(ROOT_INTERFACE)
+            .await
+            .map(|properties| Capabilities::from_root_properties(&properties))
+    }
+
+    pub(crate) async fn get_playlists_state(&self) -> Result<Option<PlaylistsState>, DBusError> {
+        if !self.supports_playlists() {
+            return Ok(None);
+        }
+
+        Ok(Some(PlaylistsState {
+            active_playlist: self.get_active_playlist().await?,
+            playlist_count: self.get_playlist_count().await?,
+        }))
+    }
+
+    pub(crate) fn connection(&self) -> &Arc<SyncConnection> {
+        &self.connection
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.max(0) as u64)
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(
+            self.bus_name.as_str(),
+            MPRIS2_PATH,
+            self.timeout(),
+            &*self.connection,
+        )
+    }
+
+    async fn call<A, R>(&self, interface: &str, method: &str, args: A) -> Result<R, DBusError>
+    where
+        A: dbus::arg::AppendAll,
+        R: ReadAll + 'static,
+    {
+        self.proxy()
+            .method_call(interface, method, args)
+            .await
+            .map_err(|e| DBusError::from_call(e, method))
+    }
+
+    async fn get<T>(&self, interface: &str, property: &str) -> Result<T, DBusError>
+    where
+        T: for<'b> Get<'b> + 'static,
+    {
+        self.proxy()
+            .get(interface, property)
+            .await
+            .map_err(|e| DBusError::from_call(e, property))
+    }
+
+    async fn get_optional<T>(&self, interface: &str, property: &str) -> Result<Option<T>, DBusError>
+    where
+        T: for<'b> Get<'b> + 'static,
+    {
+        handle_optional_property(self.get(interface, property).await)
+    }
+
+    async fn set<T>(&self, interface: &str, property: &str, value: T) -> Result<(), DBusError>
+    where
+        T: Arg + Append,
+    {
+        self.proxy()
+            .set(interface, property, value)
+            .await
+            .map_err(|e| DBusError::from_call(e, property))
+    }
+
+    async fn has_player_property(&self, property: &str) -> Result<bool, DBusError> {
+        self.proxy()
+            .get_all(PLAYER_INTERFACE)
+            .await
+            .map(|props| props.contains_key(property))
+            .map_err(|e| DBusError::from_call(e, "GetAll"))
+    }
+}
+
+impl fmt::Debug for AsyncPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPlayer")
+            .field("bus_name", &self.bus_name)
+            .field("unique_name", &self.unique_name)
+            .field("identity", &self.identity)
+            .field("timeout_ms", &self.timeout_ms)
+            .field("has_tracklist_interface", &self.has_tracklist_interface)
+            .field("has_playlists_interface", &self.has_playlists_interface)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::serve;
+    use crate::{AsyncPlayerFinder, Event};
+    use futures::StreamExt;
+    use std::future::Future;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio_crate::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Could not start a runtime")
+            .block_on(future)
+    }
+
+    async fn connect(bus_name: String) -> AsyncPlayer {
+        let (resource, connection) = dbus_tokio::connection::new_session_sync()
+            .expect("Could not open a D-Bus session connection");
+        tokio_crate::spawn(async move {
+            let _ = resource.await;
+        });
+        AsyncPlayer::new(connection, bus_name, 500)
+            .await
+            .expect("Could not connect to server")
+    }
+
+    #[test]
+    fn it_controls_players() {
+        let (bus_name, handle) = serve("mpris_rs_test_async");
+
+        block_on(async {
+            let finder = AsyncPlayerFinder::new().unwrap();
+            let players = finder.find_all().await.unwrap();
+            let player = players
+                .into_iter()
+                .find(|player| player.bus_name() == bus_name)
+                .expect("Could not find server");
+
+            assert_eq!(player.identity(), "Test Player");
+            assert!(!player.supports_track_lists());
+            assert_eq!(
+                player.get_playback_status().await.unwrap(),
+                PlaybackStatus::Paused
+            );
+            assert_eq!(
+                player.get_metadata().await.unwrap().title(),
+                Some("Riverside")
+            );
+            assert_eq!(player.checked_get_shuffle().await.unwrap(), None);
+            assert_eq!(player.get_fullscreen().await.unwrap(), None);
+
+            assert!(player.checked_play().await.unwrap());
+            player.set_volume(0.25).await.unwrap();
+            player.seek_forwards(&Duration::from_secs(5)).await.unwrap();
+            player.checked_open_uri("file:///a.mp3").await.unwrap();
+            assert!(matches!(
+                player.checked_open_uri("smb://server/a.mp3").await,
+                Err(OpenUriError::UnsupportedScheme(_))
+            ));
+            assert!(player.is_running().await);
+            assert!(player.checked_quit().await.unwrap());
+        });
+
+        let backend = handle.join().unwrap();
+        assert!(backend.playing);
+        assert!((backend.volume - 0.25).abs() < f64::EPSILON);
+        assert_eq!(backend.position, Duration::from_secs(5));
+        assert_eq!(backend.opened_uris, vec![String::from("file:///a.mp3")]);
+    }
+
+    #[test]
+    fn it_streams_events() {
+        let (bus_name, handle) = serve("mpris_rs_test_async_events");
+
+        block_on(async {
+            let player = connect(bus_name).await;
+            let mut events = player.events().await.unwrap();
+
+            player.play().await.unwrap();
+            match events.next().await {
+                Some(Ok(Event::Playing)) => {}
+                other => panic!("Unexpected event: {:?}", other),
+            }
+
+            player.set_volume(0.5).await.unwrap();
+            match events.next().await {
+                Some(Ok(Event::VolumeChanged(volume))) => {
+                    assert!((volume - 0.5).abs() < f64::EPSILON)
+                }
+                other => panic!("Unexpected event: {:?}", other),
+            }
+
+            player.quit().await.unwrap();
+            assert!(matches!(
+                events.next().await,
+                Some(Ok(Event::PlayerShutDown))
+            ));
+            assert!(events.next().await.is_none());
+        });
+
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+use crate::player::MPRIS2_PREFIX;
+
+/// The parts of a player's bus name: the application, and the instance of it if several can run
+/// at once.
+///
+/// The [MPRIS2 specification][bus_names] lets players append any unique suffix to their bus name
+/// when more than one instance is running, without saying how to recognize it. Parsing is
+/// therefore a best guess, using these rules in order:
+///
+/// 1. If the player's [desktop entry](crate::Player::get_desktop_entry) is known and the bus name
+///    starts with it, the desktop entry is the application and anything after it is the instance.
+///    This is the only way to parse names like `io.github.celluloid_player.Celluloid` correctly if
+///    they happen to end in something that looks like an instance.
+/// 2. If the last dot-separated part of the bus name is `instance` followed by digits and
+///    underscores, or only digits, it is the instance. This covers the common patterns, like
+///    `vlc.instance1234`, `chromium.instance42`, `firefox.instance_1_84` and `mpv.12345`.
+/// 3. Otherwise, the whole bus name is the application and there is no instance.
+///
+/// The MPRIS2 prefix and trailing dots are removed first.
+///
+/// ```
+/// use mpris::BusNameInfo;
+///
+/// let info = BusNameInfo::parse("org.mpris.MediaPlayer2.firefox.instance_1_84");
+/// assert_eq!(info.application(), "firefox");
+/// assert_eq!(info.instance(), Some("instance_1_84"));
+///
+/// let info = BusNameInfo::parse("org.mpris.MediaPlayer2.io.github.celluloid_player.Celluloid");
+/// assert_eq!(info.application(), "io.github.celluloid_player.Celluloid");
+/// assert_eq!(info.instance(), None);
+/// ```
+///
+/// [bus_names]: https://specifications.freedesktop.org/mpris-spec/latest/#Bus-Name-Policy
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BusNameInfo {
+    application: String,
+    instance: Option<String>,
+}
+
+impl BusNameInfo {
+    /// Parses a bus name, with or without the MPRIS2 prefix, using only its suffix patterns.
+    pub fn parse(bus_name: &str) -> BusNameInfo {
+        BusNameInfo::parse_with_desktop_entry(bus_name, None)
+    }
+
+    /// Parses a bus name, with or without the MPRIS2 prefix, using the player's desktop entry to
+    /// tell the application apart from the instance if possible.
+    pub fn parse_with_desktop_entry(bus_name: &str, desktop_entry: Option<&str>) -> BusNameInfo {
+        let trimmed = bus_name
+            .trim_start_matches(MPRIS2_PREFIX)
+            .trim_end_matches('.');
+
+        if let Some(desktop_entry) = desktop_entry {
+            let desktop_entry = desktop_entry.trim_end_matches(".desktop");
+            if let Some(info) = split_at_desktop_entry(trimmed, desktop_entry) {
+                return info;
+            }
+        }
+
+        match trimmed.rsplit_once('.') {
+            Some((application, suffix)) if !application.is_empty() && is_instance(suffix) => {
+                BusNameInfo {
+                    application: application.to_string(),
+                    instance: Some(suffix.to_string()),
+                }
+            }
+            _ => BusNameInfo {
+                application: trimmed.to_string(),
+                instance: None,
+            },
+        }
+    }
+
+    /// Returns the application part of the bus name, like `vlc` for `vlc.instance1234`.
+    pub fn application(&self) -> &str {
+        &self.application
+    }
+
+    /// Returns the instance part of the bus name, like `instance1234` for `vlc.instance1234`, or
+    /// [`None`] if the bus name does not seem to have one.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Returns [`true`] if both bus names belong to the same application, ignoring case.
+    pub fn is_same_application(&self, other: &BusNameInfo) -> bool {
+        self.application.eq_ignore_ascii_case(&other.application)
+    }
+}
+
+impl fmt::Display for BusNameInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.instance {
+            Some(ref instance) => write!(f, "{}.{}", self.application, instance),
+            None => f.write_str(&self.application),
+        }
+    }
+}
+
+/// Splits the bus name after the desktop entry, if it starts with it on a part boundary.
+fn split_at_desktop_entry(bus_name: &str, desktop_entry: &str) -> Option<BusNameInfo> {
+    if desktop_entry.is_empty() {
+        return None;
+    }
+
+    let application = bus_name.get(..desktop_entry.len())?;
+    if !application.eq_ignore_ascii_case(desktop_entry) {
+        return None;
+    }
+
+    let instance = match &bus_name[desktop_entry.len()..] {
+        "" => None,
+        rest => Some(rest.strip_prefix('.').filter(|rest| !rest.is_empty())?),
+    };
+    Some(BusNameInfo {
+        application: application.to_string(),
+        instance: instance.map(String::from),
+    })
+}
+
+/// Returns [`true`] if the part of a bus name looks like an instance suffix.
+fn is_instance(part: &str) -> bool {
+    let number = part.strip_prefix("instance").unwrap_or(part);
+    number.chars().all(|c| c.is_ascii_digit() || c == '_')
+        && number.contains(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(info: BusNameInfo) -> (String, Option<String>) {
+        (info.application, info.instance)
+    }
+
+    fn parse(bus_name: &str) -> (String, Option<String>) {
+        parts(BusNameInfo::parse(bus_name))
+    }
+
+    #[test]
+    fn it_parses_instance_suffixes() {
+        let expected = |application: &str, instance: Option<&str>| {
+            (application.to_string(), instance.map(String::from))
+        };
+
+        assert_eq!(
+            parse("org.mpris.MediaPlayer2.spotify"),
+            expected("spotify", None)
+        );
+        assert_eq!(
+            parse("org.mpris.MediaPlayer2.Spotify."),
+            expected("Spotify", None)
+        );
+        assert_eq!(
+            parse("org.mpris.MediaPlayer2.vlc.instance1234"),
+            expected("vlc", Some("instance1234"))
+        );
+        assert_eq!(
+            parse("chromium.instance42"),
+            expected("chromium", Some("instance42"))
+        );
+        assert_eq!(
+            parse("firefox.instance_1_84"),
+            expected("firefox", Some("instance_1_84"))
+        );
+        assert_eq!(parse("mpv.12345"), expected("mpv", Some("12345")));
+        assert_eq!(
+            parse("io.github.celluloid_player.Celluloid"),
+            expected("io.github.celluloid_player.Celluloid", None)
+        );
+        assert_eq!(parse("vlc.instance"), expected("vlc.instance", None));
+        assert_eq!(parse("vlc.instance_"), expected("vlc.instance_", None));
+        assert_eq!(parse("1234"), expected("1234", None));
+    }
+
+    #[test]
+    fn it_prefers_desktop_entries() {
+        let parse = |bus_name, desktop_entry| {
+            parts(BusNameInfo::parse_with_desktop_entry(
+                bus_name,
+                Some(desktop_entry),
+            ))
+        };
+
+        assert_eq!(
+            parse("org.mpris.MediaPlayer2.net.Player2.tab3", "net.player2"),
+            ("net.Player2".to_string(), Some("tab3".to_string()))
+        );
+        assert_eq!(
+            parse("app.2019", "app.2019.desktop"),
+            ("app.2019".to_string(), None)
+        );
+        // Not on a part boundary, so the suffix patterns are used instead.
+        assert_eq!(
+            parse("firefox-esr.instance7", "firefox"),
+            ("firefox-esr".to_string(), Some("instance7".to_string()))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::ffidisp::Watch;
+
+/// Wakes up blocking calls on another thread and makes them return, for example to shut down a
+/// thread that is iterating over [`PlayerEvents`](crate::PlayerEvents).
+///
+/// Attach the handle with [`PlayerEvents::with_cancel_handle`](crate::PlayerEvents::with_cancel_handle)
+/// or [`ProgressTracker::with_cancel_handle`](crate::ProgressTracker::with_cancel_handle), and
+/// keep a clone of it. Calling [`cancel`](Self::cancel) on any clone, from any thread, makes
+/// the blocked call return right away:
+///
+/// * [`PlayerEvents`](crate::PlayerEvents) returns [`EventError::Cancelled`](crate::EventError::Cancelled)
+///   instead of waiting for the next event. Events that were already received are still returned
+///   first.
+/// * [`ProgressTracker::tick`](crate::ProgressTracker::tick) stops waiting for the interval and
+///   returns a tick with [`cancelled`](crate::ProgressTick::cancelled) set.
+///
+/// A handle can not be reset, so all later calls return right away as well.
+///
+/// ```rust,no_run
+/// use mpris::{CancelHandle, EventError, PlayerFinder};
+/// use std::thread;
+///
+/// let cancel = CancelHandle::new().expect("Could not create cancel handle");
+/// let thread_cancel = cancel.clone();
+/// let thread = thread::spawn(move || {
+///     let player = PlayerFinder::new().unwrap().find_active().unwrap();
+///     let events = player.events().unwrap().with_cancel_handle(thread_cancel);
+///     for event in events {
+///         match event {
+///             Ok(event) => println!("{:?}", event),
+///             Err(EventError::Cancelled) => break,
+///             Err(err) => panic!("{}", err),
+///         }
+///     }
+/// });
+///
+/// // Later, when shutting down:
+/// cancel.cancel();
+/// thread.join().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    cancelled: AtomicBool,
+
+    /// A byte is written to this end of the pipe when cancelling.
+    sender: UnixStream,
+
+    /// Watched together with the D-Bus connection. It is never read from, so it stays readable
+    /// once the handle was cancelled.
+    receiver: UnixStream,
+}
+
+impl CancelHandle {
+    /// Creates a new handle, which is not cancelled yet.
+    ///
+    /// Returns an [`Err`] if the pipe used to wake up blocked calls could not be created, for
+    /// example because the process has too many open files.
+    pub fn new() -> io::Result<CancelHandle> {
+        let (sender, receiver) = UnixStream::pair()?;
+        sender.set_nonblocking(true)?;
+        receiver.set_nonblocking(true)?;
+        Ok(CancelHandle {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                sender,
+                receiver,
+            }),
+        })
+    }
+
+    /// Cancels all calls that are blocked on this handle, and all that will be made in the future.
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            // Can only fail if the pipe is full, in which case it is readable already.
+            let _ = (&self.inner.sender).write(&[1]);
+        }
+    }
+
+    /// Returns [`true`] if [`cancel`](Self::cancel) was called on this handle or any of its
+    /// clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Blocks until one of the watched file descriptors of a D-Bus connection is ready, the handle
+    /// is cancelled, or the timeout passes. Waits forever without a timeout.
+    pub(crate) fn wait(&self, watches: &[Watch], timeout: Option<Duration>) -> io::Result<()> {
+        let mut fds: Vec<libc::pollfd> = watches.iter().map(Watch::to_pollfd).collect();
+        fds.push(libc::pollfd {
+            fd: self.inner.receiver.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        });
+
+        // Round up, so that waiting for less than a millisecond does not turn into busy waiting.
+        let timeout_ms = timeout.map_or(-1, |timeout| {
+            let ms = (timeout.as_nanos() + 999_999) / 1_000_000;
+            ms.min(i32::MAX as u128) as i32
+        });
+        poll(&mut fds, timeout_ms)
+    }
+}
+
+/// Safe wrapper around `poll(2)`. Being interrupted by a signal counts as a timeout.
+#[allow(unsafe_code)]
+fn poll(fds: &mut [libc::pollfd], timeout_ms: i32) -> io::Result<()> {
+    // SAFETY: The pointer and length describe the slice, which is borrowed mutably for the whole
+    // call, so the kernel can write the returned events to it.
+    let result = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+    if result < 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve};
+    use crate::{Event, EventError};
+    use std::thread;
+    use std::time::Instant;
+
+    /// Returns a handle that is cancelled from another thread after the given delay.
+    pub(crate) fn cancel_later(delay: Duration) -> CancelHandle {
+        let cancel = CancelHandle::new().unwrap();
+        let thread_cancel = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            thread_cancel.cancel();
+        });
+        cancel
+    }
+
+    #[test]
+    fn it_cancels_blocking_calls() {
+        let (bus_name, handle) = serve("mpris_rs_test_cancel");
+        let player = connect(bus_name.clone());
+
+        // Events still come in while waiting for the handle.
+        let mut events = player
+            .events()
+            .unwrap()
+            .with_cancel_handle(CancelHandle::new().unwrap());
+        let other_thread = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            connect(bus_name).set_volume(0.5).unwrap();
+        });
+        assert!(matches!(
+            events.next(),
+            Some(Ok(Event::VolumeChanged(volume))) if (volume - 0.5).abs() < f64::EPSILON
+        ));
+        other_thread.join().unwrap();
+
+        let cancel = cancel_later(Duration::from_millis(100));
+        let mut events = player.events().unwrap().with_cancel_handle(cancel.clone());
+        let started = Instant::now();
+        assert!(matches!(events.next(), Some(Err(EventError::Cancelled))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(cancel.is_cancelled());
+        assert!(matches!(
+            events.next_timeout(Duration::from_secs(60)),
+            Err(EventError::Cancelled)
+        ));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+}
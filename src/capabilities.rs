@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Sub};
+
+use crate::metadata::Value;
+
+/// A set of things that a [`Player`](crate::Player) can do, or supports.
+///
+/// Read it from a player with [`Player::get_capabilities`](crate::Player::get_capabilities) or
+/// [`PlayerState::capabilities`](crate::PlayerState::capabilities). Changes are reported by
+/// [`Event::CapabilitiesChanged`](crate::Event::CapabilitiesChanged).
+///
+/// The set works like a set of bit flags: the single capabilities are associated constants, which
+/// can be combined with `|` and compared with [`contains`](Self::contains). Iterating over a set
+/// yields every single capability that it contains.
+///
+/// ```
+/// use mpris::Capabilities;
+///
+/// let capabilities = Capabilities::PLAY | Capabilities::PAUSE;
+/// assert!(capabilities.contains(Capabilities::PLAY));
+/// assert!(!capabilities.contains(Capabilities::PLAY | Capabilities::SEEK));
+/// assert_eq!(capabilities.to_string(), "Play | Pause");
+/// assert_eq!(capabilities.iter().count(), 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Capabilities(u32);
+
+/// The name of every single capability, in the order they are iterated and displayed.
+const NAMES: &[(Capabilities, &str)] = &[
+    (Capabilities::QUIT, "Quit"),
+    (Capabilities::RAISE, "Raise"),
+    (Capabilities::SET_FULLSCREEN, "SetFullscreen"),
+    (Capabilities::TRACK_LIST, "TrackList"),
+    (Capabilities::CONTROL, "Control"),
+    (Capabilities::GO_NEXT, "GoNext"),
+    (Capabilities::GO_PREVIOUS, "GoPrevious"),
+    (Capabilities::PLAY, "Play"),
+    (Capabilities::PAUSE, "Pause"),
+    (Capabilities::SEEK, "Seek"),
+    (Capabilities::SET_PLAYBACK_RATE, "SetPlaybackRate"),
+    (Capabilities::PLAYBACK_RATE, "PlaybackRate"),
+    (Capabilities::SHUFFLE, "Shuffle"),
+    (Capabilities::LOOP, "Loop"),
+    (Capabilities::VOLUME, "Volume"),
+    (Capabilities::POSITION, "Position"),
+];
+
+impl Capabilities {
+    /// The player can be asked to quit. See [`Player::can_quit`](crate::Player::can_quit).
+    pub const QUIT: Capabilities = Capabilities(1 << 0);
+
+    /// The player can be raised. See [`Player::can_raise`](crate::Player::can_raise).
+    pub const RAISE: Capabilities = Capabilities(1 << 1);
+
+    /// The player can enter and leave fullscreen. See
+    /// [`Player::can_set_fullscreen`](crate::Player::can_set_fullscreen).
+    pub const SET_FULLSCREEN: Capabilities = Capabilities(1 << 2);
+
+    /// The player has a track list. See [`Player::supports_track_lists`](crate::Player::supports_track_lists).
+    pub const TRACK_LIST: Capabilities = Capabilities(1 << 3);
+
+    /// The player can be controlled. See [`Player::can_control`](crate::Player::can_control).
+    pub const CONTROL: Capabilities = Capabilities(1 << 4);
+
+    /// The player can go to the next track. See [`Player::can_go_next`](crate::Player::can_go_next).
+    pub const GO_NEXT: Capabilities = Capabilities(1 << 5);
+
+    /// The player can go to the previous track. See
+    /// [`Player::can_go_previous`](crate::Player::can_go_previous).
+    pub const GO_PREVIOUS: Capabilities = Capabilities(1 << 6);
+
+    /// The player can start playing. See [`Player::can_play`](crate::Player::can_play).
+    pub const PLAY: Capabilities = Capabilities(1 << 7);
+
+    /// The player can pause. See [`Player::can_pause`](crate::Player::can_pause).
+    pub const PAUSE: Capabilities = Capabilities(1 << 8);
+
+    /// The player can seek. See [`Player::can_seek`](crate::Player::can_seek).
+    pub const SEEK: Capabilities = Capabilities(1 << 9);
+
+    /// The playback rate can be changed. See
+    /// [`Player::can_set_playback_rate`](crate::Player::can_set_playback_rate).
+    pub const SET_PLAYBACK_RATE: Capabilities = Capabilities(1 << 10);
+
+    /// The player has a playback rate. See
+    /// [`Player::has_playback_rate`](crate::Player::has_playback_rate).
+    pub const PLAYBACK_RATE: Capabilities = Capabilities(1 << 11);
+
+    /// The player supports shuffling. See [`Player::can_shuffle`](crate::Player::can_shuffle).
+    pub const SHUFFLE: Capabilities = Capabilities(1 << 12);
+
+    /// The player supports looping. See [`Player::can_loop`](crate::Player::can_loop).
+    pub const LOOP: Capabilities = Capabilities(1 << 13);
+
+    /// The player has a volume. See [`Player::has_volume`](crate::Player::has_volume).
+    pub const VOLUME: Capabilities = Capabilities(1 << 14);
+
+    /// The player reports its position. See [`Player::has_position`](crate::Player::has_position).
+    pub const POSITION: Capabilities = Capabilities(1 << 15);
+
+    /// Returns a set without any capabilities.
+    pub const fn empty() -> Capabilities {
+        Capabilities(0)
+    }
+
+    /// Returns a set with every capability.
+    pub const fn all() -> Capabilities {
+        Capabilities((1 << 16) - 1)
+    }
+
+    /// Returns [`true`] if the set contains no capabilities.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns [`true`] if the set contains all capabilities in `other`.
+    pub const fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns [`true`] if the set contains any of the capabilities in `other`.
+    pub const fn intersects(self, other: Capabilities) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Adds all capabilities in `other` to the set.
+    pub fn insert(&mut self, other: Capabilities) {
+        self.0 |= other.0;
+    }
+
+    /// Removes all capabilities in `other` from the set.
+    pub fn remove(&mut self, other: Capabilities) {
+        self.0 &= !other.0;
+    }
+
+    /// Adds or removes all capabilities in `other`, depending on `value`.
+    pub fn set(&mut self, other: Capabilities, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+
+    /// Returns the capabilities that are in either set. Same as `self | other`.
+    pub const fn union(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 | other.0)
+    }
+
+    /// Returns the capabilities that are in both sets. Same as `self & other`.
+    pub const fn intersection(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 & other.0)
+    }
+
+    /// Returns the capabilities that are in this set, but not in `other`. Same as `self - other`.
+    pub const fn difference(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 & !other.0)
+    }
+
+    /// Returns an iterator over every single capability in the set.
+    pub fn iter(self) -> CapabilitiesIter {
+        CapabilitiesIter {
+            capabilities: self,
+            index: 0,
+        }
+    }
+
+    /// Returns the name of the capability, like `GoNext`, if the set contains exactly one.
+    pub fn name(self) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|(capability, _)| *capability == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// Reads the capabilities of the `org.mpris.MediaPlayer2` interface from all of its
+    /// properties.
+    pub(crate) fn from_root_properties(properties: &HashMap<String, Value>) -> Capabilities {
+        let mut capabilities = Capabilities::empty();
+        capabilities.set(Capabilities::QUIT, get_bool(properties, "CanQuit"));
+        capabilities.set(Capabilities::RAISE, get_bool(properties, "CanRaise"));
+        capabilities.set(
+            Capabilities::SET_FULLSCREEN,
+            get_bool(properties, "CanSetFullscreen"),
+        );
+        capabilities.set(
+            Capabilities::TRACK_LIST,
+            get_bool(properties, "HasTrackList"),
+        );
+        capabilities
+    }
+
+    /// Reads the capabilities of the `org.mpris.MediaPlayer2.Player` interface from all of its
+    /// properties.
+    pub(crate) fn from_player_properties(properties: &HashMap<String, Value>) -> Capabilities {
+        let rate = |name| properties.get(name).and_then(Value::as_f64).unwrap_or(1.0);
+
+        let mut capabilities = Capabilities::empty();
+        capabilities.set(Capabilities::CONTROL, get_bool(properties, "CanControl"));
+        capabilities.set(Capabilities::GO_NEXT, get_bool(properties, "CanGoNext"));
+        capabilities.set(
+            Capabilities::GO_PREVIOUS,
+            get_bool(properties, "CanGoPrevious"),
+        );
+        capabilities.set(Capabilities::PLAY, get_bool(properties, "CanPlay"));
+        capabilities.set(Capabilities::PAUSE, get_bool(properties, "CanPause"));
+        capabilities.set(Capabilities::SEEK, get_bool(properties, "CanSeek"));
+        capabilities.set(
+            Capabilities::SET_PLAYBACK_RATE,
+            rate("MinimumRate") < 1.0 || rate("MaximumRate") > 1.0,
+        );
+        capabilities.set(Capabilities::PLAYBACK_RATE, properties.contains_key("Rate"));
+        capabilities.set(Capabilities::SHUFFLE, properties.contains_key("Shuffle"));
+        capabilities.set(Capabilities::LOOP, properties.contains_key("LoopStatus"));
+        capabilities.set(Capabilities::VOLUME, properties.contains_key("Volume"));
+        capabilities.set(Capabilities::POSITION, properties.contains_key("Position"));
+        capabilities
+    }
+}
+
+fn get_bool(properties: &HashMap<String, Value>, name: &str) -> bool {
+    properties
+        .get(name)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Displays the names of the capabilities in the set, separated by `|`. An empty set is displayed
+/// as an empty string.
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, capability) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" | ")?;
+            }
+            f.write_str(capability.name().unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, other: Capabilities) -> Capabilities {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for Capabilities {
+    fn bitor_assign(&mut self, other: Capabilities) {
+        self.insert(other);
+    }
+}
+
+impl BitAnd for Capabilities {
+    type Output = Capabilities;
+
+    fn bitand(self, other: Capabilities) -> Capabilities {
+        self.intersection(other)
+    }
+}
+
+impl Sub for Capabilities {
+    type Output = Capabilities;
+
+    fn sub(self, other: Capabilities) -> Capabilities {
+        self.difference(other)
+    }
+}
+
+impl FromIterator<Capabilities> for Capabilities {
+    fn from_iter<I: IntoIterator<Item = Capabilities>>(iter: I) -> Capabilities {
+        iter.into_iter()
+            .fold(Capabilities::empty(), Capabilities::union)
+    }
+}
+
+impl IntoIterator for Capabilities {
+    type Item = Capabilities;
+    type IntoIter = CapabilitiesIter;
+
+    fn into_iter(self) -> CapabilitiesIter {
+        self.iter()
+    }
+}
+
+/// Iterator over every single capability in a [`Capabilities`] set.
+///
+/// Created by calling [`Capabilities::iter`].
+#[derive(Debug, Clone)]
+pub struct CapabilitiesIter {
+    capabilities: Capabilities,
+    index: usize,
+}
+
+impl Iterator for CapabilitiesIter {
+    type Item = Capabilities;
+
+    fn next(&mut self) -> Option<Capabilities> {
+        while let Some((capability, _)) = NAMES.get(self.index) {
+            self.index += 1;
+            if self.capabilities.contains(*capability) {
+                return Some(*capability);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_combines_capabilities() {
+        let mut capabilities = Capabilities::GO_NEXT | Capabilities::GO_PREVIOUS;
+        capabilities.insert(Capabilities::SEEK);
+        capabilities.remove(Capabilities::GO_PREVIOUS);
+
+        assert_eq!(capabilities, Capabilities::GO_NEXT | Capabilities::SEEK);
+        assert_eq!(capabilities - Capabilities::SEEK, Capabilities::GO_NEXT);
+        assert_eq!(capabilities & Capabilities::SEEK, Capabilities::SEEK);
+        assert!(capabilities.intersects(Capabilities::SEEK | Capabilities::PLAY));
+        assert!(Capabilities::all().contains(capabilities));
+        assert!(Capabilities::empty().is_empty());
+    }
+
+    #[test]
+    fn it_iterates_and_displays_capabilities() {
+        assert_eq!(Capabilities::all().iter().count(), NAMES.len());
+        assert_eq!(
+            Capabilities::all().iter().collect::<Capabilities>(),
+            Capabilities::all()
+        );
+
+        let capabilities = Capabilities::VOLUME | Capabilities::QUIT;
+        assert_eq!(
+            capabilities.iter().collect::<Vec<_>>(),
+            vec![Capabilities::QUIT, Capabilities::VOLUME]
+        );
+        assert_eq!(capabilities.to_string(), "Quit | Volume");
+        assert_eq!(Capabilities::empty().to_string(), "");
+        assert_eq!(capabilities.name(), None);
+        assert_eq!(Capabilities::GO_NEXT.name(), Some("GoNext"));
+    }
+
+    #[test]
+    fn it_reads_capabilities_from_properties() {
+        let mut root = HashMap::new();
+        root.insert(String::from("CanQuit"), Value::Bool(true));
+        root.insert(String::from("CanRaise"), Value::Bool(false));
+        assert_eq!(
+            Capabilities::from_root_properties(&root),
+            Capabilities::QUIT
+        );
+
+        let mut player = HashMap::new();
+        player.insert(String::from("CanGoNext"), Value::Bool(true));
+        player.insert(String::from("Volume"), Value::F64(0.5));
+        player.insert(String::from("MaximumRate"), Value::F64(2.0));
+        assert_eq!(
+            Capabilities::from_player_properties(&player),
+            Capabilities::GO_NEXT | Capabilities::VOLUME | Capabilities::SET_PLAYBACK_RATE
+        );
+    }
+}
@@ -0,0 +1,542 @@
+mod icon_theme;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The `.desktop` file of an application, as described by the
+/// [Desktop Entry Specification][spec].
+///
+/// Players announce the basename of their `.desktop` file in their
+/// [`DesktopEntry`](crate::Player::get_desktop_entry) property. Use
+/// [`Player::get_desktop_file`](crate::Player::get_desktop_file) or [`DesktopFile::find`] to look
+/// it up and read the application's localized name and icon.
+///
+/// Only the keys of the `[Desktop Entry]` group that are useful for showing a player are read.
+///
+/// [spec]: https://specifications.freedesktop.org/desktop-entry-spec/latest/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopFile {
+    id: String,
+    path: PathBuf,
+
+    /// Values of `Name`, by locale. The unlocalized one is stored under an empty locale.
+    names: HashMap<String, String>,
+    icon: Option<String>,
+    exec: Option<String>,
+}
+
+/// The groups of a key file, like a `.desktop` file or an icon theme's `index.theme`, with their
+/// keys and unescaped values.
+type KeyFile = HashMap<String, HashMap<String, String>>;
+
+impl DesktopFile {
+    /// Searches `$XDG_DATA_HOME/applications` and the `applications` directory of every
+    /// `$XDG_DATA_DIRS` entry for the `.desktop` file with the given desktop file ID, like
+    /// `firefox` or `org.gnome.Lollypop`. A trailing `.desktop` is ignored.
+    ///
+    /// Earlier directories take precedence, as the specification requires. IDs with dashes may
+    /// refer to files in subdirectories, so `kde4-amarok` also finds `kde4/amarok.desktop`. If
+    /// there is no exact match, the ID is compared ignoring case, as some players do not announce
+    /// it with the right case.
+    ///
+    /// Returns [`None`] if no file was found or it could not be read.
+    pub fn find(id: &str) -> Option<DesktopFile> {
+        let application_dirs: Vec<PathBuf> = data_dirs()
+            .into_iter()
+            .map(|dir| dir.join("applications"))
+            .collect();
+        find_in(id, &application_dirs)
+    }
+
+    /// Reads the `.desktop` file at the given path. The desktop file ID is taken from the file
+    /// name.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<DesktopFile> {
+        let path = path.as_ref();
+        let id = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        DesktopFile::load_with_id(id, path.to_path_buf())
+    }
+
+    fn load_with_id(id: String, path: PathBuf) -> io::Result<DesktopFile> {
+        let contents = fs::read_to_string(&path)?;
+        let mut entry = parse_key_file(&contents)
+            .remove("Desktop Entry")
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Missing [Desktop Entry] group")
+            })?;
+
+        let mut names = HashMap::new();
+        for (key, value) in &entry {
+            if key == "Name" {
+                names.insert(String::new(), value.clone());
+            } else if let Some(locale) = key
+                .strip_prefix("Name[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                names.insert(locale.to_string(), value.clone());
+            }
+        }
+
+        Ok(DesktopFile {
+            id,
+            path,
+            names,
+            icon: entry.remove("Icon").filter(|icon| !icon.is_empty()),
+            exec: entry.remove("Exec").filter(|exec| !exec.is_empty()),
+        })
+    }
+
+    /// Returns the desktop file ID, like `firefox`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the path of the `.desktop` file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the application's `Name`, localized for the user's locale as set in the `LC_ALL`,
+    /// `LC_MESSAGES` or `LANG` environment variables.
+    pub fn name(&self) -> Option<&str> {
+        self.name_for_locale(&messages_locale())
+    }
+
+    /// Returns the application's `Name`, localized for the given locale, like `de_DE.UTF-8`.
+    ///
+    /// Following the specification, `lang_COUNTRY@MODIFIER` falls back to `lang_COUNTRY`, then
+    /// `lang@MODIFIER`, then `lang` and finally the unlocalized name.
+    pub fn name_for_locale(&self, locale: &str) -> Option<&str> {
+        locale_candidates(locale)
+            .iter()
+            .find_map(|candidate| self.names.get(candidate))
+            .map(String::as_str)
+    }
+
+    /// Returns the application's `Icon`. This is either an icon name to look up in the icon
+    /// theme, or an absolute path. See [`icon_path`](Self::icon_path) to resolve it.
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    /// Returns the application's `Exec` command line, as it is in the file. Field codes like
+    /// `%U` are left in place.
+    pub fn exec(&self) -> Option<&str> {
+        self.exec.as_deref()
+    }
+
+    /// Returns the program and arguments to run the application with, parsed from its
+    /// [`exec`](Self::exec) line.
+    ///
+    /// Field codes for files and URLs, like `%U`, are removed, as the application is started
+    /// without any. `%i`, `%c` and `%k` are replaced by the icon, the [`name`](Self::name) and
+    /// the path of the `.desktop` file.
+    ///
+    /// Returns [`None`] if there is no `Exec` key, or it could not be parsed.
+    pub fn command(&self) -> Option<Vec<String>> {
+        let mut command = Vec::new();
+        for (arg, quoted) in split_exec(self.exec.as_deref()?)? {
+            // Field codes are not allowed in quoted arguments.
+            if quoted {
+                command.push(arg);
+            } else if arg == "%i" {
+                if let Some(ref icon) = self.icon {
+                    command.push(String::from("--icon"));
+                    command.push(icon.clone());
+                }
+            } else {
+                let expanded = self.expand_field_codes(&arg);
+                // Arguments that only consisted of removed field codes are dropped.
+                if !expanded.is_empty() || arg.is_empty() {
+                    command.push(expanded);
+                }
+            }
+        }
+
+        if command.is_empty() {
+            None
+        } else {
+            Some(command)
+        }
+    }
+
+    fn expand_field_codes(&self, arg: &str) -> String {
+        let mut expanded = String::with_capacity(arg.len());
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('c') => expanded.push_str(self.name().unwrap_or_default()),
+                Some('k') => expanded.push_str(&self.path.to_string_lossy()),
+                _ => {}
+            }
+        }
+        expanded
+    }
+
+    /// Resolves the application's [`icon`](Self::icon) to an image file, following the
+    /// [Icon Theme Specification][spec].
+    ///
+    /// The icon is looked up in the given theme, like `Adwaita`, and the themes it inherits from,
+    /// falling back to `hicolor` and then to unthemed icons like the ones in
+    /// `/usr/share/pixmaps`. Pass [`None`] to only look in `hicolor`. Icons of the requested size
+    /// in pixels are preferred, otherwise the closest size is used.
+    ///
+    /// Returns [`None`] if the application has no icon, or no file was found for it.
+    ///
+    /// [spec]: https://specifications.freedesktop.org/icon-theme-spec/latest/
+    pub fn icon_path(&self, theme: Option<&str>, size: u32) -> Option<PathBuf> {
+        let icon = self.icon.as_deref()?;
+        icon_theme::find_icon(
+            icon,
+            theme.unwrap_or(icon_theme::DEFAULT_THEME),
+            size,
+            &icon_theme::base_dirs(),
+        )
+    }
+}
+
+/// Returns the base directories of the XDG Base Directory Specification that data files are
+/// searched in, most important first.
+fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => {
+            if let Some(home) = env::var_os("HOME") {
+                dirs.push(Path::new(&home).join(".local/share"));
+            }
+        }
+    }
+
+    let system_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+    dirs.extend(system_dirs.split(':').map(PathBuf::from));
+
+    // Relative paths are invalid, and must be ignored.
+    dirs.retain(|dir| dir.is_absolute());
+    dirs
+}
+
+fn find_in(id: &str, application_dirs: &[PathBuf]) -> Option<DesktopFile> {
+    let id = id.trim_end_matches(".desktop");
+    if id.is_empty() {
+        return None;
+    }
+
+    let path = application_dirs
+        .iter()
+        .find_map(|dir| find_in_dir(dir, id))
+        .or_else(|| {
+            application_dirs
+                .iter()
+                .find_map(|dir| find_in_dir_ignoring_case(dir, id))
+        })?;
+    DesktopFile::load_with_id(id.to_string(), path).ok()
+}
+
+fn find_in_dir(dir: &Path, id: &str) -> Option<PathBuf> {
+    let path = dir.join(format!("{}.desktop", id));
+    if path.is_file() {
+        return Some(path);
+    }
+
+    // Dashes in the ID may stand for directory separators.
+    id.match_indices('-').find_map(|(index, _)| {
+        let subdir = dir.join(&id[..index]);
+        if subdir.is_dir() {
+            find_in_dir(&subdir, &id[index + 1..])
+        } else {
+            None
+        }
+    })
+}
+
+fn find_in_dir_ignoring_case(dir: &Path, id: &str) -> Option<PathBuf> {
+    let file_name = format!("{}.desktop", id);
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.eq_ignore_ascii_case(&file_name))
+                && path.is_file()
+        })
+}
+
+/// Parses a key file. Comments, blank lines and keys outside of a group are skipped.
+fn parse_key_file(contents: &str) -> KeyFile {
+    let mut groups = KeyFile::new();
+    let mut current: Option<&mut HashMap<String, String>> = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(groups.entry(group.to_string()).or_default());
+        } else if let (Some(keys), Some((key, value))) = (current.as_mut(), line.split_once('=')) {
+            // The first occurrence of a key wins.
+            keys.entry(key.trim().to_string())
+                .or_insert_with(|| unescape(value.trim()));
+        }
+    }
+
+    groups
+}
+
+/// Replaces the escape sequences that are allowed in key file values.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits an `Exec` value into its arguments, marking the ones that were quoted. Returns
+/// [`None`] if a quote is not closed.
+fn split_exec(exec: &str) -> Option<Vec<(String, bool)>> {
+    let mut args = Vec::new();
+    let mut chars = exec.chars().peekable();
+
+    loop {
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+
+        let mut arg = String::new();
+        match chars.peek() {
+            None => break,
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => arg.push(chars.next()?),
+                        c => arg.push(c),
+                    }
+                }
+                args.push((arg, true));
+            }
+            Some(_) => {
+                while let Some(c) = chars.next_if(|&c| c != ' ') {
+                    arg.push(c);
+                }
+                args.push((arg, false));
+            }
+        }
+    }
+
+    Some(args)
+}
+
+/// Returns the locale that messages should be shown in.
+fn messages_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/// Returns the localized keys to try for the given locale, most specific first. The last one is
+/// always the empty, unlocalized one.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    // The encoding is not used for matching.
+    let locale = locale.split('.').next().unwrap_or_default();
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+
+    let mut candidates = Vec::new();
+    if !lang.is_empty() && lang != "C" && lang != "POSIX" {
+        if let (Some(country), Some(modifier)) = (country, modifier) {
+            candidates.push(format!("{}_{}@{}", lang, country, modifier));
+        }
+        if let Some(country) = country {
+            candidates.push(format!("{}_{}", lang, country));
+        }
+        if let Some(modifier) = modifier {
+            candidates.push(format!("{}@{}", lang, modifier));
+        }
+        candidates.push(lang.to_string());
+    }
+    candidates.push(String::new());
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for a test, removing anything left over from earlier runs.
+    pub(super) fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("mpris_rs_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes a file, creating its directory first.
+    pub(super) fn write_file(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn it_parses_key_files() {
+        let groups = parse_key_file(
+            "# Comment\n\
+             Ignored=outside of a group\n\
+             [Desktop Entry]\n\
+             Name = Music\\sPlayer\n\
+             Name=Duplicate\n\
+             Exec=player --title=a\\\\b %U\n\
+             \n\
+             [Desktop Action New]\n\
+             Name=New Window\n",
+        );
+
+        assert_eq!(groups.len(), 2);
+        let entry = &groups["Desktop Entry"];
+        assert_eq!(entry["Name"], "Music Player");
+        assert_eq!(entry["Exec"], "player --title=a\\b %U");
+        assert_eq!(groups["Desktop Action New"]["Name"], "New Window");
+    }
+
+    #[test]
+    fn it_localizes_names() {
+        let dir = test_dir("desktop_names");
+        let path = dir.join("player.desktop");
+        write_file(
+            &path,
+            "[Desktop Entry]\n\
+             Name=Player\n\
+             Name[de]=Spieler\n\
+             Name[sr@latin]=Plejer\n\
+             Name[pt_BR]=Reprodutor\n\
+             Icon=player\n",
+        );
+        let file = DesktopFile::load(&path).unwrap();
+
+        assert_eq!(file.id(), "player");
+        assert_eq!(file.icon(), Some("player"));
+        assert_eq!(file.exec(), None);
+        assert_eq!(file.name_for_locale("de_AT.UTF-8"), Some("Spieler"));
+        assert_eq!(file.name_for_locale("sr_RS@latin"), Some("Plejer"));
+        assert_eq!(file.name_for_locale("pt_BR.UTF-8"), Some("Reprodutor"));
+        assert_eq!(file.name_for_locale("pt_PT"), Some("Player"));
+        assert_eq!(file.name_for_locale("C"), Some("Player"));
+        assert_eq!(file.name_for_locale(""), Some("Player"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_parses_commands() {
+        let file = |exec: &str| DesktopFile {
+            id: String::from("player"),
+            path: PathBuf::from("/apps/player.desktop"),
+            names: vec![(String::new(), String::from("Player"))]
+                .into_iter()
+                .collect(),
+            icon: Some(String::from("player-icon")),
+            exec: Some(exec.to_string()),
+        };
+        let command = |exec: &str| file(exec).command();
+        let args = |args: &[&str]| Some(args.iter().map(|arg| arg.to_string()).collect());
+
+        assert_eq!(command("player %U"), args(&["player"]));
+        assert_eq!(
+            command("  player  --name=%c %i --file=%f 100%%"),
+            args(&[
+                "player",
+                "--name=Player",
+                "--icon",
+                "player-icon",
+                "--file=",
+                "100%"
+            ])
+        );
+        assert_eq!(
+            command(r#""/opt/My Player/player" "quoted \"%u\" \\ \$" %k"#),
+            args(&[
+                "/opt/My Player/player",
+                r#"quoted "%u" \ $"#,
+                "/apps/player.desktop"
+            ])
+        );
+        assert_eq!(command(r#"player "unterminated"#), None);
+        assert_eq!(command("%U"), None);
+    }
+
+    #[test]
+    fn it_finds_desktop_files() {
+        let dir = test_dir("desktop_find");
+        let user = dir.join("user");
+        let system = dir.join("system");
+        write_file(&user.join("player.desktop"), "[Desktop Entry]\nName=User\n");
+        write_file(
+            &system.join("player.desktop"),
+            "[Desktop Entry]\nName=System\n",
+        );
+        write_file(
+            &system.join("kde4/amarok.desktop"),
+            "[Desktop Entry]\nName=Amarok\n",
+        );
+        write_file(
+            &system.join("Celluloid.desktop"),
+            "[Desktop Entry]\nName=Celluloid\n",
+        );
+        let dirs = [user, system];
+
+        let name =
+            |id| find_in(id, &dirs).and_then(|file| file.name_for_locale("").map(String::from));
+        assert_eq!(name("player"), Some("User".to_string()));
+        assert_eq!(name("player.desktop"), Some("User".to_string()));
+        assert_eq!(name("kde4-amarok"), Some("Amarok".to_string()));
+        assert_eq!(name("celluloid"), Some("Celluloid".to_string()));
+        assert_eq!(name("missing"), None);
+        assert_eq!(name(""), None);
+        assert_eq!(find_in("kde4-amarok", &dirs).unwrap().id(), "kde4-amarok");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
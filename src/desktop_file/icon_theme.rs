@@ -0,0 +1,318 @@
+//! Icon lookup following the [Icon Theme Specification][spec].
+//!
+//! [spec]: https://specifications.freedesktop.org/icon-theme-spec/latest/
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{data_dirs, parse_key_file};
+
+/// The theme that every theme implicitly inherits from.
+pub(super) const DEFAULT_THEME: &str = "hicolor";
+
+/// File extensions of icons, in order of preference.
+const EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+/// Returns the directories that icon themes and unthemed icons are searched in, most important
+/// first.
+pub(super) fn base_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".icons"));
+    }
+    dirs.extend(data_dirs().into_iter().map(|dir| dir.join("icons")));
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// Finds the file of an icon in the given theme, its parents and [`DEFAULT_THEME`], and then
+/// among the unthemed icons.
+pub(super) fn find_icon(
+    icon: &str,
+    theme: &str,
+    size: u32,
+    base_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        return Some(path.to_path_buf()).filter(|path| path.is_file());
+    }
+
+    let mut visited = Vec::new();
+    lookup_in_theme(icon, theme, size, base_dirs, &mut visited)
+        .or_else(|| lookup_in_theme(icon, DEFAULT_THEME, size, base_dirs, &mut visited))
+        .or_else(|| lookup_unthemed(icon, base_dirs))
+}
+
+fn lookup_in_theme(
+    icon: &str,
+    theme: &str,
+    size: u32,
+    base_dirs: &[PathBuf],
+    visited: &mut Vec<String>,
+) -> Option<PathBuf> {
+    // Guards against themes that inherit from each other.
+    if visited.iter().any(|name| name == theme) {
+        return None;
+    }
+    visited.push(theme.to_string());
+
+    let theme = IconTheme::load(theme, base_dirs)?;
+    theme.lookup(icon, size).or_else(|| {
+        theme
+            .parents
+            .iter()
+            .find_map(|parent| lookup_in_theme(icon, parent, size, base_dirs, visited))
+    })
+}
+
+fn lookup_unthemed(icon: &str, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+    base_dirs
+        .iter()
+        .flat_map(|dir| icon_files(dir, icon))
+        .find(|path| path.is_file())
+}
+
+/// Returns the paths that an icon could have in a directory, one per extension.
+fn icon_files<'a>(dir: &'a Path, icon: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+    EXTENSIONS
+        .iter()
+        .map(move |extension| dir.join(format!("{}.{}", icon, extension)))
+}
+
+#[derive(Debug)]
+struct IconTheme {
+    /// The directories of the theme in every base directory that has one.
+    roots: Vec<PathBuf>,
+
+    /// The subdirectories that contain icons, as listed in the theme's `index.theme`.
+    directories: Vec<IconDirectory>,
+
+    /// The themes to look in if an icon is missing.
+    parents: Vec<String>,
+}
+
+#[derive(Debug)]
+struct IconDirectory {
+    path: String,
+    kind: SizeKind,
+    size: u32,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SizeKind {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+impl IconTheme {
+    /// Reads the theme with the given name, or returns [`None`] if it has no `index.theme`.
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<IconTheme> {
+        let roots: Vec<PathBuf> = base_dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .filter(|root| root.is_dir())
+            .collect();
+        let index = roots
+            .iter()
+            .find_map(|root| fs::read_to_string(root.join("index.theme")).ok())?;
+        let mut groups = parse_key_file(&index);
+
+        let theme = groups.remove("Icon Theme")?;
+        let list = |key: &str| -> Vec<String> {
+            theme
+                .get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let directories = list("Directories")
+            .into_iter()
+            .filter_map(|path| {
+                let keys = groups.get(&path)?;
+                IconDirectory::parse(path, keys)
+            })
+            .collect();
+
+        Some(IconTheme {
+            roots,
+            directories,
+            parents: list("Inherits"),
+        })
+    }
+
+    /// Returns an icon from a directory of a matching size, or from the directory closest in
+    /// size.
+    fn lookup(&self, icon: &str, size: u32) -> Option<PathBuf> {
+        let mut closest: Option<(u32, PathBuf)> = None;
+        for directory in &self.directories {
+            let distance = directory.size_distance(size);
+            if closest
+                .as_ref()
+                .map_or(false, |(best, _)| distance >= *best)
+            {
+                continue;
+            }
+
+            let file = self
+                .roots
+                .iter()
+                .flat_map(|root| icon_files(&root.join(&directory.path), icon).collect::<Vec<_>>())
+                .find(|path| path.is_file());
+            if let Some(file) = file {
+                if distance == 0 {
+                    return Some(file);
+                }
+                closest = Some((distance, file));
+            }
+        }
+        closest.map(|(_, file)| file)
+    }
+}
+
+impl IconDirectory {
+    /// Reads the settings of a directory from its group in `index.theme`. Directories without a
+    /// `Size`, and ones for other scales than 1, are skipped.
+    fn parse(path: String, keys: &HashMap<String, String>) -> Option<IconDirectory> {
+        let number = |key: &str| keys.get(key).and_then(|value| value.parse::<u32>().ok());
+
+        if number("Scale").map_or(false, |scale| scale != 1) {
+            return None;
+        }
+
+        let size = number("Size")?;
+        let kind = match keys.get("Type").map(String::as_str) {
+            Some("Fixed") => SizeKind::Fixed,
+            Some("Scalable") => SizeKind::Scalable,
+            _ => SizeKind::Threshold,
+        };
+        Some(IconDirectory {
+            path,
+            kind,
+            size,
+            min_size: number("MinSize").unwrap_or(size),
+            max_size: number("MaxSize").unwrap_or(size),
+            threshold: number("Threshold").unwrap_or(2),
+        })
+    }
+
+    /// Returns how far the size is outside of the sizes that the directory is meant for, or 0 if
+    /// it matches.
+    fn size_distance(&self, size: u32) -> u32 {
+        let (min_size, max_size) = match self.kind {
+            SizeKind::Fixed => (self.size, self.size),
+            SizeKind::Scalable => (self.min_size, self.max_size),
+            SizeKind::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+
+        min_size.saturating_sub(size) + size.saturating_sub(max_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{test_dir, write_file};
+    use super::*;
+
+    #[test]
+    fn it_finds_icons_in_themes() {
+        let dir = test_dir("icon_theme");
+        let user = dir.join("user");
+        let system = dir.join("system");
+        write_file(
+            &system.join("Custom/index.theme"),
+            "[Icon Theme]\n\
+             Name=Custom\n\
+             Inherits=Base\n\
+             Directories=16x16/apps,48x48/apps,scalable/apps\n\
+             \n\
+             [16x16/apps]\n\
+             Size=16\n\
+             Type=Fixed\n\
+             \n\
+             [48x48/apps]\n\
+             Size=48\n\
+             \n\
+             [scalable/apps]\n\
+             Size=64\n\
+             MinSize=64\n\
+             MaxSize=256\n\
+             Type=Scalable\n",
+        );
+        write_file(&system.join("Custom/16x16/apps/player.png"), "");
+        write_file(&system.join("Custom/scalable/apps/player.svg"), "");
+        // Themes may be split across base directories.
+        write_file(&user.join("Custom/48x48/apps/player.png"), "");
+        write_file(
+            &system.join("Base/index.theme"),
+            "[Icon Theme]\nInherits=Custom\nDirectories=32x32/apps\n\n[32x32/apps]\nSize=32\n",
+        );
+        write_file(&system.join("Base/32x32/apps/inherited.png"), "");
+        write_file(
+            &system.join("hicolor/index.theme"),
+            "[Icon Theme]\nDirectories=32x32/apps\n\n[32x32/apps]\nSize=32\n",
+        );
+        write_file(&system.join("hicolor/32x32/apps/fallback.png"), "");
+        write_file(&system.join("unthemed.xpm"), "");
+        let base_dirs = [user.clone(), system.clone()];
+
+        let find = |icon, size| find_icon(icon, "Custom", size, &base_dirs);
+        assert_eq!(
+            find("player", 16),
+            Some(system.join("Custom/16x16/apps/player.png"))
+        );
+        assert_eq!(
+            find("player", 47),
+            Some(user.join("Custom/48x48/apps/player.png"))
+        );
+        assert_eq!(
+            find("player", 128),
+            Some(system.join("Custom/scalable/apps/player.svg"))
+        );
+        // Closest in size.
+        assert_eq!(
+            find("player", 20),
+            Some(system.join("Custom/16x16/apps/player.png"))
+        );
+        assert_eq!(
+            find("player", 512),
+            Some(system.join("Custom/scalable/apps/player.svg"))
+        );
+        assert_eq!(
+            find("inherited", 48),
+            Some(system.join("Base/32x32/apps/inherited.png"))
+        );
+        assert_eq!(
+            find("fallback", 48),
+            Some(system.join("hicolor/32x32/apps/fallback.png"))
+        );
+        assert_eq!(find("unthemed", 48), Some(system.join("unthemed.xpm")));
+        assert_eq!(find("missing", 48), None);
+        assert_eq!(
+            find_icon("player", "Missing", 48, &base_dirs),
+            None,
+            "Only hicolor and unthemed icons are searched for missing themes"
+        );
+
+        let absolute = system.join("unthemed.xpm");
+        assert_eq!(find(absolute.to_str().unwrap(), 48), Some(absolute.clone()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,19 +1,44 @@
 use super::{
-    DBusError, LoopStatus, Metadata, PlaybackStatus, Player, Progress, TrackID, TrackList,
-    TrackListError,
+    CancelHandle, Capabilities, DBusError, LoopStatus, Metadata, PlaybackStatus, Player, Playlist,
+    Progress, TrackID, TrackList, TrackListError,
 };
-use crate::pooled_connection::MprisEvent;
+use crate::pooled_connection::{EventSubscription, MprisEvent};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 /// Represents a change in [`Player`] state.
 ///
 /// Note that this does not include position changes (seeking in a track or normal progress of time
 /// for playing media).
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Event {
     /// [`Player`] was shut down / quit.
     PlayerShutDown,
 
+    /// The player quit, but it is being followed by a
+    /// [`PersistentPlayer`](crate::PersistentPlayer), which will reconnect when it starts again.
+    ///
+    /// Only emitted by [`PersistentPlayerEvents`](crate::PersistentPlayerEvents), instead of
+    /// [`Event::PlayerShutDown`].
+    Disconnected,
+
+    /// A [`PersistentPlayer`](crate::PersistentPlayer) bound to a new instance of the player after
+    /// it was restarted.
+    ///
+    /// Only emitted by [`PersistentPlayerEvents`](crate::PersistentPlayerEvents).
+    Reconnected,
+
+    /// Some events of the [`Player`] were lost because they were not read fast enough. See
+    /// [`OverflowPolicy`](crate::OverflowPolicy) for how many events can be queued up, and what
+    /// happens to the rest.
+    ///
+    /// Anything that was kept up to date from earlier events should be read from the player again.
+    EventsLost,
+
     /// [`Player`] was paused.
     Paused,
 
@@ -77,10 +102,35 @@ pub enum Event {
 
     /// The track list was replaced.
     TrackListReplaced,
+
+    /// A [`Playlist`] changed, for example because it was renamed. The new [`Playlist`] is provided.
+    ///
+    /// Note that the changed playlist does not have to be the active one.
+    PlaylistChanged(Playlist),
+
+    /// The [`Player`]'s active [`Playlist`] changed. The new active [`Playlist`] is provided, or
+    /// [`None`] if no playlist is active anymore.
+    ActivePlaylistChanged(Option<Playlist>),
+
+    /// The number of [`Playlist`]s available on the [`Player`] changed. The new count is provided.
+    PlaylistCountChanged(u32),
+
+    /// The [`Capabilities`] of the [`Player`] changed, for example because it can no longer go to
+    /// the next track.
+    CapabilitiesChanged {
+        /// The capabilities that the player gained.
+        added: Capabilities,
+
+        /// The capabilities that the player lost.
+        removed: Capabilities,
+    },
 }
 
 /// Errors that can occur while processing event streams.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum EventError {
     /// Something went wrong with the D-Bus communication. See the [`DBusError`] type.
     #[error("D-Bus communication failed: {0}")]
@@ -89,6 +139,10 @@ pub enum EventError {
     /// Something went wrong with the track list. See the [`TrackListError`] type.
     #[error("TrackList could not be refreshed: {0}")]
     TrackListError(#[from] TrackListError),
+
+    /// Waiting for the next event was cancelled with a [`CancelHandle`].
+    #[error("Waiting for events was cancelled")]
+    Cancelled,
 }
 
 /// Iterator that blocks forever until the player has an [`Event`].
@@ -103,188 +157,574 @@ pub struct PlayerEvents<'a> {
     /// [`Player`] to watch.
     player: &'a Player,
 
+    /// State used to find events.
+    state: EventState,
+
+    /// The events of the player that were not read yet.
+    events: Rc<EventSubscription>,
+}
+
+/// The parts of the `Playlists` interface that can change without a dedicated signal.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PlaylistsState {
+    pub(crate) active_playlist: Option<Playlist>,
+    pub(crate) playlist_count: u32,
+}
+
+/// A player that [`Event`]s can be read from in a blocking fashion.
+pub(crate) trait EventSource {
+    /// Keeps the [`MprisEvent`]s of the player coming in for a single reader.
+    type Subscription;
+
+    /// Checks if the player is still connected.
+    fn is_running(&self) -> bool;
+
+    /// Blocks until the player has pending [`MprisEvent`]s for the reader, and returns them.
+    fn wait_for_events(
+        &self,
+        subscription: &Self::Subscription,
+    ) -> Result<Vec<MprisEvent>, EventError>;
+
+    /// Reads the current [`Progress`] of the player.
+    fn progress(&self) -> Result<Progress, DBusError>;
+
+    /// Reads the [`Capabilities`] of the `org.mpris.MediaPlayer2` interface of the player.
+    fn root_capabilities(&self) -> Result<Capabilities, DBusError>;
+
+    /// Reads the current track list of the player, if it supports track lists.
+    fn checked_get_track_list(&self) -> Result<Option<TrackList>, DBusError>;
+
+    /// Reads the current state of the `Playlists` interface, if the player supports playlists.
+    fn playlists_state(&self) -> Result<Option<PlaylistsState>, DBusError>;
+}
+
+impl EventSource for Player {
+    type Subscription = Rc<EventSubscription>;
+
+    fn is_running(&self) -> bool {
+        Player::is_running(self)
+    }
+
+    fn wait_for_events(
+        &self,
+        subscription: &Self::Subscription,
+    ) -> Result<Vec<MprisEvent>, EventError> {
+        if self
+            .connection()
+            .process_events_blocking_until_queued(subscription)
+        {
+            Ok(subscription.take_events())
+        } else {
+            Err(EventError::Cancelled)
+        }
+    }
+
+    fn progress(&self) -> Result<Progress, DBusError> {
+        Progress::from_player(self)
+    }
+
+    fn root_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_root_capabilities()
+    }
+
+    fn checked_get_track_list(&self) -> Result<Option<TrackList>, DBusError> {
+        Player::checked_get_track_list(self)
+    }
+
+    fn playlists_state(&self) -> Result<Option<PlaylistsState>, DBusError> {
+        if !self.supports_playlists() {
+            return Ok(None);
+        }
+
+        Ok(Some(PlaylistsState {
+            active_playlist: self.get_active_playlist()?,
+            playlist_count: self.get_playlist_count()?,
+        }))
+    }
+}
+
+/// The state of a blocking event iterator, which is diffed against the new state of the player
+/// whenever it emits signals.
+#[derive(Debug)]
+pub(crate) struct EventState {
     /// Queued up events found after the last signal.
     buffer: Vec<Event>,
 
     /// Used to diff older state to find events.
     last_progress: Progress,
 
+    /// Capabilities of the `org.mpris.MediaPlayer2` interface. The ones of the
+    /// `org.mpris.MediaPlayer2.Player` interface are part of the [`Progress`].
+    root_capabilities: Capabilities,
+
     /// Current tracklist of the player. Will be kept up to date.
     track_list: Option<TrackList>,
+
+    /// Current playlists state of the player, if it supports playlists. Used to diff older state
+    /// to find events.
+    playlists: Option<PlaylistsState>,
+}
+
+impl EventState {
+    pub(crate) fn new<P: EventSource>(player: &P) -> Result<Self, DBusError> {
+        Ok(EventState::from_parts(
+            player.progress()?,
+            player.root_capabilities()?,
+            player.checked_get_track_list()?,
+            player.playlists_state()?,
+        ))
+    }
+
+    /// Creates the state from parts of the player's state that were already read.
+    pub(crate) fn from_parts(
+        progress: Progress,
+        root_capabilities: Capabilities,
+        track_list: Option<TrackList>,
+        playlists: Option<PlaylistsState>,
+    ) -> Self {
+        EventState {
+            buffer: Vec::new(),
+            last_progress: progress,
+            root_capabilities,
+            track_list,
+            playlists,
+        }
+    }
+
+    pub(crate) fn track_list(&self) -> Option<&TrackList> {
+        self.track_list.as_ref()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.root_capabilities | self.last_progress.capabilities()
+    }
+
+    /// Returns the next [`Event`], blocking until the player has one. Returns [`None`] when the
+    /// player is no longer running.
+    pub(crate) fn next<P: EventSource>(
+        &mut self,
+        player: &P,
+        subscription: &P::Subscription,
+    ) -> Option<Result<Event, EventError>> {
+        while self.buffer.is_empty() {
+            // Stop iteration when player is not running. Why beat a dead horse?
+            if !player.is_running() {
+                return None;
+            }
+
+            match self.read_events(player, subscription) {
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            };
+        }
+
+        self.pop_event().map(Ok)
+    }
+
+    /// Removes the oldest queued up [`Event`], without blocking.
+    pub(crate) fn pop_event(&mut self) -> Option<Event> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(self.buffer.remove(0))
+        }
+    }
+
+    fn read_events<P: EventSource>(
+        &mut self,
+        player: &P,
+        subscription: &P::Subscription,
+    ) -> Result<(), EventError> {
+        let events = player.wait_for_events(subscription)?;
+        self.apply_events(player, events).map(|_| ())
+    }
+
+    /// Queues up the [`Event`]s that the given [`MprisEvent`]s of the player translate to.
+    ///
+    /// Returns [`true`] if the player shut down.
+    pub(crate) fn apply_events<P: EventSource>(
+        &mut self,
+        player: &P,
+        events: Vec<MprisEvent>,
+    ) -> Result<bool, EventError> {
+        let reloads = match self.apply_mpris_events(events) {
+            Some(reloads) => reloads,
+            None => return Ok(true),
+        };
+
+        let mut reloaded = Reloaded::default();
+        if reloads.root {
+            reloaded.root_capabilities = Some(player.root_capabilities()?);
+        }
+        if reloads.progress {
+            reloaded.progress = Some(player.progress()?);
+        }
+        if reloads.playlists {
+            reloaded.playlists = player.playlists_state()?;
+        }
+
+        if self.apply_reloaded(&reloads, reloaded) {
+            self.apply_track_list(player.checked_get_track_list()?);
+        }
+        Ok(false)
+    }
+
+    /// Queues up the [`Event`]s that the given [`MprisEvent`]s directly translate to, and returns
+    /// the parts of the player's state that have to be read again for the rest.
+    ///
+    /// Returns [`None`] if the player shut down.
+    pub(crate) fn apply_mpris_events(&mut self, events: Vec<MprisEvent>) -> Option<Reloads> {
+        let mut reloads = Reloads::default();
+        for event in events {
+            let shut_down = apply_mpris_event(
+                event,
+                &mut self.track_list,
+                &mut self.playlists,
+                &mut self.buffer,
+                &mut reloads,
+            );
+            if shut_down {
+                return None;
+            }
+        }
+        Some(reloads)
+    }
+
+    /// Queues up the [`Event`]s found by diffing the state that was read again for `reloads`.
+    ///
+    /// Returns [`true`] if the track list has to be read again, and passed to
+    /// [`apply_track_list`](Self::apply_track_list).
+    pub(crate) fn apply_reloaded(&mut self, reloads: &Reloads, reloaded: Reloaded) -> bool {
+        let old_capabilities = self.capabilities();
+        if let Some(root_capabilities) = reloaded.root_capabilities {
+            self.root_capabilities = root_capabilities;
+        }
+
+        let mut reload_track_list = reloads.track_list;
+        if let Some(progress) = reloaded.progress {
+            reload_track_list |=
+                detect_progress_events(&self.last_progress, &progress, &mut self.buffer);
+            self.last_progress = progress;
+        }
+        detect_capabilities_events(old_capabilities, self.capabilities(), &mut self.buffer);
+
+        if let Some(playlists) = reloaded.playlists {
+            detect_playlists_events(self.playlists.as_ref(), &playlists, &mut self.buffer);
+            self.playlists = Some(playlists);
+        }
+
+        reload_track_list && self.track_list.is_some()
+    }
+
+    /// Replaces the track list with one that was read again.
+    pub(crate) fn apply_track_list(&mut self, new_tracks: Option<TrackList>) {
+        if let Some(new_tracks) = new_tracks {
+            match self.track_list {
+                Some(ref mut list) => list.replace(new_tracks),
+                None => self.track_list = Some(new_tracks),
+            }
+            self.buffer.push(Event::TrackListReplaced);
+        }
+    }
 }
 
 impl PlayerEvents<'_> {
-    pub(crate) fn new(player: &Player) -> Result<PlayerEvents, DBusError> {
-        let progress = Progress::from_player(player)?;
+    pub(crate) fn new(player: &Player) -> Result<PlayerEvents<'_>, DBusError> {
+        // Subscribe before reading the state, so no change is missed.
+        let events = player.subscribe_events();
         Ok(PlayerEvents {
             player,
-            buffer: Vec::new(),
-            last_progress: progress,
-            track_list: player.checked_get_track_list()?,
+            state: EventState::new(player)?,
+            events,
         })
     }
 
     /// Current tracklist of the player. Will be kept up to date.
     pub fn track_list(&self) -> Option<&TrackList> {
-        self.track_list.as_ref()
+        self.state.track_list()
+    }
+
+    /// Makes the iterator return [`EventError::Cancelled`] instead of waiting for the next event
+    /// once the handle is cancelled, even if it is blocked already.
+    ///
+    /// See [`CancelHandle`] for an example.
+    pub fn with_cancel_handle(self, cancel: CancelHandle) -> Self {
+        self.events.set_cancel_handle(cancel);
+        self
     }
 
-    fn read_events(&mut self) -> Result<(), EventError> {
-        self.player.process_events_blocking_until_received();
+    /// Returns the next [`Event`] if the player already has one, without blocking.
+    ///
+    /// Returns `Ok(None)` if there is no event right now. Unlike [`next`](Iterator::next), this
+    /// does not check if the player is still running; it stops returning events after
+    /// [`Event::PlayerShutDown`].
+    ///
+    /// Useful for driving the iterator from another loop, like the frame loop of a UI.
+    pub fn try_next(&mut self) -> Result<Option<Event>, EventError> {
+        self.next_timeout(Duration::from_millis(0))
+    }
 
-        let mut new_progress: Option<Progress> = None;
-        let mut reload_track_list = false;
+    /// Returns the next [`Event`], blocking for at most the given timeout until the player has
+    /// one.
+    ///
+    /// Returns `Ok(None)` if there was no event in time, or [`EventError::Cancelled`] if waiting
+    /// was cancelled. See [`try_next`](Self::try_next) for the differences to
+    /// [`next`](Iterator::next).
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Option<Event>, EventError> {
+        // A timeout too large for an `Instant`, like `Duration::MAX`, means waiting forever.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if let Some(event) = self.state.pop_event() {
+                return Ok(Some(event));
+            }
 
-        for event in self.player.pending_events().into_iter() {
-            match event {
-                MprisEvent::PlayerQuit => {
-                    self.buffer.push(Event::PlayerShutDown);
-                    return Ok(());
-                }
-                MprisEvent::PlayerPropertiesChanged => {
-                    if new_progress.is_none() {
-                        new_progress = Some(Progress::from_player(self.player)?);
-                    }
-                }
-                MprisEvent::Seeked { position_in_us } => {
-                    self.buffer.push(Event::Seeked { position_in_us })
-                }
-                MprisEvent::TrackListPropertiesChanged => {
-                    reload_track_list = true;
-                }
-                MprisEvent::TrackListReplaced { ids } => {
-                    if let Some(ref mut list) = self.track_list {
-                        list.replace(ids.into_iter().map(TrackID::from).collect());
-                    }
-                    self.buffer.push(Event::TrackListReplaced);
-                }
-                MprisEvent::TrackAdded { after_id, metadata } => {
-                    if let Some(id) = metadata.track_id() {
-                        if let Some(ref mut list) = self.track_list {
-                            list.insert(&after_id, metadata);
-                        }
-                        self.buffer.push(Event::TrackAdded(id));
+            let connection = self.player.connection();
+            connection.process_pending_messages();
+            let events = self.events.take_events();
+            if !events.is_empty() {
+                self.state.apply_events(self.player, events)?;
+                continue;
+            }
+
+            let time_left = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(time_left) if !time_left.is_zero() => Some(time_left),
+                    _ => return Ok(None),
+                },
+                None => None,
+            };
+            match (self.events.cancel_handle(), time_left) {
+                (Some(cancel), time_left) => {
+                    connection
+                        .process_events_blocking_until_received_or_cancelled(time_left, &cancel);
+                    if cancel.is_cancelled() {
+                        return Err(EventError::Cancelled);
                     }
                 }
-                MprisEvent::TrackRemoved { id } => {
-                    if let Some(ref mut list) = self.track_list {
-                        list.remove(&id);
-                    }
-                    self.buffer.push(Event::TrackRemoved(id));
-                }
-                MprisEvent::TrackMetadataChanged { old_id, metadata } => {
-                    if let Some(ref mut list) = self.track_list {
-                        if let Some(new_id) = list.replace_track_metadata(&old_id, metadata) {
-                            self.buffer
-                                .push(Event::TrackMetadataChanged { old_id, new_id });
-                        }
-                    }
+                (None, Some(time_left)) => {
+                    connection.process_events_blocking_until_received_for(time_left);
                 }
+                (None, None) => connection.process_events_blocking_until_received(),
             }
         }
+    }
+}
 
-        if let Some(progress) = new_progress {
-            self.detect_playback_status_events(&progress);
-            self.detect_loop_status_events(&progress);
-            reload_track_list |= self.detect_shuffle_events(&progress);
-            self.detect_volume_events(&progress);
-            self.detect_playback_rate_events(&progress);
-            self.detect_metadata_events(&progress);
-            self.last_progress = progress;
-        }
+/// The parts of a player's state that have to be read again after applying [`MprisEvent`]s with
+/// [`apply_mpris_event`].
+#[derive(Debug, Default)]
+pub(crate) struct Reloads {
+    pub(crate) root: bool,
+    pub(crate) progress: bool,
+    pub(crate) track_list: bool,
+    pub(crate) playlists: bool,
+}
+
+/// The parts of a player's state that were read again for [`Reloads`].
+#[derive(Debug, Default)]
+pub(crate) struct Reloaded {
+    pub(crate) root_capabilities: Option<Capabilities>,
+    pub(crate) progress: Option<Progress>,
+    /// The playlists state, if the player supports playlists.
+    pub(crate) playlists: Option<PlaylistsState>,
+}
 
-        if reload_track_list && self.track_list.is_some() {
-            if let Some(new_tracks) = self.player.checked_get_track_list()? {
-                match self.track_list {
-                    Some(ref mut list) => list.replace(new_tracks),
-                    None => self.track_list = Some(new_tracks),
+/// Applies an [`MprisEvent`] to the known track list and playlists state of a player, and pushes
+/// the [`Event`]s that it directly translates to on `events`. Changes that require reading more
+/// state from the player are noted in `reloads` instead.
+///
+/// Returns [`true`] if the player shut down, in which case the remaining events should be ignored.
+fn apply_mpris_event(
+    event: MprisEvent,
+    track_list: &mut Option<TrackList>,
+    playlists: &mut Option<PlaylistsState>,
+    events: &mut Vec<Event>,
+    reloads: &mut Reloads,
+) -> bool {
+    match event {
+        MprisEvent::PlayerQuit => {
+            events.push(Event::PlayerShutDown);
+            return true;
+        }
+        MprisEvent::RootPropertiesChanged => reloads.root = true,
+        MprisEvent::PlayerPropertiesChanged => reloads.progress = true,
+        MprisEvent::Seeked { position_in_us } => events.push(Event::Seeked { position_in_us }),
+        MprisEvent::TrackListPropertiesChanged => reloads.track_list = true,
+        MprisEvent::TrackListReplaced { ids } => {
+            if let Some(ref mut list) = track_list {
+                list.replace(ids.into_iter().collect());
+            }
+            events.push(Event::TrackListReplaced);
+        }
+        MprisEvent::TrackAdded { after_id, metadata } => {
+            if let Some(id) = metadata.track_id() {
+                if let Some(ref mut list) = track_list {
+                    list.insert(&after_id, metadata);
+                }
+                events.push(Event::TrackAdded(id));
+            }
+        }
+        MprisEvent::TrackRemoved { id } => {
+            if let Some(ref mut list) = track_list {
+                list.remove(&id);
+            }
+            events.push(Event::TrackRemoved(id));
+        }
+        MprisEvent::TrackMetadataChanged { old_id, metadata } => {
+            if let Some(ref mut list) = track_list {
+                if let Some(new_id) = list.replace_track_metadata(&old_id, metadata) {
+                    events.push(Event::TrackMetadataChanged { old_id, new_id });
                 }
-                self.buffer.push(Event::TrackListReplaced);
             }
         }
+        MprisEvent::PlaylistsPropertiesChanged => reloads.playlists = true,
+        MprisEvent::PlaylistChanged { playlist } => {
+            if let Some(ref mut state) = playlists {
+                // Keep the active playlist up to date if it was the one that changed, so it is
+                // not reported as a new active playlist later.
+                if let Some(ref mut active) = state.active_playlist {
+                    if active.id() == playlist.id() {
+                        *active = playlist.clone();
+                    }
+                }
+            }
+            events.push(Event::PlaylistChanged(playlist));
+        }
+        MprisEvent::EventsDropped => events.push(Event::EventsLost),
+        MprisEvent::ResyncNeeded => {
+            events.push(Event::EventsLost);
+            reloads.root = true;
+            reloads.progress = true;
+            reloads.track_list = true;
+            reloads.playlists = true;
+        }
+    }
+    false
+}
 
-        Ok(())
+/// Compares two [`Progress`] snapshots of the same player and pushes an [`Event`] for every
+/// difference to `events`.
+///
+/// Returns [`true`] if the shuffle status changed, as that could also have changed the order of
+/// the player's [`TrackList`].
+fn detect_progress_events(old: &Progress, new: &Progress, events: &mut Vec<Event>) -> bool {
+    match new.playback_status() {
+        status if old.playback_status() == status => {}
+        PlaybackStatus::Playing => events.push(Event::Playing),
+        PlaybackStatus::Paused => events.push(Event::Paused),
+        PlaybackStatus::Stopped => events.push(Event::Stopped),
     }
 
-    fn detect_playback_status_events(&mut self, new_progress: &Progress) {
-        match new_progress.playback_status() {
-            status if self.last_progress.playback_status() == status => {}
-            PlaybackStatus::Playing => self.buffer.push(Event::Playing),
-            PlaybackStatus::Paused => self.buffer.push(Event::Paused),
-            PlaybackStatus::Stopped => self.buffer.push(Event::Stopped),
-        }
+    if old.loop_status() != new.loop_status() {
+        events.push(Event::LoopingChanged(new.loop_status()));
     }
 
-    fn detect_loop_status_events(&mut self, new_progress: &Progress) {
-        let loop_status = new_progress.loop_status();
-        if self.last_progress.loop_status() != loop_status {
-            self.buffer.push(Event::LoopingChanged(loop_status));
-        }
+    let shuffle_toggled = old.shuffle() != new.shuffle();
+    if shuffle_toggled {
+        events.push(Event::ShuffleToggled(new.shuffle()));
     }
 
-    fn detect_shuffle_events(&mut self, new_progress: &Progress) -> bool {
-        let status = new_progress.shuffle();
-        if self.last_progress.shuffle() != status {
-            self.buffer.push(Event::ShuffleToggled(status));
-            true
-        } else {
-            false
-        }
+    if is_different_float(old.current_volume(), new.current_volume()) {
+        events.push(Event::VolumeChanged(new.current_volume()));
     }
 
-    fn detect_volume_events(&mut self, new_progress: &Progress) {
-        let volume = new_progress.current_volume();
-        if is_different_float(self.last_progress.current_volume(), volume) {
-            self.buffer.push(Event::VolumeChanged(volume));
-        }
+    if is_different_float(old.playback_rate(), new.playback_rate()) {
+        events.push(Event::PlaybackRateChanged(new.playback_rate()));
     }
 
-    fn detect_playback_rate_events(&mut self, new_progress: &Progress) {
-        let rate = new_progress.playback_rate();
-        if is_different_float(self.last_progress.playback_rate(), rate) {
-            self.buffer.push(Event::PlaybackRateChanged(rate));
-        }
+    let new_metadata = new.metadata();
+    let old_metadata = old.metadata();
+
+    // As a workaround for Players not setting a valid track ID, we also check against the URL
+    // Title and artists are checked to detect changes for streams (radios) because track ID and URL don't change.
+    // Title is checked first because most radios set title to `Artist - Title` and have the station name in artists.
+
+    if old_metadata.track_id() != new_metadata.track_id()
+        || old_metadata.url() != new_metadata.url()
+        || old_metadata.title() != new_metadata.title()
+        || old_metadata.artists() != new_metadata.artists()
+    {
+        events.push(Event::TrackChanged(new_metadata.clone()));
     }
 
-    fn detect_metadata_events(&mut self, new_progress: &Progress) {
-        let new_metadata = new_progress.metadata();
-        let old_metadata = self.last_progress.metadata();
+    shuffle_toggled
+}
+
+/// Compares the [`Capabilities`] of a player before and after applying [`MprisEvent`]s, and pushes
+/// an [`Event::CapabilitiesChanged`] to `events` if they differ.
+fn detect_capabilities_events(old: Capabilities, new: Capabilities, events: &mut Vec<Event>) {
+    if old != new {
+        events.push(Event::CapabilitiesChanged {
+            added: new - old,
+            removed: old - new,
+        });
+    }
+}
 
-        // As a workaround for Players not setting a valid track ID, we also check against the URL
-        // Title and artists are checked to detect changes for streams (radios) because track ID and URL don't change.
-        // Title is checked first because most radios set title to `Artist - Title` and have the station name in artists.
+/// Compares the state of a player's `Playlists` interface with the previous state (if any) and
+/// pushes an [`Event`] for every difference to `events`.
+fn detect_playlists_events(
+    old: Option<&PlaylistsState>,
+    new: &PlaylistsState,
+    events: &mut Vec<Event>,
+) {
+    let (old_active, old_count) = match old {
+        Some(old) => (old.active_playlist.as_ref(), Some(old.playlist_count)),
+        None => (None, None),
+    };
+
+    if old_active != new.active_playlist.as_ref() {
+        events.push(Event::ActivePlaylistChanged(new.active_playlist.clone()));
+    }
 
-        if old_metadata.track_id() != new_metadata.track_id()
-            || old_metadata.url() != new_metadata.url()
-            || old_metadata.title() != new_metadata.title()
-            || old_metadata.artists() != new_metadata.artists()
-        {
-            self.buffer.push(Event::TrackChanged(new_metadata.clone()));
-        }
+    if old_count != Some(new.playlist_count) {
+        events.push(Event::PlaylistCountChanged(new.playlist_count));
     }
 }
 
 fn is_different_float(a: f64, b: f64) -> bool {
-    (a - b).abs() >= ::std::f64::EPSILON
+    (a - b).abs() >= f64::EPSILON
 }
 
 impl<'a> Iterator for PlayerEvents<'a> {
     type Item = Result<Event, EventError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.buffer.is_empty() {
-            // Stop iteration when player is not running. Why beat a dead horse?
-            if !self.player.is_running() {
-                return None;
-            }
-
-            match self.read_events() {
-                Ok(_) => {}
-                Err(err) => return Some(Err(err)),
-            };
-        }
+        self.state.next(self.player, &self.events)
+    }
+}
 
-        let event = self.buffer.remove(0);
-        Some(Ok(event))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve};
+
+    #[test]
+    fn it_polls_for_signals() {
+        let (bus_name, handle) = serve("mpris_rs_test_polling");
+        let player = connect(bus_name);
+        let mut events = player.events().unwrap();
+
+        assert!(matches!(events.try_next(), Ok(None)));
+        assert!(matches!(
+            events.next_timeout(Duration::from_millis(50)),
+            Ok(None)
+        ));
+
+        player.play().unwrap();
+        assert!(matches!(
+            events.next_timeout(Duration::from_secs(5)),
+            Ok(Some(Event::Playing))
+        ));
+        assert!(matches!(events.try_next(), Ok(None)));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+        // Timeouts that don't fit an `Instant` wait without a deadline.
+        assert!(matches!(
+            events.next_timeout(Duration::MAX),
+            Ok(Some(Event::PlayerShutDown))
+        ));
+        assert!(matches!(events.try_next(), Ok(None)));
     }
 }
@@ -2,16 +2,23 @@ use thiserror::Error;
 
 use std::iter::FusedIterator;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use dbus::ffidisp::{BusType, Connection};
+use dbus::ffidisp::{BusType, Connection, Watch};
 use dbus::{arg, Message};
 
 use super::DBusError;
+use crate::active::ActivePlayerTracker;
+use crate::bus_name::BusNameInfo;
+use crate::launch::{self, LaunchError};
 use crate::player::{Player, DEFAULT_TIMEOUT_MS, MPRIS2_PREFIX};
-use crate::pooled_connection::PooledConnection;
-use crate::PlaybackStatus;
+use crate::pooled_connection::{OverflowPolicy, PooledConnection};
+use crate::selector::PlayerSelector;
+use crate::watch::PlayerWatcher;
+use crate::AllPlayerEvents;
+use crate::{Metadata, PlaybackStatus};
 
-const LIST_NAMES_TIMEOUT_MS: i32 = 500;
+pub(crate) const LIST_NAMES_TIMEOUT_MS: i32 = 500;
 
 /// This enum encodes possible error cases that could happen when finding players.
 #[derive(Debug, Error)]
@@ -73,6 +80,53 @@ impl PlayerFinder {
         self.player_timeout_ms = timeout_ms;
     }
 
+    /// Get the number of events that a single reader, like a [`PlayerEvents`](crate::PlayerEvents) iterator, can queue up for a
+    /// player before the [`OverflowPolicy`] applies.
+    ///
+    /// Can be set with [`set_event_queue_capacity`][Self::set_event_queue_capacity]
+    pub fn event_queue_capacity(&self) -> usize {
+        self.connection.event_queue_limit().capacity
+    }
+
+    /// Set the number of events that a single reader can queue up for a player. Only readers
+    /// created afterwards are affected. The default is 1024.
+    ///
+    /// Events are only queued up for players that somebody reads the events of. A capacity of 0 is
+    /// treated as 1.
+    pub fn set_event_queue_capacity(&mut self, capacity: usize) {
+        let mut limit = self.connection.event_queue_limit();
+        limit.capacity = capacity;
+        self.connection.set_event_queue_limit(limit);
+    }
+
+    /// Get what happens when a reader does not keep up with the events of a player.
+    ///
+    /// Can be set with [`set_event_overflow_policy`][Self::set_event_overflow_policy]
+    pub fn event_overflow_policy(&self) -> OverflowPolicy {
+        self.connection.event_queue_limit().overflow
+    }
+
+    /// Set what happens when a reader does not keep up with the events of a player. Only readers
+    /// created afterwards are affected.
+    pub fn set_event_overflow_policy(&mut self, overflow: OverflowPolicy) {
+        let mut limit = self.connection.event_queue_limit();
+        limit.overflow = overflow;
+        self.connection.set_event_queue_limit(limit);
+    }
+
+    /// Get if [`Player`]s created through this finder cache their properties.
+    ///
+    /// Can be set with [`set_property_caching`][Self::set_property_caching]
+    pub fn property_caching(&self) -> bool {
+        self.connection.property_caching()
+    }
+
+    /// Set if [`Player`]s created through this finder from now on cache their properties. The
+    /// default is [`true`]. See [`Player::set_property_caching`] for details.
+    pub fn set_property_caching(&mut self, enabled: bool) {
+        self.connection.set_property_caching(enabled);
+    }
+
     /// Find all available [`Player`]s in the connection.
     ///
     /// Will return an empty [`Vec`] and not [`NoPlayerFound`](FindingError::NoPlayerFound) if there are no players.
@@ -82,6 +136,35 @@ impl PlayerFinder {
             .collect()
     }
 
+    /// Find all available [`Player`]s in the connection, grouped by application.
+    ///
+    /// Players with several running instances, like one per browser tab, end up in a single
+    /// [`PlayerGroup`]. See [`BusNameInfo`] for how the application of a player is determined.
+    /// Groups are returned in the order their first player was found.
+    ///
+    /// Players that can not be read, for example because they quit while being listed, are
+    /// skipped. If a player's desktop entry can't be read, only its bus name is used.
+    pub fn find_grouped(&self) -> Result<Vec<PlayerGroup>, FindingError> {
+        let players = self
+            .iter_players()?
+            .filter_map(Result::ok)
+            .map(|player| {
+                let info = player
+                    .get_bus_name_info()
+                    .unwrap_or_else(|_| BusNameInfo::parse(player.bus_name()));
+                (info, player)
+            })
+            .collect();
+
+        Ok(group_by_application(players)
+            .into_iter()
+            .map(|(info, players)| PlayerGroup {
+                application: info.application().to_string(),
+                players,
+            })
+            .collect())
+    }
+
     /// Return the first found [`Player`] regardless of state.
     pub fn find_first(&self) -> Result<Player, FindingError> {
         if let Some(player) = self.iter_players()?.next() {
@@ -100,42 +183,12 @@ impl PlayerFinder {
     pub fn find_active(&self) -> Result<Player, FindingError> {
         let players: PlayerIter = self.iter_players()?;
 
-        match self.find_active_player(players)? {
+        match find_active_player(players, Player::get_playback_status, Player::get_metadata)? {
             Some(player) => Ok(player),
             None => Err(FindingError::NoPlayerFound),
         }
     }
 
-    /// Finds the index of an "active" player. Follows the order mentioned in [`find_active`](Self::find_active).
-    fn find_active_player(&self, players: PlayerIter) -> Result<Option<Player>, DBusError> {
-        if players.len() == 0 {
-            return Ok(None);
-        }
-
-        let mut first_paused: Option<Player> = None;
-        let mut first_with_track: Option<Player> = None;
-        let mut first_found: Option<Player> = None;
-
-        for player in players {
-            let player = player?;
-            let player_status = player.get_playback_status()?;
-
-            if player_status == PlaybackStatus::Playing {
-                return Ok(Some(player));
-            }
-
-            if first_paused.is_none() && player_status == PlaybackStatus::Paused {
-                first_paused.replace(player);
-            } else if first_with_track.is_none() && !player.get_metadata()?.is_empty() {
-                first_with_track.replace(player);
-            } else if first_found.is_none() {
-                first_found.replace(player);
-            }
-        }
-
-        Ok(first_paused.or(first_with_track).or(first_found))
-    }
-
     /// Find a [`Player`] by it's MPRIS [`Identity`][identity]. Returns [`NoPlayerFound`](FindingError::NoPlayerFound) if no direct match found.
     ///
     /// [identity]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:Identity
@@ -149,6 +202,140 @@ impl PlayerFinder {
         Err(FindingError::NoPlayerFound)
     }
 
+    /// Find the [`Player`] that a [`PlayerSelector`] picks. See [`PlayerSelector`] for the syntax.
+    ///
+    /// If the highest priority term matches several players, the "active" one of them is returned,
+    /// as described in [`find_active`](Self::find_active). Players that can not be read are
+    /// skipped. Returns [`NoPlayerFound`](FindingError::NoPlayerFound) if no player matches.
+    ///
+    /// ```no_run
+    /// use mpris::{PlayerFinder, PlayerSelector};
+    ///
+    /// let selector: PlayerSelector = "spotify,vlc,%any,!firefox".parse().unwrap();
+    /// let player = PlayerFinder::new().unwrap().find_by_selector(&selector);
+    /// ```
+    pub fn find_by_selector(&self, selector: &PlayerSelector) -> Result<Player, FindingError> {
+        // A player that can't be read, for example because it is quitting right now, must not
+        // keep the others from being found.
+        let players = self
+            .iter_players()?
+            .filter_map(Result::ok)
+            .collect::<Vec<Player>>();
+        find_active_readable_player(selector.select(players)).ok_or(FindingError::NoPlayerFound)
+    }
+
+    /// Find the [`Player`] that a [`PlayerSelector`] picks, waiting for one to start if none is
+    /// running yet.
+    ///
+    /// If a matching player is already running, it is returned right away, like
+    /// [`find_by_selector`](Self::find_by_selector) does. Otherwise this blocks until a player
+    /// that matches the selector appears on the bus, without polling it. Players that appear but
+    /// can not be read are skipped. Returns [`NoPlayerFound`](FindingError::NoPlayerFound) if none
+    /// appeared within the timeout.
+    ///
+    /// ```no_run
+    /// use mpris::{PlayerFinder, PlayerSelector};
+    /// use std::time::Duration;
+    ///
+    /// let selector: PlayerSelector = "spotify".parse().unwrap();
+    /// let finder = PlayerFinder::new().unwrap();
+    /// let player = finder.wait_for(&selector, Duration::from_secs(30)).unwrap();
+    /// ```
+    pub fn wait_for(
+        &self,
+        selector: &PlayerSelector,
+        timeout: Duration,
+    ) -> Result<Player, FindingError> {
+        // A timeout too large for an `Instant`, like `Duration::MAX`, means waiting forever.
+        let deadline = Instant::now().checked_add(timeout);
+        // Start watching for new players before looking for a running one, so none are missed.
+        let name_changes = self.connection.watch_names();
+
+        // Players that can't be read are skipped here as well, so they don't end the wait early.
+        let running = self
+            .iter_players()?
+            .filter_map(Result::ok)
+            .collect::<Vec<Player>>();
+        if let Some(player) = find_active_readable_player(selector.select(running)) {
+            return Ok(player);
+        }
+
+        self.connection
+            .wait_for_acquired_name(
+                &name_changes,
+                deadline,
+                |bus_name| -> Result<Option<Player>, FindingError> {
+                    // A candidate that can't be read, for example because it quit again right
+                    // away, is skipped instead of ending the wait for the right one.
+                    let player = match Player::for_pooled_connection(
+                        self.connection.clone(),
+                        bus_name,
+                        self.player_timeout_ms,
+                    ) {
+                        Ok(player) => player,
+                        Err(_) => return Ok(None),
+                    };
+                    match selector.matches(&player) {
+                        Ok(true) => Ok(Some(player)),
+                        Ok(false) | Err(_) => Ok(None),
+                    }
+                },
+            )?
+            .ok_or(FindingError::NoPlayerFound)
+    }
+
+    /// Starts the player with the given name, like `vlc`, and returns it once it appeared on the
+    /// bus. If the player is already running, it is returned right away.
+    ///
+    /// The player is started through D-Bus activation of `org.mpris.MediaPlayer2.<name>` if it
+    /// supports that, otherwise with the `Exec` line of the `.desktop` file whose ID is `name`.
+    /// Instances of the player, like `vlc.instance1234`, are accepted as well unless `name`
+    /// includes an instance itself.
+    ///
+    /// Returns [`LaunchError::NotLaunchable`] if there is no way to start the player, and
+    /// [`LaunchError::Timeout`] if it did not appear on the bus within the timeout. If D-Bus
+    /// activation fails for another reason, for example because the player did not finish starting
+    /// within the timeout, the error is returned as [`LaunchError::DBusError`].
+    ///
+    /// ```no_run
+    /// use mpris::PlayerFinder;
+    /// use std::time::Duration;
+    ///
+    /// let finder = PlayerFinder::new().unwrap();
+    /// let player = finder.launch("vlc", Duration::from_secs(10)).unwrap();
+    /// player.play().unwrap();
+    /// ```
+    pub fn launch(&self, name: &str, timeout: Duration) -> Result<Player, LaunchError> {
+        let name = name.trim_start_matches(MPRIS2_PREFIX);
+        // A timeout too large for an `Instant`, like `Duration::MAX`, means waiting forever.
+        let deadline = Instant::now().checked_add(timeout);
+        // Start watching for new players before looking for a running one, so none are missed.
+        let name_changes = self.connection.watch_names();
+
+        let running = self
+            .all_player_buses()?
+            .into_iter()
+            .find(|bus_name| launch::is_launched_name(bus_name, name));
+        let bus_name = match running {
+            Some(bus_name) => bus_name,
+            None => {
+                launch::start_player(&self.connection, name, timeout)?;
+                self.connection
+                    .wait_for_acquired_name(&name_changes, deadline, |bus_name| {
+                        let launched = launch::is_launched_name(&bus_name, name);
+                        Ok::<_, LaunchError>(Some(bus_name).filter(|_| launched))
+                    })?
+                    .ok_or_else(|| LaunchError::Timeout(name.to_string()))?
+            }
+        };
+
+        Ok(Player::for_pooled_connection(
+            self.connection.clone(),
+            bus_name,
+            self.player_timeout_ms,
+        )?)
+    }
+
     /// Returns all of the MPRIS DBus paths
     fn all_player_buses(&self) -> Result<Vec<String>, DBusError> {
         let list_names = Message::new_method_call(
@@ -185,6 +372,164 @@ impl PlayerFinder {
             self.player_timeout_ms,
         ))
     }
+
+    /// Returns a [`PlayerWatcher`] iterator, which blocks until a player appears or disappears.
+    ///
+    /// Unlike [`PlayerIter`] this keeps up with players that connect or quit later on. See the
+    /// [`PlayerWatcher`] documentation for how to combine it with [`find_all`](Self::find_all).
+    pub fn watch(&self) -> PlayerWatcher {
+        PlayerWatcher::new(self.connection.clone(), self.player_timeout_ms)
+    }
+
+    /// Returns an [`AllPlayerEvents`] iterator, which blocks until any player on the bus has an
+    /// [`Event`](crate::Event), or an [`DBusError`] if the players could not be listed.
+    ///
+    /// All players are watched on the connection of this finder, including the ones that start
+    /// later. See [`Player::events`] for watching a single player.
+    pub fn all_player_events(&self) -> Result<AllPlayerEvents, DBusError> {
+        // Start watching for new players before listing the current ones, so none are missed.
+        let events = AllPlayerEvents::new(self.connection.clone(), self.player_timeout_ms);
+        let buses = self.all_player_buses()?;
+        Ok(events.with_players(buses))
+    }
+
+    /// Returns an [`ActivePlayerTracker`] that orders the running players by when the user last
+    /// interacted with them.
+    ///
+    /// Commands sent to [`Player`]s found by this finder count as interactions.
+    pub fn track_active(&self) -> Result<ActivePlayerTracker, DBusError> {
+        // Start watching for new players before listing the current ones, so none are missed.
+        let tracker = ActivePlayerTracker::new(self.connection.clone(), self.player_timeout_ms);
+        let buses = self.all_player_buses()?;
+        Ok(tracker.with_buses(buses))
+    }
+
+    /// Returns the file descriptors of the D-Bus connection, so they can be watched by an
+    /// external event loop, like the ones of GTK, calloop or mio.
+    ///
+    /// Watch each [`Watch`] for reading and/or writing as it asks, and call
+    /// [`dispatch_ready`](Self::dispatch_ready) whenever one of them is ready. The connection
+    /// can start or stop watching file descriptors while dispatching, so get them again
+    /// afterwards if your event loop allows it. Watch them level-triggered: a single dispatch
+    /// does not have to empty the socket.
+    ///
+    /// This lets [`Player`]s found by this finder, and everything reading their events, share
+    /// the event loop of your application without any extra threads. See the "Poll events"
+    /// example.
+    pub fn watch_fds(&self) -> Vec<Watch> {
+        self.connection.watch_fds()
+    }
+
+    /// Processes the messages that are available on the D-Bus connection, without blocking.
+    ///
+    /// Events found that way are handed to the readers of the affected players, like
+    /// [`PlayerEvents`](crate::PlayerEvents), [`ProgressTracker`](crate::ProgressTracker) or
+    /// [`AllPlayerEvents`], where their non-blocking methods pick them up.
+    ///
+    /// Call this whenever one of the [`watch_fds`](Self::watch_fds) is ready, and also after
+    /// calling methods of players: messages that arrive while waiting for a reply are read right
+    /// away, so their file descriptor will not become ready for them again.
+    pub fn dispatch_ready(&self) {
+        self.connection.process_pending_messages();
+    }
+}
+
+/// Finds an "active" player. Follows the order mentioned in
+/// [`PlayerFinder::find_active`].
+pub(crate) fn find_active_player<P, I, S, M>(
+    players: I,
+    playback_status: S,
+    metadata: M,
+) -> Result<Option<P>, DBusError>
+where
+    I: Iterator<Item = Result<P, DBusError>>,
+    S: Fn(&P) -> Result<PlaybackStatus, DBusError>,
+    M: Fn(&P) -> Result<Metadata, DBusError>,
+{
+    let mut first_paused: Option<P> = None;
+    let mut first_with_track: Option<P> = None;
+    let mut first_found: Option<P> = None;
+
+    for player in players {
+        let player = player?;
+        let player_status = playback_status(&player)?;
+
+        if player_status == PlaybackStatus::Playing {
+            return Ok(Some(player));
+        }
+
+        if first_paused.is_none() && player_status == PlaybackStatus::Paused {
+            first_paused.replace(player);
+        } else if first_with_track.is_none() && !metadata(&player)?.is_empty() {
+            first_with_track.replace(player);
+        } else if first_found.is_none() {
+            first_found.replace(player);
+        }
+    }
+
+    Ok(first_paused.or(first_with_track).or(first_found))
+}
+
+/// Finds an "active" player like [`find_active_player`], but skips the players whose playback
+/// status can't be read instead of failing. Unreadable metadata counts as empty.
+fn find_active_readable_player(players: Vec<Player>) -> Option<Player> {
+    let players = players.into_iter().filter_map(|player| {
+        let status = player.get_playback_status().ok()?;
+        Some(Ok((player, status)))
+    });
+    find_active_player(
+        players,
+        |(_, status)| Ok(*status),
+        |(player, _)| Ok(player.get_metadata().unwrap_or_default()),
+    )
+    .ok()
+    .flatten()
+    .map(|(player, _)| player)
+}
+
+/// Groups the items by the application of their bus names, keeping the order they first appear
+/// in.
+fn group_by_application<T>(items: Vec<(BusNameInfo, T)>) -> Vec<(BusNameInfo, Vec<T>)> {
+    let mut groups: Vec<(BusNameInfo, Vec<T>)> = Vec::new();
+    for (info, item) in items {
+        match groups
+            .iter_mut()
+            .find(|(group, _)| group.is_same_application(&info))
+        {
+            Some((_, group)) => group.push(item),
+            None => groups.push((info, vec![item])),
+        }
+    }
+    groups
+}
+
+/// All running instances of one application, as found by [`PlayerFinder::find_grouped`].
+///
+/// Useful for showing something like "Firefox (3 tabs)" instead of one entry per instance.
+#[derive(Debug)]
+pub struct PlayerGroup {
+    application: String,
+    players: Vec<Player>,
+}
+
+impl PlayerGroup {
+    /// Returns the application part of the bus names, as described in [`BusNameInfo`].
+    ///
+    /// Use the [`identity`](Player::identity) of one of the players for a human-readable name.
+    pub fn application(&self) -> &str {
+        &self.application
+    }
+
+    /// Returns the players of the group, in the order they were found. There is always at least
+    /// one.
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Consumes the group and returns its players.
+    pub fn into_players(self) -> Vec<Player> {
+        self.players
+    }
 }
 
 /// An iterator that lazily iterates over all of the found [`Player`]s. Useful for efficiently searching for a specific player.
@@ -195,6 +540,7 @@ impl PlayerFinder {
 /// a new one might connect at a later time, this will result in an error or the player not being present respectively.
 /// If you want to make sure the data is "fresh" you'll either have to make a new PlayerIter whenever you want to get new data or
 /// use [`PlayerFinder::find_all`] which will immediately return a [`Vec`] with all the [`Player`]s that were connected at that point.
+/// To keep up with players as they come and go, use [`PlayerFinder::watch`].
 #[derive(Debug)]
 pub struct PlayerIter {
     buses: std::vec::IntoIter<String>,
@@ -233,3 +579,156 @@ impl Iterator for PlayerIter {
 impl ExactSizeIterator for PlayerIter {}
 
 impl FusedIterator for PlayerIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::serve;
+
+    #[test]
+    fn it_groups_by_application() {
+        let groups = group_by_application(vec![
+            (BusNameInfo::parse("firefox.instance_1_84"), 1),
+            (BusNameInfo::parse("spotify"), 2),
+            (BusNameInfo::parse("Firefox.instance_1_92"), 3),
+            (BusNameInfo::parse("firefox"), 4),
+        ]);
+        let groups: Vec<(&str, Vec<i32>)> = groups
+            .iter()
+            .map(|(info, items)| (info.application(), items.clone()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![("firefox", vec![1, 3, 4]), ("spotify", vec![2])]
+        );
+    }
+
+    #[test]
+    fn it_waits_for_players() {
+        let finder = PlayerFinder::new().unwrap();
+        let selector: PlayerSelector = "mpris_rs_test_wait_for".parse().unwrap();
+
+        let server = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            serve("mpris_rs_test_wait_for")
+        });
+        // Timeouts that don't fit an `Instant` wait without a deadline.
+        let player = finder.wait_for(&selector, Duration::MAX).unwrap();
+        let (bus_name, handle) = server.join().unwrap();
+        assert_eq!(player.bus_name(), bus_name);
+
+        // Already running players are returned right away.
+        let player = finder.wait_for(&selector, Duration::from_secs(0)).unwrap();
+        assert_eq!(player.bus_name(), bus_name);
+
+        let missing: PlayerSelector = "mpris_rs_test_wait_for_missing".parse().unwrap();
+        match finder.wait_for(&missing, Duration::from_millis(100)) {
+            Err(FindingError::NoPlayerFound) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_keeps_waiting_after_broken_players() {
+        let finder = PlayerFinder::new().unwrap();
+        let selector: PlayerSelector = "mpris_rs_test_wait_for_broken".parse().unwrap();
+
+        let server = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            // Takes a matching name without serving the MPRIS interfaces.
+            let broken = Connection::get_private(BusType::Session).unwrap();
+            broken
+                .register_name(
+                    "org.mpris.MediaPlayer2.mpris_rs_test_wait_for_broken.instance1",
+                    0,
+                )
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+            let served = serve("mpris_rs_test_wait_for_broken");
+            drop(broken);
+            served
+        });
+        let player = finder.wait_for(&selector, Duration::from_secs(5)).unwrap();
+        let (bus_name, handle) = server.join().unwrap();
+        assert_eq!(player.bus_name(), bus_name);
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_waits_past_broken_running_players() {
+        let finder = PlayerFinder::new().unwrap();
+        let selector: PlayerSelector = "mpris_rs_test_wait_for_running_broken".parse().unwrap();
+
+        // Takes a matching name without serving the MPRIS interfaces.
+        let broken = Connection::get_private(BusType::Session).unwrap();
+        broken
+            .register_name(
+                "org.mpris.MediaPlayer2.mpris_rs_test_wait_for_running_broken.instance1",
+                0,
+            )
+            .unwrap();
+
+        let server = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            serve("mpris_rs_test_wait_for_running_broken")
+        });
+        let player = finder.wait_for(&selector, Duration::from_secs(5)).unwrap();
+        let (bus_name, handle) = server.join().unwrap();
+        assert_eq!(player.bus_name(), bus_name);
+
+        drop(broken);
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_launches_running_players() {
+        let (bus_name, handle) = serve("mpris_rs_test_launch");
+        let finder = PlayerFinder::new().unwrap();
+
+        let player = finder
+            .launch("mpris_rs_test_launch", Duration::MAX)
+            .unwrap();
+        assert_eq!(player.bus_name(), bus_name);
+
+        match finder.launch("mpris_rs_test_missing", Duration::from_secs(1)) {
+            Err(LaunchError::NotLaunchable(name)) => assert_eq!(name, "mpris_rs_test_missing"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_finds_grouped_players() {
+        let (bus_name, handle) = serve("mpris_rs_test_grouped");
+        let finder = PlayerFinder::new().unwrap();
+
+        // Players that can't be read don't keep the others from being grouped.
+        let broken = Connection::get_private(BusType::Session).unwrap();
+        broken
+            .register_name("org.mpris.MediaPlayer2.mpris_rs_test_grouped_broken", 0)
+            .unwrap();
+
+        let groups = finder.find_grouped().unwrap();
+        let group = groups
+            .iter()
+            .find(|group| group.application() == "mpris_rs_test_grouped")
+            .unwrap();
+        assert_eq!(group.players().len(), 1);
+        assert_eq!(group.players()[0].bus_name(), bus_name);
+        assert!(groups
+            .iter()
+            .all(|group| group.application() != "mpris_rs_test_grouped_broken"));
+
+        drop(broken);
+        group.players()[0].quit().unwrap();
+        handle.join().unwrap();
+    }
+}
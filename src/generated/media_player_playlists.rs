@@ -1,6 +1,7 @@
 #![allow(unknown_lints)]
 #![allow(clippy::all)]
 #![allow(
+    dead_code,
     missing_debug_implementations,
     missing_copy_implementations,
     trivial_casts,
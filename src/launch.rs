@@ -0,0 +1,174 @@
+use std::io;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use dbus::Message;
+use thiserror::Error;
+
+use crate::bus_name::BusNameInfo;
+use crate::player::MPRIS2_PREFIX;
+use crate::pooled_connection::PooledConnection;
+use crate::{DBusError, DesktopFile};
+
+/// Reply of `StartServiceByName` when the service was not running yet, or already was.
+const START_REPLY_SUCCESS: u32 = 1;
+const START_REPLY_ALREADY_RUNNING: u32 = 2;
+
+/// The reasons that [`PlayerFinder::launch`](crate::PlayerFinder::launch) could fail.
+#[derive(Debug, Error)]
+pub enum LaunchError {
+    /// The player can not be started through D-Bus activation, and has no `.desktop` file with an
+    /// `Exec` line either. The name of the player is provided.
+    #[error("Don't know how to launch {0}")]
+    NotLaunchable(String),
+
+    /// The command from the player's `.desktop` file could not be run.
+    #[error("Could not run {command}: {source}")]
+    SpawnFailed {
+        /// The program that was run.
+        command: String,
+
+        /// The reason it could not be run.
+        source: io::Error,
+    },
+
+    /// The player was started, but did not appear on the bus in time. The name of the player is
+    /// provided.
+    #[error("{0} did not appear on the bus in time")]
+    Timeout(String),
+
+    /// Launching failed due to an underlying [`DBusError`].
+    #[error("{0}")]
+    DBusError(#[from] DBusError),
+}
+
+/// Returns [`true`] if the bus name belongs to the player with the given name, either exactly or
+/// as one of its instances.
+pub(crate) fn is_launched_name(bus_name: &str, name: &str) -> bool {
+    let trimmed = match bus_name.strip_prefix(MPRIS2_PREFIX) {
+        Some(trimmed) => trimmed,
+        None => return false,
+    };
+    let wanted = BusNameInfo::parse(name);
+    match wanted.instance() {
+        Some(_) => trimmed.eq_ignore_ascii_case(name),
+        None => BusNameInfo::parse(trimmed).is_same_application(&wanted),
+    }
+}
+
+/// Errors of `StartServiceByName` that mean that the player can not be started through D-Bus
+/// activation at all.
+const NOT_ACTIVATABLE_ERRORS: &[&str] = &[
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.Spawn.ServiceNotFound",
+];
+
+/// Starts the player with the given name, preferring D-Bus activation over the `Exec` line of its
+/// `.desktop` file.
+///
+/// The `Exec` line is only used if the player is not activatable. Other errors, like a player
+/// that is slow to start, are returned as they are, so the player is not started twice.
+pub(crate) fn start_player(
+    connection: &PooledConnection,
+    name: &str,
+    timeout: Duration,
+) -> Result<(), LaunchError> {
+    match start_service(connection, name, timeout) {
+        Ok(()) => Ok(()),
+        Err(error) if is_not_activatable(&error) => {
+            match DesktopFile::find(name).and_then(|file| file.command()) {
+                Some(command) => spawn(command),
+                None => Err(LaunchError::NotLaunchable(name.to_string())),
+            }
+        }
+        Err(error) => Err(DBusError::from_call(error, "StartServiceByName").into()),
+    }
+}
+
+fn is_not_activatable(error: &dbus::Error) -> bool {
+    error
+        .name()
+        .map_or(false, |name| NOT_ACTIVATABLE_ERRORS.contains(&name))
+}
+
+/// Asks the bus to start the service for the player's bus name.
+fn start_service(
+    connection: &PooledConnection,
+    name: &str,
+    timeout: Duration,
+) -> Result<(), dbus::Error> {
+    let start_service = Message::new_method_call(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+        "StartServiceByName",
+    )
+    .unwrap()
+    .append2(format!("{}{}", MPRIS2_PREFIX, name), 0u32);
+
+    let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+    let reply = connection
+        .underlying()
+        .send_with_reply_and_block(start_service, timeout_ms)?;
+    match reply.read1()? {
+        START_REPLY_SUCCESS | START_REPLY_ALREADY_RUNNING => Ok(()),
+        other => Err(dbus::Error::new_custom(
+            "org.freedesktop.DBus.Error.Failed",
+            &format!("Unexpected reply to StartServiceByName: {}", other),
+        )),
+    }
+}
+
+/// Runs the command in the background.
+fn spawn(command: Vec<String>) -> Result<(), LaunchError> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|source| LaunchError::SpawnFailed {
+            command: command[0].clone(),
+            source,
+        })?;
+
+    // Reap the process once it exits, so it does not linger as a zombie.
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_falls_back_for_services_that_are_not_activatable() {
+        let unknown =
+            dbus::Error::new_custom("org.freedesktop.DBus.Error.ServiceUnknown", "Unknown");
+        let no_reply = dbus::Error::new_custom("org.freedesktop.DBus.Error.NoReply", "Too slow");
+        assert!(is_not_activatable(&unknown));
+        assert!(!is_not_activatable(&no_reply));
+    }
+
+    #[test]
+    fn it_matches_launched_names() {
+        assert!(is_launched_name("org.mpris.MediaPlayer2.vlc", "vlc"));
+        assert!(is_launched_name("org.mpris.MediaPlayer2.VLC", "vlc"));
+        assert!(is_launched_name(
+            "org.mpris.MediaPlayer2.vlc.instance1234",
+            "vlc"
+        ));
+        assert!(is_launched_name(
+            "org.mpris.MediaPlayer2.vlc.instance1234",
+            "vlc.instance1234"
+        ));
+        assert!(!is_launched_name(
+            "org.mpris.MediaPlayer2.vlc.instance99",
+            "vlc.instance1234"
+        ));
+        assert!(!is_launched_name("org.mpris.MediaPlayer2.vlc", "mpv"));
+        assert!(!is_launched_name("org.mpris.MediaPlayer2.vlcx", "vlc"));
+        assert!(!is_launched_name("org.example.vlc", "vlc"));
+    }
+}
@@ -31,25 +31,62 @@ use thiserror::Error;
 
 mod extensions;
 
+#[cfg(feature = "tokio")]
+mod asynchronous;
+
 #[allow(unreachable_pub)]
 mod generated;
 
+mod active;
+mod all_events;
+mod bus_name;
+mod cancel;
+mod capabilities;
+mod desktop_file;
 mod event;
 mod find;
+mod launch;
 mod metadata;
+mod persistent;
 mod player;
+mod playlist;
 mod pooled_connection;
 mod progress;
+mod property_cache;
+mod selector;
+mod server;
+mod shared;
+mod state;
 mod track_list;
+mod uri;
+mod watch;
 
+pub use crate::active::ActivePlayerTracker;
+pub use crate::all_events::AllPlayerEvents;
+#[cfg(feature = "tokio")]
+pub use crate::asynchronous::{AsyncPlayer, AsyncPlayerEvents, AsyncPlayerFinder};
+pub use crate::bus_name::BusNameInfo;
+pub use crate::cancel::CancelHandle;
+pub use crate::capabilities::{Capabilities, CapabilitiesIter};
+pub use crate::desktop_file::DesktopFile;
 pub use crate::event::{Event, EventError, PlayerEvents};
-pub use crate::find::{FindingError, PlayerFinder, PlayerIter};
+pub use crate::find::{FindingError, PlayerFinder, PlayerGroup, PlayerIter};
+pub use crate::launch::LaunchError;
 pub use crate::metadata::Metadata;
 pub use crate::metadata::Value as MetadataValue;
 pub use crate::metadata::ValueKind as MetadataValueKind;
-pub use crate::player::Player;
+pub use crate::persistent::{PersistentPlayer, PersistentPlayerEvents};
+pub use crate::player::{Player, PlayerId};
+pub use crate::playlist::{InvalidPlaylistOrdering, Playlist, PlaylistOrdering};
+pub use crate::pooled_connection::OverflowPolicy;
 pub use crate::progress::{Progress, ProgressError, ProgressTick, ProgressTracker};
+pub use crate::selector::{InvalidPlayerSelector, PlayerSelector};
+pub use crate::server::{MprisServer, PlayerBackend};
+pub use crate::shared::{SharedPlayer, SharedPlayerEvents, SharedPlayerFinder, SharedPlayerIter};
+pub use crate::state::PlayerState;
 pub use crate::track_list::{TrackID, TrackList, TrackListError};
+pub use crate::uri::OpenUriError;
+pub use crate::watch::{PlayerChange, PlayerWatcher};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[allow(missing_docs)]
@@ -104,6 +141,16 @@ impl ::std::str::FromStr for PlaybackStatus {
     }
 }
 
+impl PlaybackStatus {
+    fn dbus_value(self) -> String {
+        String::from(match self {
+            PlaybackStatus::Playing => "Playing",
+            PlaybackStatus::Paused => "Paused",
+            PlaybackStatus::Stopped => "Stopped",
+        })
+    }
+}
+
 /// [`LoopStatus`] had an invalid string value.
 #[derive(Debug, Error)]
 #[error("LoopStatus must be one of None, Track, Playlist, but was {0}")]
@@ -134,12 +181,61 @@ impl LoopStatus {
 
 /// Something went wrong when communicating with the D-Bus. This could either be an underlying
 /// D-Bus library problem, or that the other side did not conform to the expected protocols.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum DBusError {
     /// An error occurred while talking to the D-Bus.
     #[error("D-Bus call failed: {0}")]
     TransportError(#[from] dbus::Error),
 
+    /// The [`Player`] did not reply in time.
+    #[error("{member} timed out: the player did not reply in time ({message})")]
+    Timeout {
+        /// The method or property that was called, like `Play` or `Volume`.
+        member: String,
+        /// The error message that came with the D-Bus error.
+        message: String,
+    },
+
+    /// The [`Player`] is no longer on the bus, most likely because it quit.
+    #[error("{member} failed: the player is no longer running ({message})")]
+    PlayerGone {
+        /// The method or property that was called, like `Play` or `Volume`.
+        member: String,
+        /// The error message that came with the D-Bus error.
+        message: String,
+    },
+
+    /// The [`Player`] does not implement the method or property, or rejected the arguments that
+    /// were passed to it.
+    #[error("{member} is not supported by the player: {message}")]
+    NotSupported {
+        /// The method or property that was called, like `Play` or `Volume`.
+        member: String,
+        /// The error message that came with the D-Bus error.
+        message: String,
+    },
+
+    /// The D-Bus or the [`Player`] denied access to the method or property.
+    #[error("Access to {member} was denied: {message}")]
+    AccessDenied {
+        /// The method or property that was called, like `Play` or `Volume`.
+        member: String,
+        /// The error message that came with the D-Bus error.
+        message: String,
+    },
+
+    /// The property can not be changed.
+    #[error("{member} is a read-only property: {message}")]
+    PropertyReadOnly {
+        /// The property that was changed, like `Volume`.
+        member: String,
+        /// The error message that came with the D-Bus error.
+        message: String,
+    },
+
     /// Failed to parse an enum from a string value received from the [`Player`]. This means that the
     /// [`Player`] replied with unexpected data.
     #[error("Failed to parse enum value: {0}")]
@@ -155,6 +251,39 @@ pub enum DBusError {
     Miscellaneous(String),
 }
 
+impl DBusError {
+    /// Converts the error that a call of the method or property `member` failed with, based on
+    /// the D-Bus error name. Errors without a dedicated variant become a
+    /// [`DBusError::TransportError`].
+    pub(crate) fn from_call(error: dbus::Error, member: &str) -> DBusError {
+        let member = member.to_string();
+        let message = error.message().unwrap_or_default().to_string();
+        match error.name() {
+            Some("org.freedesktop.DBus.Error.NoReply")
+            | Some("org.freedesktop.DBus.Error.Timeout")
+            | Some("org.freedesktop.DBus.Error.TimedOut") => DBusError::Timeout { member, message },
+            Some("org.freedesktop.DBus.Error.ServiceUnknown")
+            | Some("org.freedesktop.DBus.Error.NameHasNoOwner") => {
+                DBusError::PlayerGone { member, message }
+            }
+            Some("org.freedesktop.DBus.Error.UnknownMethod")
+            | Some("org.freedesktop.DBus.Error.UnknownProperty")
+            | Some("org.freedesktop.DBus.Error.UnknownInterface")
+            | Some("org.freedesktop.DBus.Error.InvalidArgs")
+            | Some("org.freedesktop.DBus.Error.NotSupported") => {
+                DBusError::NotSupported { member, message }
+            }
+            Some("org.freedesktop.DBus.Error.AccessDenied") => {
+                DBusError::AccessDenied { member, message }
+            }
+            Some("org.freedesktop.DBus.Error.PropertyReadOnly") => {
+                DBusError::PropertyReadOnly { member, message }
+            }
+            _ => DBusError::TransportError(error),
+        }
+    }
+}
+
 impl From<InvalidPlaybackStatus> for DBusError {
     fn from(error: InvalidPlaybackStatus) -> Self {
         DBusError::EnumParseError(error.to_string())
@@ -166,3 +295,44 @@ impl From<InvalidLoopStatus> for DBusError {
         DBusError::EnumParseError(error.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(name: &str) -> dbus::Error {
+        dbus::Error::new_custom(name, "Something went wrong")
+    }
+
+    #[test]
+    fn it_converts_errors_by_name() {
+        match DBusError::from_call(error("org.freedesktop.DBus.Error.NoReply"), "Play") {
+            DBusError::Timeout { member, message } => {
+                assert_eq!(member, "Play");
+                assert_eq!(message, "Something went wrong");
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        match DBusError::from_call(error("org.freedesktop.DBus.Error.ServiceUnknown"), "Next") {
+            DBusError::PlayerGone { member, .. } => assert_eq!(member, "Next"),
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        match DBusError::from_call(error("org.freedesktop.DBus.Error.InvalidArgs"), "Volume") {
+            DBusError::NotSupported { member, .. } => assert_eq!(member, "Volume"),
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        match DBusError::from_call(
+            error("org.freedesktop.DBus.Error.PropertyReadOnly"),
+            "Identity",
+        ) {
+            DBusError::PropertyReadOnly { member, .. } => assert_eq!(member, "Identity"),
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        match DBusError::from_call(error("org.example.Error.Failed"), "Play") {
+            DBusError::TransportError(error) => {
+                assert_eq!(error.name(), Some("org.example.Error.Failed"))
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod value;
 pub use self::value::{Value, ValueKind};
 use super::TrackID;
 
+use dbus::arg::{Append, Arg, ArgType, IterAppend, Variant};
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -12,7 +13,7 @@ use std::time::Duration;
 ///
 /// [metadata_map]: https://specifications.freedesktop.org/mpris-spec/latest/Track_List_Interface.html#Mapping:Metadata_Map
 /// [metadata_guidelines]: https://www.freedesktop.org/wiki/Specifications/mpris-spec/metadata/
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Metadata {
     values: HashMap<String, Value>,
 }
@@ -203,6 +204,37 @@ impl From<HashMap<String, Value>> for Metadata {
     }
 }
 
+impl Arg for Metadata {
+    const ARG_TYPE: ArgType = ArgType::Array;
+    fn signature() -> dbus::Signature<'static> {
+        dbus::Signature::from_slice("a{sv}").unwrap()
+    }
+}
+
+impl Append for Metadata {
+    /// Appends the metadata as a `Metadata_Map` (`a{sv}`).
+    ///
+    /// `mpris:trackid` is sent as an object path, as required by the MPRIS2 specification.
+    fn append_by_ref(&self, i: &mut IterAppend<'_>) {
+        i.append_dict(&"s".into(), &"v".into(), |i| {
+            for (key, value) in self.iter().filter(|(_, value)| !value.is_unsupported()) {
+                let track_id = match key {
+                    "mpris:trackid" => value.as_str().and_then(|id| dbus::Path::new(id).ok()),
+                    _ => None,
+                };
+
+                i.append_dict_entry(|i| {
+                    i.append(key);
+                    match track_id {
+                        Some(path) => i.append(Variant(path)),
+                        None => value.append_by_ref(i),
+                    }
+                });
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,9 +285,10 @@ mod tests {
     fn from_hashmap_artist_string() {
         use std::iter::FromIterator;
 
-        let metadata = Metadata::from(HashMap::from_iter(
-            vec![(String::from("xesam:artist"), Value::from("Agnes Obel"))].into_iter(),
-        ));
+        let metadata = Metadata::from(HashMap::from_iter(vec![(
+            String::from("xesam:artist"),
+            Value::from("Agnes Obel"),
+        )]));
 
         assert_eq!(metadata.artists(), Some(vec!["Agnes Obel"]));
     }
@@ -264,14 +297,29 @@ mod tests {
     fn from_hashmap_artists_list() {
         use std::iter::FromIterator;
 
-        let metadata = Metadata::from(HashMap::from_iter(
-            vec![(
-                String::from("xesam:artist"),
-                Value::from(vec![Value::from("Agnes Obel")]),
-            )]
-            .into_iter(),
-        ));
+        let metadata = Metadata::from(HashMap::from_iter(vec![(
+            String::from("xesam:artist"),
+            Value::from(vec![Value::from("Agnes Obel")]),
+        )]));
 
         assert_eq!(metadata.artists(), Some(vec!["Agnes Obel"]));
     }
+
+    #[test]
+    fn it_appends_track_ids_as_object_paths() {
+        use dbus::arg::{PropMap, RefArg};
+
+        let mut metadata = Metadata::new("/org/example/Track/1");
+        metadata
+            .values
+            .insert(String::from("xesam:title"), Value::from("Riverside"));
+
+        let message = dbus::Message::new_method_call("org.example", "/", "org.example", "Test")
+            .unwrap()
+            .append1(&metadata);
+        let map: PropMap = message.read1().unwrap();
+
+        assert_eq!(map["mpris:trackid"].0.arg_type(), ArgType::ObjectPath);
+        assert_eq!(map["xesam:title"].as_str(), Some("Riverside"));
+    }
 }
@@ -447,6 +447,75 @@ impl<'a> dbus::arg::Get<'a> for Value {
     }
 }
 
+impl Value {
+    /// The D-Bus signature of the value, when it is wrapped inside a variant.
+    fn dbus_signature(&self) -> &'static str {
+        match self {
+            Value::String(_) => "s",
+            Value::I16(_) => "n",
+            Value::I32(_) => "i",
+            Value::I64(_) => "x",
+            Value::U8(_) => "y",
+            Value::U16(_) => "q",
+            Value::U32(_) => "u",
+            Value::U64(_) => "t",
+            Value::F64(_) => "d",
+            Value::Bool(_) => "b",
+            Value::Array(values) if values.iter().all(Value::is_string) => "as",
+            Value::Array(_) | Value::Unsupported => "av",
+            Value::Map(_) => "a{sv}",
+        }
+    }
+
+    fn append_inner(&self, i: &mut dbus::arg::IterAppend<'_>) {
+        match self {
+            Value::String(val) => i.append(val.as_str()),
+            Value::I16(val) => i.append(*val),
+            Value::I32(val) => i.append(*val),
+            Value::I64(val) => i.append(*val),
+            Value::U8(val) => i.append(*val),
+            Value::U16(val) => i.append(*val),
+            Value::U32(val) => i.append(*val),
+            Value::U64(val) => i.append(*val),
+            Value::F64(val) => i.append(*val),
+            Value::Bool(val) => i.append(*val),
+            Value::Array(values) if values.iter().all(Value::is_string) => {
+                i.append_array(&"s".into(), |i| {
+                    for string in values.iter().flat_map(Value::as_str) {
+                        i.append(string);
+                    }
+                })
+            }
+            Value::Array(values) => i.append_array(&"v".into(), |i| {
+                for value in values.iter().filter(|value| !value.is_unsupported()) {
+                    dbus::arg::Append::append_by_ref(value, i);
+                }
+            }),
+            Value::Map(map) => i.append_dict(&"s".into(), &"v".into(), |i| {
+                for (key, value) in map.iter().filter(|(_, value)| !value.is_unsupported()) {
+                    i.append_dict_entry(|i| {
+                        i.append(key.as_str());
+                        dbus::arg::Append::append_by_ref(value, i);
+                    });
+                }
+            }),
+            // There is nothing to write for unsupported values, so use an empty array instead.
+            Value::Unsupported => i.append_array(&"v".into(), |_| {}),
+        }
+    }
+}
+
+impl dbus::arg::Append for Value {
+    /// Appends the value as a variant.
+    ///
+    /// Arrays where every element is a string are sent as a string array (`as`) and all other
+    /// arrays as arrays of variants (`av`). Unsupported values have no D-Bus representation, so
+    /// they are left out of arrays and maps.
+    fn append_by_ref(&self, i: &mut dbus::arg::IterAppend<'_>) {
+        i.append_variant(&self.dbus_signature().into(), |i| self.append_inner(i));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -467,7 +536,7 @@ mod tests {
             .register_object_path("/hello")
             .expect("Could not register object path");
         let send_message = Message::new_method_call(
-            &connection.unique_name(),
+            connection.unique_name(),
             "/hello",
             "com.example.hello",
             "Hello",
@@ -610,4 +679,40 @@ mod tests {
         assert!(hash.is_map());
         assert_eq!(hash.into_map(), Some(expected));
     }
+
+    #[test]
+    fn it_appends_values() {
+        let mut map = HashMap::new();
+        map.insert(String::from("count"), Value::U32(3));
+        map.insert(String::from("nested"), Value::Map(HashMap::new()));
+        map.insert(String::from("ignored"), Value::Unsupported);
+
+        let values = [
+            Value::String(String::from("Hello")),
+            Value::I64(-42),
+            Value::F64(0.5),
+            Value::Bool(true),
+            Value::Array(vec!["Hello".into(), "World".into()]),
+            Value::Array(vec![Value::U8(1), "Mixed".into(), Value::Unsupported]),
+        ];
+
+        let message = send_values_over_dbus(|message| {
+            values
+                .iter()
+                .fold(message, |message, value| message.append1(value.clone()))
+                .append1(Value::Map(map.clone()))
+        });
+
+        let mut iter = message.iter_init();
+        for value in values.iter().take(5) {
+            assert_eq!(&iter.read::<Value>().unwrap(), value);
+        }
+        assert_eq!(
+            iter.read::<Value>().unwrap(),
+            Value::Array(vec![Value::U8(1), "Mixed".into()])
+        );
+
+        map.remove("ignored");
+        assert_eq!(iter.read::<Value>().unwrap(), Value::Map(map));
+    }
 }
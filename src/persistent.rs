@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::event::EventState;
+use crate::pooled_connection::{EventSubscription, NameChange, NameChangeQueue, PooledConnection};
+use crate::{DBusError, Event, EventError, Player};
+
+/// A handle to a [`Player`] that survives restarts of the player.
+///
+/// Created by calling [`Player::into_persistent`].
+///
+/// A [`Player`] is bound to the unique bus name of one running instance of the player, so it stops
+/// working for good once that instance quits. A [`PersistentPlayer`] follows the player's
+/// well-known bus name instead, like `org.mpris.MediaPlayer2.spotify`, and binds to the new
+/// instance as soon as the player is started again.
+///
+/// While the player is not running, [`player`](Self::player) keeps returning the last bound
+/// [`Player`]. Commands and queries sent to it fail with [`DBusError::PlayerGone`].
+#[derive(Debug)]
+pub struct PersistentPlayer {
+    connection: Rc<PooledConnection>,
+    name_changes: Rc<NameChangeQueue>,
+    bus_name: String,
+    binding: RefCell<Binding>,
+}
+
+#[derive(Debug)]
+struct Binding {
+    /// The last bound player instance.
+    player: Rc<Player>,
+
+    /// If the bound instance is still running.
+    connected: bool,
+
+    /// Set when a new instance appeared that could not be bound yet.
+    rebind: bool,
+
+    /// Increased every time a new instance is bound.
+    generation: u64,
+}
+
+impl PersistentPlayer {
+    pub(crate) fn new(connection: Rc<PooledConnection>, player: Player) -> PersistentPlayer {
+        let name_changes = connection.watch_names();
+        // The player could have quit before its name was watched.
+        let connected = player.is_running();
+
+        PersistentPlayer {
+            connection,
+            name_changes,
+            bus_name: player.bus_name().to_string(),
+            binding: RefCell::new(Binding {
+                player: Rc::new(player),
+                connected,
+                rebind: false,
+                generation: 0,
+            }),
+        }
+    }
+
+    /// Returns the well-known bus name that is being followed.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// Returns the [`Player`] of the currently running instance, binding to a new instance first
+    /// if the player was restarted.
+    ///
+    /// If the player is not running, the last bound [`Player`] is returned. Returns an [`Err`] if
+    /// the player was restarted but the new instance could not be bound; this is retried on the
+    /// next call.
+    ///
+    /// The returned [`Player`] stays bound to the same instance; call this again to follow a
+    /// restart.
+    pub fn player(&self) -> Result<Rc<Player>, DBusError> {
+        self.refresh()?;
+        Ok(self.binding.borrow().player.clone())
+    }
+
+    /// Returns [`true`] if an instance of the player is running and bound.
+    pub fn is_connected(&self) -> bool {
+        self.refresh().is_ok() && self.binding.borrow().connected
+    }
+
+    /// Returns a [`PersistentPlayerEvents`] iterator, or an [`Err`] if the initial state of the
+    /// player could not be read.
+    pub fn events(&self) -> Result<PersistentPlayerEvents<'_>, DBusError> {
+        PersistentPlayerEvents::new(self)
+    }
+
+    /// Applies the changes of the player's bus name, and binds to a new instance if one appeared.
+    fn refresh(&self) -> Result<(), DBusError> {
+        self.connection.process_pending_messages();
+
+        let mut binding = self.binding.borrow_mut();
+        loop {
+            let change = self.name_changes.borrow_mut().pop_front();
+            match change {
+                Some(NameChange::Lost { bus_name, .. }) if bus_name == self.bus_name => {
+                    binding.connected = false;
+                    binding.rebind = false;
+                }
+                Some(NameChange::Acquired { bus_name }) if bus_name == self.bus_name => {
+                    binding.rebind = true;
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+
+        if binding.rebind {
+            let player = Player::for_pooled_connection(
+                self.connection.clone(),
+                self.bus_name.clone(),
+                binding.player.dbus_timeout_ms(),
+            )?;
+
+            binding.player = Rc::new(player);
+            binding.connected = true;
+            binding.rebind = false;
+            binding.generation += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Marks the instance of the given generation as no longer running.
+    fn disconnect(&self, generation: u64) {
+        let mut binding = self.binding.borrow_mut();
+        if binding.generation == generation {
+            binding.connected = false;
+        }
+    }
+}
+
+/// Iterator that blocks forever until a [`PersistentPlayer`] has an [`Event`].
+///
+/// Created by calling [`PersistentPlayer::events`].
+///
+/// This works like [`PlayerEvents`](crate::PlayerEvents), except that it does not end when the
+/// player quits. It emits [`Event::Disconnected`] instead, and [`Event::Reconnected`] once the
+/// player was started again. Read the state of the player again after reconnecting, as it most
+/// likely changed.
+#[derive(Debug)]
+pub struct PersistentPlayerEvents<'a> {
+    persistent: &'a PersistentPlayer,
+
+    /// State used to find events, as long as the player is connected.
+    state: Option<BoundEvents>,
+
+    /// The generation of the bound instance that `state` belongs to.
+    generation: u64,
+
+    /// Queued up events found after the last signal.
+    buffer: VecDeque<Event>,
+}
+
+/// The events of one bound instance.
+#[derive(Debug)]
+struct BoundEvents {
+    state: EventState,
+    events: Rc<EventSubscription>,
+}
+
+impl BoundEvents {
+    fn new(player: &Player) -> Result<BoundEvents, DBusError> {
+        let events = player.subscribe_events();
+        let state = EventState::new(player)?;
+        Ok(BoundEvents { state, events })
+    }
+}
+
+impl<'a> PersistentPlayerEvents<'a> {
+    fn new(persistent: &'a PersistentPlayer) -> Result<Self, DBusError> {
+        persistent.refresh()?;
+
+        let binding = persistent.binding.borrow();
+        let state = if binding.connected {
+            Some(BoundEvents::new(&binding.player)?)
+        } else {
+            None
+        };
+
+        Ok(PersistentPlayerEvents {
+            persistent,
+            state,
+            generation: binding.generation,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    /// Queues up [`Event::Disconnected`] and [`Event::Reconnected`] if the bound instance changed.
+    fn detect_reconnects(&mut self) -> Result<(), DBusError> {
+        self.persistent.refresh()?;
+
+        let binding = self.persistent.binding.borrow();
+        if self.state.is_some() && (!binding.connected || binding.generation != self.generation) {
+            self.state = None;
+            self.buffer.push_back(Event::Disconnected);
+        }
+
+        if self.state.is_none() && binding.connected {
+            self.state = Some(BoundEvents::new(&binding.player)?);
+            self.generation = binding.generation;
+            self.buffer.push_back(Event::Reconnected);
+        }
+
+        Ok(())
+    }
+
+    /// Queues up the events of the bound instance. Returns [`true`] if there were any.
+    fn read_events(&mut self) -> Result<bool, EventError> {
+        let (state, events) = match self.state {
+            Some(ref mut bound) => (&mut bound.state, bound.events.take_events()),
+            None => return Ok(false),
+        };
+        if events.is_empty() {
+            return Ok(false);
+        }
+
+        let player = self.persistent.binding.borrow().player.clone();
+        let shut_down = state.apply_events(&*player, events)?;
+        while let Some(event) = state.pop_event() {
+            match event {
+                // Reported as a disconnect instead.
+                Event::PlayerShutDown => {}
+                event => self.buffer.push_back(event),
+            }
+        }
+
+        if shut_down {
+            self.persistent.disconnect(self.generation);
+            self.state = None;
+            self.buffer.push_back(Event::Disconnected);
+        }
+        Ok(true)
+    }
+}
+
+impl Iterator for PersistentPlayerEvents<'_> {
+    type Item = Result<Event, EventError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.buffer.pop_front() {
+                return Some(Ok(event));
+            }
+
+            if let Err(error) = self.detect_reconnects() {
+                return Some(Err(error.into()));
+            }
+
+            match self.read_events() {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(error) => return Some(Err(error)),
+            }
+
+            if self.buffer.is_empty() {
+                self.persistent
+                    .connection
+                    .process_events_blocking_until_received();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve};
+
+    #[test]
+    fn it_follows_restarted_players() {
+        let (bus_name, handle) = serve("mpris_rs_test_persistent");
+        let persistent = connect(bus_name.clone()).into_persistent();
+        let mut events = persistent.events().unwrap();
+        let first_unique_name = persistent.player().unwrap().unique_name().to_string();
+
+        persistent.player().unwrap().quit().unwrap();
+        handle.join().unwrap();
+        match events.next().unwrap().unwrap() {
+            Event::Disconnected => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert!(!persistent.is_connected());
+        match persistent.player().unwrap().play() {
+            Err(DBusError::PlayerGone { member, .. }) => assert_eq!(member, "Play"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let (_, handle) = serve("mpris_rs_test_persistent");
+        match events.next().unwrap().unwrap() {
+            Event::Reconnected => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert!(persistent.is_connected());
+        assert_ne!(
+            persistent.player().unwrap().unique_name(),
+            first_unique_name
+        );
+
+        persistent.player().unwrap().play().unwrap();
+        match events.next().unwrap().unwrap() {
+            Event::Playing => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        persistent.player().unwrap().quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_rebinds_while_the_player_is_held() {
+        let (bus_name, handle) = serve("mpris_rs_test_persistent_held");
+        let persistent = connect(bus_name).into_persistent();
+        let player = persistent.player().unwrap();
+        assert!(persistent.is_connected());
+
+        let mut events = persistent.events().unwrap();
+        player.quit().unwrap();
+        handle.join().unwrap();
+        match events.next().unwrap().unwrap() {
+            Event::Disconnected => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        let (_, handle) = serve("mpris_rs_test_persistent_held");
+        match events.next().unwrap().unwrap() {
+            Event::Reconnected => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert!(persistent.is_connected());
+        assert_ne!(
+            persistent.player().unwrap().unique_name(),
+            player.unique_name()
+        );
+
+        persistent.player().unwrap().quit().unwrap();
+        handle.join().unwrap();
+    }
+}
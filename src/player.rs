@@ -3,28 +3,86 @@ use std::ops::Range;
 use std::rc::Rc;
 use std::time::Duration;
 
-use dbus::ffidisp::{ConnPath, Connection};
+use dbus::ffidisp::{ConnPath, Connection, Watch};
 use dbus::strings::{BusName, Path};
 
-use super::{DBusError, LoopStatus, MetadataValue, PlaybackStatus, TrackID, TrackList};
+use super::{
+    BusNameInfo, Capabilities, DBusError, DesktopFile, LoopStatus, MetadataValue, PlaybackStatus,
+    Playlist, PlaylistOrdering, TrackID, TrackList,
+};
 use crate::event::PlayerEvents;
 use crate::extensions::DurationExtensions;
 use crate::generated::OrgMprisMediaPlayer2;
 use crate::generated::OrgMprisMediaPlayer2Player;
-use crate::metadata::Metadata;
-use crate::pooled_connection::{MprisEvent, PooledConnection};
+use crate::generated::OrgMprisMediaPlayer2Playlists;
+use crate::metadata::{Metadata, Value};
+use crate::persistent::PersistentPlayer;
+use crate::pooled_connection::{EventSubscription, PooledConnection};
 use crate::progress::ProgressTracker;
+use crate::property_cache::PropertyCache;
+use crate::state::PlayerState;
+use crate::uri::{check_uri, OpenUriError};
 
 pub(crate) const MPRIS2_PREFIX: &str = "org.mpris.MediaPlayer2.";
 pub(crate) const MPRIS2_PATH: &str = "/org/mpris/MediaPlayer2";
 
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Properties that are not announced with `PropertiesChanged`, according to the MPRIS2
+/// specification.
+pub(crate) const UNANNOUNCED_PROPERTIES: &[&str] = &["Position", "CanControl"];
+
 /// When D-Bus connection is managed for you, use this timeout while communicating with a Player.
 pub(crate) const DEFAULT_TIMEOUT_MS: i32 = 500; // ms
 
+/// Identifies a running [`Player`] on the bus, for example in the items of
+/// [`AllPlayerEvents`](crate::AllPlayerEvents).
+///
+/// Two ids are only equal if they belong to the same running instance of a player. A player that
+/// is restarted gets a new unique name, and therefore a new id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerId {
+    bus_name: String,
+    unique_name: String,
+}
+
+impl PlayerId {
+    pub(crate) fn new(bus_name: String, unique_name: String) -> PlayerId {
+        PlayerId {
+            bus_name,
+            unique_name,
+        }
+    }
+
+    /// Returns the player's D-Bus bus name.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// Returns the player's bus name without the MPRIS2 prefix.
+    ///
+    /// See [`Player::bus_name_trimmed`].
+    pub fn bus_name_trimmed(&self) -> &str {
+        self.bus_name.trim_start_matches(MPRIS2_PREFIX)
+    }
+
+    /// Returns the player's unique D-Bus bus name (usually something like `:1.1337`).
+    pub fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+}
+
 /// A MPRIS-compatible player.
 ///
 /// You can query this player about the currently playing media, or control it.
 ///
+/// Properties that the player announces changes of with `PropertiesChanged` signals are cached, so
+/// asking for them again does not need another round trip over the D-Bus. `Position` and
+/// `CanControl` are never announced, so they are always read from the player. Use
+/// [`set_property_caching`](Self::set_property_caching) to always read every property from the
+/// player, for example for players that do not announce all of their changes.
+///
 /// **See:** [MPRIS2 MediaPlayer2.Player Specification][spec].
 ///
 /// [spec]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html
@@ -36,6 +94,10 @@ pub struct Player {
     identity: String,
     timeout_ms: i32,
     has_tracklist_interface: bool,
+    has_playlists_interface: bool,
+
+    /// The cached properties, unless caching is turned off.
+    cache: Option<Rc<PropertyCache>>,
 }
 
 impl Player {
@@ -60,7 +122,9 @@ impl Player {
         let identity = {
             let connection_path =
                 pooled_connection.with_path(bus.clone(), path.clone(), timeout_ms);
-            connection_path.identity()?
+            connection_path
+                .identity()
+                .map_err(|e| DBusError::from_call(e, "Identity"))?
         };
 
         let unique_name = pooled_connection
@@ -71,10 +135,18 @@ impl Player {
                 ))
             })?;
 
-        let has_tracklist_interface = {
+        let cache = if pooled_connection.property_caching() {
+            Some(pooled_connection.cache_properties(&unique_name))
+        } else {
+            None
+        };
+
+        let interfaces = {
             let connection_path = pooled_connection.with_path(bus, path, timeout_ms);
-            has_tracklist_interface(connection_path).unwrap_or(false)
+            introspect(connection_path).unwrap_or_default()
         };
+        let has_tracklist_interface = interfaces.contains("org.mpris.MediaPlayer2.TrackList");
+        let has_playlists_interface = interfaces.contains("org.mpris.MediaPlayer2.Playlists");
 
         Ok(Player {
             connection: pooled_connection,
@@ -83,6 +155,8 @@ impl Player {
             identity,
             timeout_ms,
             has_tracklist_interface,
+            has_playlists_interface,
+            cache,
         })
     }
 
@@ -101,6 +175,25 @@ impl Player {
         self.timeout_ms = timeout_ms;
     }
 
+    /// Returns [`true`] if properties that the player announces changes of are cached.
+    ///
+    /// You can change this using [`set_property_caching`](Self::set_property_caching).
+    pub fn property_caching(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// Turns caching of properties on or off. It is on by default.
+    ///
+    /// Turn it off for players that change properties without announcing it with a
+    /// `PropertiesChanged` signal, so that every query reads the current value from the player.
+    pub fn set_property_caching(&mut self, enabled: bool) {
+        if !enabled {
+            self.cache = None;
+        } else if self.cache.is_none() {
+            self.cache = Some(self.connection.cache_properties(&self.unique_name));
+        }
+    }
+
     /// Returns the player's D-Bus bus name.
     pub fn bus_name(&self) -> &str {
         &self.bus_name
@@ -116,7 +209,8 @@ impl Player {
     /// and implement it in its own way. This method was trying to guess the instance part and was
     /// not always successful. See [this issue][issue] for the relevant discussion.
     /// In the case when [`identity`][Self::identity] is not able to differentiate the players so
-    /// you need to filter out players by their bus names you should filter the results of [`PlayerFinder`][crate::find::PlayerFinder].
+    /// you need to filter out players by their bus names you should filter the results of [`PlayerFinder`][crate::find::PlayerFinder],
+    /// or use [`get_bus_name_info`](Self::get_bus_name_info) which documents how it guesses.
     /// For example:
     /// ```no_run
     /// use mpris::PlayerFinder;
@@ -160,6 +254,11 @@ impl Player {
         &self.unique_name
     }
 
+    /// Returns a [`PlayerId`] that identifies this player on the bus.
+    pub fn id(&self) -> PlayerId {
+        PlayerId::new(self.bus_name.clone(), self.unique_name.clone())
+    }
+
     /// Returns the player's MPRIS [`Identity`][identity].
     ///
     /// This is usually the application's name, like `Spotify`.
@@ -174,13 +273,68 @@ impl Player {
         self.has_tracklist_interface
     }
 
+    /// Checks if the Player implements the `org.mpris.MediaPlayer2.Playlists` interface.
+    pub fn supports_playlists(&self) -> bool {
+        self.has_playlists_interface
+    }
+
+    /// Reads all properties of the `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player`
+    /// interfaces at once, with only two round trips over the D-Bus.
+    ///
+    /// Prefer this over calling many separate getters when you need more than a few properties,
+    /// for example when rendering a user interface. The values of the returned [`PlayerState`]
+    /// are cached as well.
+    pub fn snapshot(&self) -> Result<PlayerState, DBusError> {
+        let root = self.get_all_properties(ROOT_INTERFACE)?;
+        let player = self.get_all_properties(PLAYER_INTERFACE)?;
+        PlayerState::from_properties(root, player)
+    }
+
+    /// Queries the player for everything that it can do, or supports, with only two round trips
+    /// over the D-Bus.
+    ///
+    /// This combines [`can_quit`](Self::can_quit), [`can_go_next`](Self::can_go_next),
+    /// [`has_volume`](Self::has_volume) and the other capability checks into a single
+    /// [`Capabilities`] set. Use [`Event::CapabilitiesChanged`](crate::Event::CapabilitiesChanged)
+    /// to find out when it changes.
+    ///
+    /// With [property caching](Self::property_caching) the capabilities are answered from the
+    /// cache once both interfaces have been read.
+    pub fn get_capabilities(&self) -> Result<Capabilities, DBusError> {
+        let root = self.get_cached_properties(ROOT_INTERFACE)?;
+        Ok(Capabilities::from_root_properties(&root) | self.get_player_capabilities()?)
+    }
+
     /// Returns the player's `DesktopEntry` property, if supported.
     ///
     /// See: [MPRIS2 specification about `DesktopEntry`][desktop_entry].
     ///
     /// [desktop_entry]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:DesktopEntry
     pub fn get_desktop_entry(&self) -> Result<Option<String>, DBusError> {
-        handle_optional_property(self.connection_path().desktop_entry())
+        self.get_optional_cached(ROOT_INTERFACE, "DesktopEntry", Value::into_string)
+    }
+
+    /// Looks up the `.desktop` file named by the player's [desktop entry](Self::get_desktop_entry),
+    /// to read its localized name and icon.
+    ///
+    /// Returns [`None`] if the player has no desktop entry, or no matching file was found. See
+    /// [`DesktopFile::find`] for where it is searched.
+    pub fn get_desktop_file(&self) -> Result<Option<DesktopFile>, DBusError> {
+        Ok(self
+            .get_desktop_entry()?
+            .and_then(|desktop_entry| DesktopFile::find(&desktop_entry)))
+    }
+
+    /// Splits the player's bus name into the application and instance parts, using the
+    /// [desktop entry](Self::get_desktop_entry) as a tiebreaker.
+    ///
+    /// See [`BusNameInfo`] for how the parts are told apart.
+    pub fn get_bus_name_info(&self) -> Result<BusNameInfo, DBusError> {
+        let desktop_entry = self.get_desktop_entry()?;
+        Ok(BusNameInfo::parse_with_desktop_entry(
+            &self.bus_name,
+            desktop_entry.as_deref(),
+        ))
     }
 
     /// Returns the player's `SupportedMimeTypes` property.
@@ -189,9 +343,7 @@ impl Player {
     ///
     /// [mime_types]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:SupportedMimeTypes
     pub fn get_supported_mime_types(&self) -> Result<Vec<String>, DBusError> {
-        self.connection_path()
-            .supported_mime_types()
-            .map_err(|e| e.into())
+        self.get_cached(ROOT_INTERFACE, "SupportedMimeTypes", into_string_array)
     }
 
     /// Returns the player's `SupportedUriSchemes` property.
@@ -200,9 +352,7 @@ impl Player {
     ///
     /// [schemes]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:SupportedUriSchemes
     pub fn get_supported_uri_schemes(&self) -> Result<Vec<String>, DBusError> {
-        self.connection_path()
-            .supported_uri_schemes()
-            .map_err(|e| e.into())
+        self.get_cached(ROOT_INTERFACE, "SupportedUriSchemes", into_string_array)
     }
 
     /// Returns the player's `HasTrackList` property.
@@ -211,9 +361,7 @@ impl Player {
     ///
     /// [track_list]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:HasTrackList
     pub fn get_has_track_list(&self) -> Result<bool, DBusError> {
-        self.connection_path()
-            .has_track_list()
-            .map_err(|e| e.into())
+        self.get_cached(ROOT_INTERFACE, "HasTrackList", Value::into_bool)
     }
 
     /// Returns the player's MPRIS `position` as a [`Duration`] since the start of the media.
@@ -240,7 +388,7 @@ impl Player {
         self.connection_path()
             .position()
             .map(|p| p as u64)
-            .map_err(|e| e.into())
+            .map_err(|e| DBusError::from_call(e, "Position"))
     }
 
     /// Sets the position of the current track to the given position (as a [`Duration`]).
@@ -272,9 +420,7 @@ impl Player {
         position: &Duration,
     ) -> Result<bool, DBusError> {
         if self.can_control()? && self.has_position()? {
-            self.set_position(track_id, position)
-                .map(|_| true)
-                .map_err(DBusError::from)
+            self.set_position(track_id, position).map(|_| true)
         } else {
             Ok(false)
         }
@@ -296,16 +442,16 @@ impl Player {
         track_id: TrackID,
         position_in_us: u64,
     ) -> Result<(), DBusError> {
-        self.connection_path()
+        self.command_path()
             .set_position(track_id.as_path(), position_in_us as i64)
-            .map_err(|e| e.into())
+            .map_err(|e| DBusError::from_call(e, "SetPosition"))
     }
 
     /// Returns the player's MPRIS (playback) `rate` as a factor.
     ///
     /// 1.0 would mean normal rate, while 2.0 would mean twice the playback speed.
     pub fn get_playback_rate(&self) -> Result<f64, DBusError> {
-        self.connection_path().rate().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "Rate", Value::into_f64)
     }
 
     /// Gets the "Rate" setting, if the player indicates that it supports it.
@@ -334,7 +480,9 @@ impl Player {
     ///
     /// [rate]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Rate
     pub fn set_playback_rate(&self, rate: f64) -> Result<(), DBusError> {
-        self.connection_path().set_rate(rate).map_err(|e| e.into())
+        self.command_path()
+            .set_rate(rate)
+            .map_err(|e| DBusError::from_call(e, "Rate"))
     }
 
     /// Set the playback rate of the player, if the player indicates that supports it and that it
@@ -347,9 +495,7 @@ impl Player {
     /// [rate]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Rate
     pub fn checked_set_playback_rate(&self, rate: f64) -> Result<bool, DBusError> {
         if self.can_control()? && self.has_playback_rate()? {
-            self.set_playback_rate(rate)
-                .map(|_| true)
-                .map_err(DBusError::from)
+            self.set_playback_rate(rate).map(|_| true)
         } else {
             Ok(false)
         }
@@ -361,7 +507,7 @@ impl Player {
     ///
     /// [min_rate]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:MinimumRate
     pub fn get_minimum_playback_rate(&self) -> Result<f64, DBusError> {
-        self.connection_path().minimum_rate().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "MinimumRate", Value::into_f64)
     }
 
     /// Gets the maximum allowed value for playback rate.
@@ -370,7 +516,7 @@ impl Player {
     ///
     /// [max_rate]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:MaximumRate
     pub fn get_maximum_playback_rate(&self) -> Result<f64, DBusError> {
-        self.connection_path().maximum_rate().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "MaximumRate", Value::into_f64)
     }
 
     /// Gets the minimum-maximum allowed value range for playback rate.
@@ -386,17 +532,8 @@ impl Player {
     ///
     /// See [`Metadata`] for more information about what is included here.
     pub fn get_metadata(&self) -> Result<Metadata, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        let connection_path = self.connection_path();
-
-        Properties::get::<HashMap<String, MetadataValue>>(
-            &connection_path,
-            "org.mpris.MediaPlayer2.Player",
-            "Metadata",
-        )
-        .map(Metadata::from)
-        .map_err(DBusError::from)
+        self.get_cached(PLAYER_INTERFACE, "Metadata", Value::into_map)
+            .map(Metadata::from)
     }
 
     /// Query the player for the current tracklist.
@@ -416,7 +553,7 @@ impl Player {
             "Tracks",
         )
         .map(TrackList::from)
-        .map_err(DBusError::from)
+        .map_err(|e| DBusError::from_call(e, "Tracks"))
     }
 
     /// Query the player for the current tracklist.
@@ -452,7 +589,7 @@ impl Player {
             "org.mpris.MediaPlayer2.TrackList",
             "CanEditTracks",
         )
-        .map_err(DBusError::from)
+        .map_err(|e| DBusError::from_call(e, "CanEditTracks"))
     }
 
     /// Query the player to see if it allows changes to its TrackList.
@@ -484,15 +621,19 @@ impl Player {
         use dbus::arg::IterAppend;
         let connection_path = self.connection_path();
 
-        let mut method = connection_path.method_call_with_args(
-            &"org.mpris.MediaPlayer2.TrackList".into(),
-            &"GetTracksMetadata".into(),
-            |msg| {
-                let mut i = IterAppend::new(msg);
-                i.append(track_ids.iter().map(|id| id.as_path()).collect::<Vec<_>>());
-            },
-        )?;
-        method.as_result()?;
+        let mut method = connection_path
+            .method_call_with_args(
+                &"org.mpris.MediaPlayer2.TrackList".into(),
+                &"GetTracksMetadata".into(),
+                |msg| {
+                    let mut i = IterAppend::new(msg);
+                    i.append(track_ids.iter().map(|id| id.as_path()).collect::<Vec<_>>());
+                },
+            )
+            .map_err(|e| DBusError::from_call(e, "GetTracksMetadata"))?;
+        method
+            .as_result()
+            .map_err(|e| DBusError::from_call(e, "GetTracksMetadata"))?;
         let mut i = method.iter_init();
         let metadata: Vec<::std::collections::HashMap<String, MetadataValue>> = i.read()?;
 
@@ -516,7 +657,7 @@ impl Player {
     ///
     /// [get_meta]: https://specifications.freedesktop.org/mpris-spec/latest/Track_List_Interface.html#Method:GetTracksMetadata
     pub fn get_track_metadata(&self, track_id: &TrackID) -> Result<Metadata, DBusError> {
-        self.get_tracks_metadata(&[track_id.clone()])
+        self.get_tracks_metadata(std::slice::from_ref(track_id))
             .and_then(|mut result| {
                 result.pop().map(Ok).unwrap_or_else(|| {
                     Err(DBusError::Miscellaneous(format!(
@@ -527,6 +668,95 @@ impl Player {
             })
     }
 
+    /// Query the player for a range of its [`Playlist`]s.
+    ///
+    /// At most `max_count` playlists, starting at `index`, will be returned in the given
+    /// `ordering`. Set `reverse` to reverse the ordering. Players are only required to support
+    /// [`PlaylistOrdering::Alphabetical`] and [`PlaylistOrdering::UserDefined`]; see
+    /// [`get_playlist_orderings`](Self::get_playlist_orderings) for the supported orderings.
+    ///
+    /// Requires the player to implement the `Playlists` interface.
+    ///
+    /// See: [MPRIS2 specification about `GetPlaylists`][get_playlists].
+    ///
+    /// [get_playlists]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Method:GetPlaylists
+    pub fn get_playlists(
+        &self,
+        index: u32,
+        max_count: u32,
+        ordering: PlaylistOrdering,
+        reverse: bool,
+    ) -> Result<Vec<Playlist>, DBusError> {
+        self.connection_path()
+            .get_playlists(index, max_count, ordering.dbus_value(), reverse)
+            .map(|playlists| playlists.into_iter().map(Playlist::from).collect())
+            .map_err(|e| DBusError::from_call(e, "GetPlaylists"))
+    }
+
+    /// Starts playing the given [`Playlist`].
+    ///
+    /// Requires the player to implement the `Playlists` interface.
+    ///
+    /// See: [MPRIS2 specification about `ActivatePlaylist`][activate].
+    ///
+    /// [activate]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Method:ActivatePlaylist
+    pub fn activate_playlist(&self, playlist: &Playlist) -> Result<(), DBusError> {
+        self.command_path()
+            .activate_playlist(playlist.as_path())
+            .map_err(|e| DBusError::from_call(e, "ActivatePlaylist"))
+    }
+
+    /// Query the player for the number of playlists available.
+    ///
+    /// Requires the player to implement the `Playlists` interface.
+    ///
+    /// See: [MPRIS2 specification about `PlaylistCount`][count].
+    ///
+    /// [count]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Property:PlaylistCount
+    pub fn get_playlist_count(&self) -> Result<u32, DBusError> {
+        self.connection_path()
+            .playlist_count()
+            .map_err(|e| DBusError::from_call(e, "PlaylistCount"))
+    }
+
+    /// Query the player for the [`PlaylistOrdering`]s it supports in
+    /// [`get_playlists`](Self::get_playlists).
+    ///
+    /// Requires the player to implement the `Playlists` interface.
+    ///
+    /// See: [MPRIS2 specification about `Orderings`][orderings].
+    ///
+    /// [orderings]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Property:Orderings
+    pub fn get_playlist_orderings(&self) -> Result<Vec<PlaylistOrdering>, DBusError> {
+        self.connection_path()
+            .orderings()
+            .map_err(|e| DBusError::from_call(e, "Orderings"))?
+            .iter()
+            .map(|ordering| ordering.parse().map_err(DBusError::from))
+            .collect()
+    }
+
+    /// Query the player for the currently active [`Playlist`].
+    ///
+    /// Returns [`None`] if no playlist is active.
+    ///
+    /// Requires the player to implement the `Playlists` interface.
+    ///
+    /// See: [MPRIS2 specification about `ActivePlaylist`][active].
+    ///
+    /// [active]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Property:ActivePlaylist
+    pub fn get_active_playlist(&self) -> Result<Option<Playlist>, DBusError> {
+        let (valid, playlist) = self
+            .connection_path()
+            .active_playlist()
+            .map_err(|e| DBusError::from_call(e, "ActivePlaylist"))?;
+        if valid {
+            Ok(Some(Playlist::from(playlist)))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Returns a new [`ProgressTracker`] for the player.
     ///
     /// Use this if you want to monitor a player in order to show close-to-realtime information
@@ -554,10 +784,17 @@ impl Player {
     /// remain frozen until the next event is emitted and the iterator returns.
     ///
     /// See: [`track_progress`](Self::track_progress) for an alternative approach.
-    pub fn events(&self) -> Result<PlayerEvents, DBusError> {
+    pub fn events(&self) -> Result<PlayerEvents<'_>, DBusError> {
         PlayerEvents::new(self)
     }
 
+    /// Turns the [`Player`] into a [`PersistentPlayer`], which keeps working after the player is
+    /// restarted. Use this for long-running programs that should not give up on a player when it
+    /// crashes or is restarted by the user.
+    pub fn into_persistent(self) -> PersistentPlayer {
+        PersistentPlayer::new(self.connection.clone(), self)
+    }
+
     /// Returns true if the bus of this player is still occupied in the connection, or put in
     /// another way: If there's a process still listening on messages on this bus.
     ///
@@ -569,6 +806,23 @@ impl Player {
             .unwrap_or(false)
     }
 
+    /// Returns the file descriptors of the player's D-Bus connection, so they can be watched by an
+    /// external event loop.
+    ///
+    /// See [`PlayerFinder::watch_fds`](crate::PlayerFinder::watch_fds), which works the same. All
+    /// players found by the same [`PlayerFinder`](crate::PlayerFinder) share the connection.
+    pub fn watch_fds(&self) -> Vec<Watch> {
+        self.connection.watch_fds()
+    }
+
+    /// Processes the messages that are available on the player's D-Bus connection, without
+    /// blocking.
+    ///
+    /// See [`PlayerFinder::dispatch_ready`](crate::PlayerFinder::dispatch_ready).
+    pub fn dispatch_ready(&self) {
+        self.connection.process_pending_messages();
+    }
+
     pub(crate) fn connection(&self) -> &PooledConnection {
         &self.connection
     }
@@ -579,7 +833,9 @@ impl Player {
     ///
     /// [play_pause]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:PlayPause
     pub fn play_pause(&self) -> Result<(), DBusError> {
-        self.connection_path().play_pause().map_err(|e| e.into())
+        self.command_path()
+            .play_pause()
+            .map_err(|e| DBusError::from_call(e, "PlayPause"))
     }
 
     /// Send a `Play` signal to the player.
@@ -588,7 +844,9 @@ impl Player {
     ///
     /// [play]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Play
     pub fn play(&self) -> Result<(), DBusError> {
-        self.connection_path().play().map_err(|e| e.into())
+        self.command_path()
+            .play()
+            .map_err(|e| DBusError::from_call(e, "Play"))
     }
 
     /// Send a `Pause` signal to the player.
@@ -597,7 +855,9 @@ impl Player {
     ///
     /// [pause]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Pause
     pub fn pause(&self) -> Result<(), DBusError> {
-        self.connection_path().pause().map_err(|e| e.into())
+        self.command_path()
+            .pause()
+            .map_err(|e| DBusError::from_call(e, "Pause"))
     }
 
     /// Send a `Stop` signal to the player.
@@ -606,7 +866,9 @@ impl Player {
     ///
     /// [stop]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Stop
     pub fn stop(&self) -> Result<(), DBusError> {
-        self.connection_path().stop().map_err(|e| e.into())
+        self.command_path()
+            .stop()
+            .map_err(|e| DBusError::from_call(e, "Stop"))
     }
 
     /// Send a `Next` signal to the player.
@@ -615,7 +877,9 @@ impl Player {
     ///
     /// [next]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Next
     pub fn next(&self) -> Result<(), DBusError> {
-        self.connection_path().next().map_err(|e| e.into())
+        self.command_path()
+            .next()
+            .map_err(|e| DBusError::from_call(e, "Next"))
     }
 
     /// Send a `Previous` signal to the player.
@@ -624,7 +888,9 @@ impl Player {
     ///
     /// [prev]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Previous
     pub fn previous(&self) -> Result<(), DBusError> {
-        self.connection_path().previous().map_err(|e| e.into())
+        self.command_path()
+            .previous()
+            .map_err(|e| DBusError::from_call(e, "Previous"))
     }
 
     /// Send a `Seek` signal to the player.
@@ -633,9 +899,9 @@ impl Player {
     ///
     /// [seek]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:Seek
     pub fn seek(&self, offset_in_microseconds: i64) -> Result<(), DBusError> {
-        self.connection_path()
+        self.command_path()
             .seek(offset_in_microseconds)
-            .map_err(|e| e.into())
+            .map_err(|e| DBusError::from_call(e, "Seek"))
     }
 
     /// Tell the player to seek forwards.
@@ -658,7 +924,9 @@ impl Player {
     ///
     /// [raise]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Method:Raise
     pub fn raise(&self) -> Result<(), DBusError> {
-        self.connection_path().raise().map_err(|e| e.into())
+        self.command_path()
+            .raise()
+            .map_err(|e| DBusError::from_call(e, "Raise"))
     }
 
     /// Send a `Raise` signal to the player, if it supports it.
@@ -689,7 +957,9 @@ impl Player {
     ///
     /// [quit]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Method:Quit
     pub fn quit(&self) -> Result<(), DBusError> {
-        self.connection_path().quit().map_err(|e| e.into())
+        self.command_path()
+            .quit()
+            .map_err(|e| DBusError::from_call(e, "Quit"))
     }
 
     /// Send a `Quit` signal to the player, if it supports it.
@@ -722,9 +992,9 @@ impl Player {
     pub fn go_to(&self, track_id: &TrackID) -> Result<(), DBusError> {
         use crate::generated::OrgMprisMediaPlayer2TrackList;
 
-        self.connection_path()
+        self.command_path()
             .go_to(track_id.into())
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "GoTo"))
     }
 
     /// Add a URI to the TrackList and optionally set it as current.
@@ -744,9 +1014,9 @@ impl Player {
     ) -> Result<(), DBusError> {
         use crate::generated::OrgMprisMediaPlayer2TrackList;
 
-        self.connection_path()
+        self.command_path()
             .add_track(uri, after.into(), set_as_current)
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "AddTrack"))
     }
 
     /// Add a URI to the start of the TrackList and optionally set it as current.
@@ -759,9 +1029,9 @@ impl Player {
     pub fn add_track_at_start(&self, uri: &str, set_as_current: bool) -> Result<(), DBusError> {
         use crate::generated::OrgMprisMediaPlayer2TrackList;
 
-        self.connection_path()
+        self.command_path()
             .add_track(uri, crate::track_list::NO_TRACK.into(), set_as_current)
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "AddTrack"))
     }
 
     /// Remove an item from the TrackList.
@@ -774,9 +1044,47 @@ impl Player {
     pub fn remove_track(&self, track_id: &TrackID) -> Result<(), DBusError> {
         use crate::generated::OrgMprisMediaPlayer2TrackList;
 
-        self.connection_path()
+        self.command_path()
             .remove_track(track_id.into())
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "RemoveTrack"))
+    }
+
+    /// Tell the player to open the given URI and start playing it.
+    ///
+    /// The URI scheme should be one of the player's
+    /// [`get_supported_uri_schemes`](Self::get_supported_uri_schemes), and its MIME type one of
+    /// [`get_supported_mime_types`](Self::get_supported_mime_types). Players might silently ignore
+    /// URIs they do not support; see [`checked_open_uri`](Self::checked_open_uri) for a version that
+    /// checks this first.
+    ///
+    /// See: [MPRIS2 specification about `OpenUri`][open_uri].
+    ///
+    /// [open_uri]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:OpenUri
+    pub fn open_uri(&self, uri: &str) -> Result<(), DBusError> {
+        self.command_path()
+            .open_uri(uri)
+            .map_err(|e| DBusError::from_call(e, "OpenUri"))
+    }
+
+    /// Tell the player to open the given URI, if the player indicates that it supports it.
+    ///
+    /// The URI's scheme is checked against [`get_supported_uri_schemes`](Self::get_supported_uri_schemes).
+    /// If a MIME type can be guessed from the URI's file extension, it is checked against
+    /// [`get_supported_mime_types`](Self::get_supported_mime_types) too. Players that don't list
+    /// any MIME types, and URIs without a known file extension (like streams), skip that check.
+    ///
+    /// Returns an [`OpenUriError`] explaining why the URI was rejected, if it was.
+    ///
+    /// See: [MPRIS2 specification about `OpenUri`][open_uri].
+    ///
+    /// [open_uri]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Method:OpenUri
+    pub fn checked_open_uri(&self, uri: &str) -> Result<(), OpenUriError> {
+        check_uri(
+            uri,
+            &self.get_supported_uri_schemes()?,
+            &self.get_supported_mime_types()?,
+        )?;
+        self.open_uri(uri).map_err(OpenUriError::from)
     }
 
     /// Sends a `PlayPause` signal to the player, if the player indicates that it can pause.
@@ -922,7 +1230,7 @@ impl Player {
     ///
     /// [can_raise]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:CanRaise
     pub fn can_raise(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_raise().map_err(|e| e.into())
+        self.get_cached(ROOT_INTERFACE, "CanRaise", Value::into_bool)
     }
 
     /// Queries the player to see if it can be asked to quit.
@@ -931,7 +1239,7 @@ impl Player {
     ///
     /// [can_quit]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:CanQuit
     pub fn can_quit(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_quit().map_err(|e| e.into())
+        self.get_cached(ROOT_INTERFACE, "CanQuit", Value::into_bool)
     }
 
     /// Queries the player to see if it can be asked to entrer fullscreen.
@@ -945,7 +1253,7 @@ impl Player {
     ///
     /// [can_full]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:CanSetFullscreen
     pub fn can_set_fullscreen(&self) -> Result<bool, DBusError> {
-        handle_optional_property(self.connection_path().can_set_fullscreen())
+        self.get_optional_cached(ROOT_INTERFACE, "CanSetFullscreen", Value::into_bool)
             .map(|o| o.unwrap_or(false))
     }
 
@@ -955,7 +1263,9 @@ impl Player {
     ///
     /// [can_control]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanControl
     pub fn can_control(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_control().map_err(|e| e.into())
+        self.connection_path()
+            .can_control()
+            .map_err(|e| DBusError::from_call(e, "CanControl"))
     }
 
     /// Queries the player to see if it can go to next or not.
@@ -964,7 +1274,7 @@ impl Player {
     ///
     /// [can_next]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanGoNext
     pub fn can_go_next(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_go_next().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "CanGoNext", Value::into_bool)
     }
 
     /// Queries the player to see if it can go to previous or not.
@@ -973,9 +1283,7 @@ impl Player {
     ///
     /// [can_prev]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanGoPrevious
     pub fn can_go_previous(&self) -> Result<bool, DBusError> {
-        self.connection_path()
-            .can_go_previous()
-            .map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "CanGoPrevious", Value::into_bool)
     }
 
     /// Queries the player to see if it can pause.
@@ -984,7 +1292,7 @@ impl Player {
     ///
     /// [can_pause]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanPause
     pub fn can_pause(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_pause().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "CanPause", Value::into_bool)
     }
 
     /// Queries the player to see if it can play.
@@ -993,7 +1301,7 @@ impl Player {
     ///
     /// [can_play]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanPlay
     pub fn can_play(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_play().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "CanPlay", Value::into_bool)
     }
 
     /// Queries the player to see if it can seek within the media.
@@ -1002,7 +1310,7 @@ impl Player {
     ///
     /// [can_seek]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:CanSeek
     pub fn can_seek(&self) -> Result<bool, DBusError> {
-        self.connection_path().can_seek().map_err(|e| e.into())
+        self.get_cached(PLAYER_INTERFACE, "CanSeek", Value::into_bool)
     }
 
     /// Queries the player to see if it can stop.
@@ -1026,52 +1334,32 @@ impl Player {
 
     /// Queries the player to see if it supports the "Shuffle" setting
     pub fn can_shuffle(&self) -> Result<bool, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        self.connection_path()
-            .get_all("org.mpris.MediaPlayer2.Player")
-            .map(|props| props.contains_key("Shuffle"))
-            .map_err(DBusError::from)
+        self.get_player_capabilities()
+            .map(|capabilities| capabilities.contains(Capabilities::SHUFFLE))
     }
 
     /// Queries the player to see if it supports the "LoopStatus" setting
     pub fn can_loop(&self) -> Result<bool, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        self.connection_path()
-            .get_all("org.mpris.MediaPlayer2.Player")
-            .map(|props| props.contains_key("LoopStatus"))
-            .map_err(DBusError::from)
+        self.get_player_capabilities()
+            .map(|capabilities| capabilities.contains(Capabilities::LOOP))
     }
 
     /// Queries the player to see if it supports the "Rate" setting
     pub fn has_playback_rate(&self) -> Result<bool, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        self.connection_path()
-            .get_all("org.mpris.MediaPlayer2.Player")
-            .map(|props| props.contains_key("Rate"))
-            .map_err(DBusError::from)
+        self.get_player_capabilities()
+            .map(|capabilities| capabilities.contains(Capabilities::PLAYBACK_RATE))
     }
 
     /// Queries the player to see if it supports the "Position" setting
     pub fn has_position(&self) -> Result<bool, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        self.connection_path()
-            .get_all("org.mpris.MediaPlayer2.Player")
-            .map(|props| props.contains_key("Position"))
-            .map_err(DBusError::from)
+        self.get_player_capabilities()
+            .map(|capabilities| capabilities.contains(Capabilities::POSITION))
     }
 
     /// Queries the player to see if it supports the "Volume" setting
     pub fn has_volume(&self) -> Result<bool, DBusError> {
-        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
-
-        self.connection_path()
-            .get_all("org.mpris.MediaPlayer2.Player")
-            .map(|props| props.contains_key("Volume"))
-            .map_err(DBusError::from)
+        self.get_player_capabilities()
+            .map(|capabilities| capabilities.contains(Capabilities::VOLUME))
     }
 
     /// Query the player for current fullscreen state.
@@ -1085,7 +1373,7 @@ impl Player {
     ///
     /// [full]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:Fullscreen
     pub fn get_fullscreen(&self) -> Result<Option<bool>, DBusError> {
-        handle_optional_property(self.connection_path().fullscreen())
+        self.get_optional_cached(ROOT_INTERFACE, "Fullscreen", Value::into_bool)
     }
 
     /// Asks the player to change fullscreen state.
@@ -1101,14 +1389,17 @@ impl Player {
     ///
     /// [full]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html#Property:Fullscreen
     pub fn set_fullscreen(&self, new_state: bool) -> Result<bool, DBusError> {
-        handle_optional_property(self.connection_path().set_fullscreen(new_state))
-            .map(|o| o.is_some())
+        handle_optional_property(
+            self.command_path()
+                .set_fullscreen(new_state)
+                .map_err(|e| DBusError::from_call(e, "Fullscreen")),
+        )
+        .map(|o| o.is_some())
     }
 
     /// Query the player for current playback status.
     pub fn get_playback_status(&self) -> Result<PlaybackStatus, DBusError> {
-        self.connection_path()
-            .playback_status()?
+        self.get_cached(PLAYER_INTERFACE, "PlaybackStatus", Value::into_string)?
             .parse()
             .map_err(DBusError::from)
     }
@@ -1119,7 +1410,7 @@ impl Player {
     ///
     /// [shuffle]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Shuffle
     pub fn get_shuffle(&self) -> Result<bool, DBusError> {
-        self.connection_path().shuffle().map_err(DBusError::from)
+        self.get_cached(PLAYER_INTERFACE, "Shuffle", Value::into_bool)
     }
 
     /// Gets the "Shuffle" setting, if the player indicates that it supports it.
@@ -1140,9 +1431,9 @@ impl Player {
     ///
     /// [shuffle]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Shuffle
     pub fn set_shuffle(&self, state: bool) -> Result<(), DBusError> {
-        self.connection_path()
+        self.command_path()
             .set_shuffle(state)
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "Shuffle"))
     }
 
     /// Set the "Shuffle" setting of the player, if the player indicates that it supports the
@@ -1155,9 +1446,7 @@ impl Player {
     /// [shuffle]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Shuffle
     pub fn checked_set_shuffle(&self, state: bool) -> Result<bool, DBusError> {
         if self.can_control()? && self.can_shuffle()? {
-            self.set_shuffle(state)
-                .map(|_| true)
-                .map_err(DBusError::from)
+            self.set_shuffle(state).map(|_| true)
         } else {
             Ok(false)
         }
@@ -1169,8 +1458,7 @@ impl Player {
     ///
     /// [loop_status]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:LoopStatus
     pub fn get_loop_status(&self) -> Result<LoopStatus, DBusError> {
-        self.connection_path()
-            .loop_status()?
+        self.get_cached(PLAYER_INTERFACE, "LoopStatus", Value::into_string)?
             .parse()
             .map_err(DBusError::from)
     }
@@ -1193,9 +1481,9 @@ impl Player {
     ///
     /// [loop_status]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:LoopStatus
     pub fn set_loop_status(&self, status: LoopStatus) -> Result<(), DBusError> {
-        self.connection_path()
+        self.command_path()
             .set_loop_status(status.dbus_value())
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "LoopStatus"))
     }
 
     /// Set the loop status of the player, if the player indicates that supports it and that it can
@@ -1208,9 +1496,7 @@ impl Player {
     /// [loop_status]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:LoopStatus
     pub fn checked_set_loop_status(&self, status: LoopStatus) -> Result<bool, DBusError> {
         if self.can_control()? && self.can_loop()? {
-            self.set_loop_status(status)
-                .map(|_| true)
-                .map_err(DBusError::from)
+            self.set_loop_status(status).map(|_| true)
         } else {
             Ok(false)
         }
@@ -1225,7 +1511,7 @@ impl Player {
     ///
     /// [vol]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Volume
     pub fn get_volume(&self) -> Result<f64, DBusError> {
-        self.connection_path().volume().map_err(DBusError::from)
+        self.get_cached(PLAYER_INTERFACE, "Volume", Value::into_f64)
     }
 
     /// Gets the "Volume" setting, if the player indicates that it supports it.
@@ -1249,9 +1535,9 @@ impl Player {
     ///
     /// [vol]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Volume
     pub fn set_volume(&self, value: f64) -> Result<(), DBusError> {
-        self.connection_path()
+        self.command_path()
             .set_volume(value.max(0.0))
-            .map_err(DBusError::from)
+            .map_err(|e| DBusError::from_call(e, "Volume"))
     }
 
     /// Set the "Volume" setting of the player, if the player indicates that it supports the
@@ -1264,9 +1550,7 @@ impl Player {
     /// [vol]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Property:Volume
     pub fn checked_set_volume(&self, volume: f64) -> Result<bool, DBusError> {
         if self.can_control()? && self.has_volume()? {
-            self.set_volume(volume)
-                .map(|_| true)
-                .map_err(DBusError::from)
+            self.set_volume(volume).map(|_| true)
         } else {
             Ok(false)
         }
@@ -1297,46 +1581,320 @@ impl Player {
         )
     }
 
-    /// Blocks until player gets an event on the bus.
-    ///
-    /// Other player events will also be recorded, but will not cause this function to return. Note
-    /// that this will block forever if player is not running. Make sure to check that the player
-    /// is running before calling this method!
-    pub(crate) fn process_events_blocking_until_received(&self) {
-        while !self.connection.has_pending_events(&self.unique_name) {
-            self.connection.process_events_blocking_until_received();
+    /// Reads all properties of an interface with a single call, and caches the ones that the
+    /// player announces changes of.
+    fn get_all_properties(&self, interface: &str) -> Result<HashMap<String, Value>, DBusError> {
+        let mut reply = self
+            .connection_path()
+            .method_call_with_args(
+                &"org.freedesktop.DBus.Properties".into(),
+                &"GetAll".into(),
+                |message| {
+                    dbus::arg::IterAppend::new(message).append(interface);
+                },
+            )
+            .map_err(|e| DBusError::from_call(e, "GetAll"))?;
+        reply
+            .as_result()
+            .map_err(|e| DBusError::from_call(e, "GetAll"))?;
+        let properties: HashMap<String, Value> = reply.read1()?;
+
+        if let Some(ref cache) = self.cache {
+            cache.insert_all(interface, &properties);
         }
+        Ok(properties)
     }
 
-    /// Return any events that are pending (for this player) on the connection.
-    pub(crate) fn pending_events(&self) -> Vec<MprisEvent> {
-        self.connection.pending_events(&self.unique_name)
+    /// Reads all properties of the `org.mpris.MediaPlayer2.Player` interface with a single call.
+    pub(crate) fn get_player_properties(&self) -> Result<HashMap<String, Value>, DBusError> {
+        self.get_all_properties(PLAYER_INTERFACE)
+    }
+
+    /// Reads all properties of an interface, answering from the cache when all of them are
+    /// cached.
+    fn get_cached_properties(&self, interface: &str) -> Result<HashMap<String, Value>, DBusError> {
+        if let Some(ref cache) = self.cache {
+            // Apply the changes that were already announced before looking at the cache.
+            self.connection.process_pending_messages();
+            if let Some(properties) = cache.get_all(interface) {
+                return Ok(properties);
+            }
+        }
+        self.get_all_properties(interface)
+    }
+
+    /// Reads the capabilities of the `org.mpris.MediaPlayer2.Player` interface.
+    fn get_player_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_cached_properties(PLAYER_INTERFACE)
+            .map(|properties| Capabilities::from_player_properties(&properties))
+    }
+
+    /// Reads the capabilities of the `org.mpris.MediaPlayer2` interface.
+    pub(crate) fn get_root_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_all_properties(ROOT_INTERFACE)
+            .map(|properties| Capabilities::from_root_properties(&properties))
     }
-}
 
-fn handle_optional_property<T>(result: Result<T, dbus::Error>) -> Result<Option<T>, DBusError> {
-    if let Err(ref error) = result {
-        if let Some(error_name) = error.name() {
-            if error_name == "org.freedesktop.DBus.Error.InvalidArgs" {
-                // This property was likely just missing, which means that the player has not
-                // implemented it.
-                return Ok(None);
+    /// Returns a [`ConnPath`] for calls that can change the state of the player.
+    ///
+    /// All cached properties are forgotten first, as players are free to announce the changes
+    /// only after replying to the call. The call also counts as activity for
+    /// [`ActivePlayerTracker`](crate::ActivePlayerTracker)s.
+    fn command_path(&self) -> ConnPath<'_, &Connection> {
+        if let Some(ref cache) = self.cache {
+            cache.clear();
+        }
+        self.connection.record_activity(&self.unique_name);
+        self.connection_path()
+    }
+
+    /// Reads a property that the player announces changes of, answering from the cache when
+    /// possible.
+    fn get_cached_value(&self, interface: &str, property: &str) -> Result<Value, DBusError> {
+        use dbus::ffidisp::stdintf::org_freedesktop_dbus::Properties;
+
+        let cache = match self.cache {
+            Some(ref cache) => cache,
+            None => {
+                return self
+                    .connection_path()
+                    .get(interface, property)
+                    .map_err(|e| DBusError::from_call(e, property))
             }
+        };
+
+        // Apply the changes that were already announced before looking at the cache.
+        self.connection.process_pending_messages();
+        if let Some(value) = cache.get(interface, property) {
+            return Ok(value);
+        }
+
+        let value: Value = self
+            .connection_path()
+            .get(interface, property)
+            .map_err(|e| DBusError::from_call(e, property))?;
+        cache.insert(interface, property, value.clone());
+        Ok(value)
+    }
+
+    fn get_cached<T, F>(&self, interface: &str, property: &str, convert: F) -> Result<T, DBusError>
+    where
+        F: FnOnce(Value) -> Option<T>,
+    {
+        let value = self.get_cached_value(interface, property)?;
+        convert(value).ok_or_else(|| unexpected_type(property))
+    }
+
+    fn get_optional_cached<T, F>(
+        &self,
+        interface: &str,
+        property: &str,
+        convert: F,
+    ) -> Result<Option<T>, DBusError>
+    where
+        F: FnOnce(Value) -> Option<T>,
+    {
+        match handle_optional_property(self.get_cached_value(interface, property))? {
+            Some(value) => convert(value)
+                .map(Some)
+                .ok_or_else(|| unexpected_type(property)),
+            None => Ok(None),
         }
     }
 
-    result.map(Some).map_err(|e| e.into())
+    /// Starts collecting the events of this player for a new reader. The reader gets all events
+    /// from now on, regardless of other readers of the same player.
+    pub(crate) fn subscribe_events(&self) -> Rc<EventSubscription> {
+        self.connection.subscribe_events(&self.unique_name)
+    }
+}
+
+pub(crate) fn handle_optional_property<T>(
+    result: Result<T, DBusError>,
+) -> Result<Option<T>, DBusError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        // This property was likely just missing, which means that the player has not implemented
+        // it.
+        Err(DBusError::NotSupported { .. }) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn unexpected_type(property: &str) -> DBusError {
+    DBusError::Miscellaneous(format!(
+        "Player returned a value of an unexpected type for {}",
+        property
+    ))
+}
+
+fn into_string_array(value: Value) -> Option<Vec<String>> {
+    value
+        .into_array()?
+        .into_iter()
+        .map(Value::into_string)
+        .collect()
 }
 
-/// Checks if the Player implements the `org.mpris.MediaPlayer2.TrackList` interface.
-fn has_tracklist_interface(connection: ConnPath<'_, &Connection>) -> Result<bool, DBusError> {
-    // Get the introspection XML and look for the substring instead of parsing the XML. Yeah,
-    // pretty dirty, but it's also a lot faster and doesn't require a huge XML library as a
-    // dependency either.
+/// Returns the introspection XML of the Player, used to check which optional interfaces (like
+/// `org.mpris.MediaPlayer2.TrackList`) the Player implements.
+fn introspect(connection: ConnPath<'_, &Connection>) -> Result<String, DBusError> {
+    // Callers look for interface names as substrings instead of parsing the XML. Yeah, pretty
+    // dirty, but it's also a lot faster and doesn't require a huge XML library as a dependency
+    // either.
     //
     // It's probably accurate enough.
 
     use dbus::ffidisp::stdintf::OrgFreedesktopDBusIntrospectable;
-    let xml: String = connection.introspect()?;
-    Ok(xml.contains("org.mpris.MediaPlayer2.TrackList"))
+    connection.introspect().map_err(DBusError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve};
+    use crate::PlayerFinder;
+    use std::thread;
+
+    fn cached(player: &Player, property: &str) -> Option<Value> {
+        player
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(PLAYER_INTERFACE, property))
+    }
+
+    #[test]
+    fn it_reports_unsupported_calls() {
+        let (bus_name, handle) = serve("mpris_rs_test_unsupported");
+        let player = connect(bus_name);
+
+        match player.get_track_list() {
+            Err(DBusError::NotSupported { member, .. }) => assert_eq!(member, "Tracks"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_takes_snapshots() {
+        let (bus_name, handle) = serve("mpris_rs_test_snapshot");
+        let player = connect(bus_name);
+
+        let state = player.snapshot().unwrap();
+        assert_eq!(state.identity(), "Test Player");
+        assert_eq!(state.playback_status(), PlaybackStatus::Paused);
+        assert_eq!(state.volume(), Some(1.0));
+        assert_eq!(state.metadata().title(), Some("Riverside"));
+        assert!(state.can_quit());
+        assert!(state.capabilities().contains(Capabilities::QUIT));
+        assert_eq!(player.get_capabilities().unwrap(), state.capabilities());
+
+        // Everything but the unannounced properties ends up in the cache.
+        assert_eq!(cached(&player, "Volume"), Some(Value::F64(1.0)));
+        assert_eq!(cached(&player, "Position"), None);
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_answers_capability_checks_from_the_cache() {
+        let (bus_name, handle) = serve("mpris_rs_test_cached_capabilities");
+        let player = connect(bus_name);
+
+        let capabilities = player.snapshot().unwrap().capabilities();
+        let cache = player.cache.as_ref().unwrap();
+        assert!(cache.get_all(PLAYER_INTERFACE).is_some());
+        assert!(cache.get_all(ROOT_INTERFACE).is_some());
+
+        assert_eq!(player.get_capabilities().unwrap(), capabilities);
+        assert_eq!(
+            player.has_volume().unwrap(),
+            capabilities.contains(Capabilities::VOLUME)
+        );
+        assert_eq!(
+            player.has_position().unwrap(),
+            capabilities.contains(Capabilities::POSITION)
+        );
+        assert_eq!(
+            player.can_shuffle().unwrap(),
+            capabilities.contains(Capabilities::SHUFFLE)
+        );
+        assert_eq!(
+            player.can_set_playback_rate().unwrap(),
+            capabilities.contains(Capabilities::SET_PLAYBACK_RATE)
+        );
+
+        // Commands make the next check read the properties again.
+        player.play().unwrap();
+        assert!(cache.get_all(PLAYER_INTERFACE).is_none());
+        assert!(player.has_volume().unwrap());
+        assert!(cache.get_all(PLAYER_INTERFACE).is_some());
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_reads_every_property_without_cache() {
+        let (bus_name, handle) = serve("mpris_rs_test_no_cache");
+        let mut finder = PlayerFinder::new().unwrap();
+        finder.set_property_caching(false);
+        let mut player = finder
+            .find_all()
+            .unwrap()
+            .into_iter()
+            .find(|player| player.bus_name() == bus_name)
+            .expect("Could not find server");
+        assert!(!player.property_caching());
+
+        player.snapshot().unwrap();
+        assert!((player.get_volume().unwrap() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(cached(&player, "Volume"), None);
+
+        player.set_property_caching(true);
+        assert!((player.get_volume().unwrap() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(cached(&player, "Volume"), Some(Value::F64(1.0)));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_caches_announced_properties() {
+        let (bus_name, handle) = serve("mpris_rs_test_cache");
+        let player = connect(bus_name.clone());
+        let controller = connect(bus_name);
+
+        assert!((player.get_volume().unwrap() - 1.0).abs() < f64::EPSILON);
+        assert_eq!(cached(&player, "Volume"), Some(Value::F64(1.0)));
+
+        // Changes made by other clients reach the cache through `PropertiesChanged`.
+        controller.set_volume(0.5).unwrap();
+        for _ in 0..100 {
+            player.connection.process_pending_messages();
+            if cached(&player, "Volume") == Some(Value::F64(0.5)) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(cached(&player, "Volume"), Some(Value::F64(0.5)));
+        assert!((player.get_volume().unwrap() - 0.5).abs() < f64::EPSILON);
+
+        // Commands from the player itself must not leave stale values behind.
+        assert_eq!(
+            player.get_playback_status().unwrap(),
+            PlaybackStatus::Paused
+        );
+        player.play().unwrap();
+        assert_eq!(
+            player.get_playback_status().unwrap(),
+            PlaybackStatus::Playing
+        );
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
 }
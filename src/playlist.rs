@@ -0,0 +1,180 @@
+use std::fmt;
+
+use dbus::Path;
+use thiserror::Error;
+
+use super::DBusError;
+
+/// Represents [the MPRIS `Playlist` type][playlist].
+///
+/// A playlist has a unique ID (a D-Bus object path), a name and optionally an icon that can be
+/// shown next to the name.
+///
+/// [playlist]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Struct:Playlist
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Playlist {
+    id: String,
+    name: String,
+    icon: Option<String>,
+}
+
+/// Specifies the ordering of returned [`Playlist`]s.
+///
+/// See: [MPRIS2 specification about `Playlist_Ordering`][ordering]
+///
+/// [ordering]: https://specifications.freedesktop.org/mpris-spec/latest/Playlists_Interface.html#Enum:Playlist_Ordering
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum PlaylistOrdering {
+    /// Alphabetical ordering by name, ascending.
+    Alphabetical,
+
+    /// Ordering by creation date, oldest first.
+    CreationDate,
+
+    /// Ordering by last modified date, oldest first.
+    ModifiedDate,
+
+    /// Ordering by date of last playback, oldest first.
+    LastPlayDate,
+
+    /// A user-defined ordering.
+    UserDefined,
+}
+
+/// [`PlaylistOrdering`] had an invalid string value.
+#[derive(Debug, Error)]
+#[error(
+    "PlaylistOrdering must be one of Alphabetical, Created, Modified, Played, User, but was {0}"
+)]
+pub struct InvalidPlaylistOrdering(String);
+
+impl Playlist {
+    /// Create a new [`Playlist`].
+    ///
+    /// This is not something you should normally do as the IDs are only meaningful to the
+    /// [`Player`](crate::Player) that provided them. It can be useful for test fixtures, though.
+    ///
+    /// Will fail if the `id` is not a valid D-Bus object path.
+    pub fn new<I, N>(id: I, name: N, icon: Option<String>) -> Result<Self, String>
+    where
+        I: Into<String>,
+        N: Into<String>,
+    {
+        let id = id.into();
+        // Validate the ID by constructing a dbus::Path.
+        Path::new(id.as_str())?;
+        Ok(Playlist {
+            id,
+            name: name.into(),
+            icon: icon.filter(|icon| !icon.is_empty()),
+        })
+    }
+
+    /// The unique identifier of the playlist.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The name of the playlist, as it should be displayed to the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// An URI to an (optional) icon for the playlist.
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    pub(crate) fn as_path(&self) -> Path<'_> {
+        // All inputs to this class should be validated to work with [`dbus::Path`], so unwrapping
+        // should be safe here.
+        Path::new(self.id.as_str()).unwrap()
+    }
+}
+
+impl<'a> From<(Path<'a>, String, String)> for Playlist {
+    fn from((id, name, icon): (Path<'a>, String, String)) -> Self {
+        Playlist {
+            id: id.to_string(),
+            name,
+            icon: Some(icon).filter(|icon| !icon.is_empty()),
+        }
+    }
+}
+
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl ::std::str::FromStr for PlaylistOrdering {
+    type Err = InvalidPlaylistOrdering;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "Alphabetical" => Ok(PlaylistOrdering::Alphabetical),
+            "Created" => Ok(PlaylistOrdering::CreationDate),
+            "Modified" => Ok(PlaylistOrdering::ModifiedDate),
+            "Played" => Ok(PlaylistOrdering::LastPlayDate),
+            "User" => Ok(PlaylistOrdering::UserDefined),
+            other => Err(InvalidPlaylistOrdering(other.to_string())),
+        }
+    }
+}
+
+impl PlaylistOrdering {
+    pub(crate) fn dbus_value(self) -> &'static str {
+        match self {
+            PlaylistOrdering::Alphabetical => "Alphabetical",
+            PlaylistOrdering::CreationDate => "Created",
+            PlaylistOrdering::ModifiedDate => "Modified",
+            PlaylistOrdering::LastPlayDate => "Played",
+            PlaylistOrdering::UserDefined => "User",
+        }
+    }
+}
+
+impl From<InvalidPlaylistOrdering> for DBusError {
+    fn from(error: InvalidPlaylistOrdering) -> Self {
+        DBusError::EnumParseError(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_roundtrips_orderings() {
+        for ordering in &[
+            PlaylistOrdering::Alphabetical,
+            PlaylistOrdering::CreationDate,
+            PlaylistOrdering::ModifiedDate,
+            PlaylistOrdering::LastPlayDate,
+            PlaylistOrdering::UserDefined,
+        ] {
+            assert_eq!(
+                ordering.dbus_value().parse::<PlaylistOrdering>().unwrap(),
+                *ordering
+            );
+        }
+        assert!("Random".parse::<PlaylistOrdering>().is_err());
+    }
+
+    #[test]
+    fn it_treats_empty_icons_as_missing() {
+        let path = Path::new("/org/example/Playlist/1").unwrap();
+        let playlist = Playlist::from((path, String::from("Favorites"), String::new()));
+
+        assert_eq!(playlist.id(), "/org/example/Playlist/1");
+        assert_eq!(playlist.name(), "Favorites");
+        assert_eq!(playlist.icon(), None);
+    }
+
+    #[test]
+    fn it_validates_ids() {
+        assert!(Playlist::new("not a path", "Favorites", None).is_err());
+        assert!(Playlist::new("/playlist/1", "Favorites", None).is_ok());
+    }
+}
@@ -1,43 +1,141 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::rc::{Rc, Weak};
 use std::time::{Duration, Instant};
 
-use dbus::ffidisp::{ConnPath, Connection};
+use dbus::ffidisp::{ConnPath, Connection, Watch};
 use dbus::strings::{BusName, Path};
 use dbus::Message;
 
+use crate::cancel::CancelHandle;
 use crate::extensions::DurationExtensions;
 use crate::metadata::{Metadata, Value};
-use crate::player::MPRIS2_PATH;
+use crate::player::{MPRIS2_PATH, MPRIS2_PREFIX};
+use crate::playlist::Playlist;
+use crate::property_cache::PropertyCache;
 use crate::track_list::TrackID;
 
 #[derive(Debug)]
 pub(crate) struct PooledConnection {
     connection: Connection,
-    events: RefCell<HashMap<String, Vec<MprisEvent>>>,
+    event_queue_limit: Cell<EventQueueLimit>,
+    event_subscriptions: RefCell<Vec<Weak<EventSubscription>>>,
+    name_watchers: RefCell<Vec<Weak<NameChangeQueue>>>,
+    property_caches: RefCell<Vec<Weak<PropertyCache>>>,
+
+    /// If new [`Player`](crate::Player)s cache their properties.
+    property_caching: Cell<bool>,
+    activity_logs: RefCell<Vec<Weak<ActivityLog>>>,
+}
+
+/// The [`MprisEvent`]s of one player that a single reader, like a
+/// [`PlayerEvents`](crate::PlayerEvents) iterator, has not read yet. Every reader has its own
+/// subscription, so they do not take each other's events.
+#[derive(Debug)]
+pub(crate) struct EventSubscription {
+    unique_name: String,
+    events: RefCell<EventQueue>,
+
+    /// Set if an event could not be placed on the queue.
+    lost: Cell<bool>,
+
+    /// Cancels waiting for events of the reader.
+    cancel: RefCell<Option<CancelHandle>>,
 }
 
-const GET_NAME_OWNER_TIMEOUT: i32 = 100; // ms
-const NAME_HAS_OWNER_TIMEOUT: i32 = 100; // ms
+/// What happens when more [`Event`](crate::Event)s of a player come in than a reader can queue up,
+/// because it does not read them fast enough.
+///
+/// Set with [`PlayerFinder::set_event_overflow_policy`](crate::PlayerFinder::set_event_overflow_policy)
+/// or [`SharedPlayerFinder::set_event_overflow_policy`](crate::SharedPlayerFinder::set_event_overflow_policy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued events to make room for new ones. The reader reports
+    /// [`Event::EventsLost`](crate::Event::EventsLost) before the remaining events.
+    ///
+    /// Changes that are only reported as events, like [`Event::Seeked`](crate::Event::Seeked), are
+    /// lost, and the [`TrackList`](crate::TrackList) kept up to date by the reader can end up out of
+    /// sync with the player.
+    DropOldest,
+
+    /// Drop all queued events, and read the state of the player again once the reader catches up.
+    /// The reader reports [`Event::EventsLost`](crate::Event::EventsLost), followed by the
+    /// differences to the state it knew as if they were new changes.
+    ///
+    /// This is the default.
+    Resync,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        OverflowPolicy::Resync
+    }
+}
+
+/// How many events a single reader can queue up, and what happens when there are more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EventQueueLimit {
+    pub(crate) capacity: usize,
+    pub(crate) overflow: OverflowPolicy,
+}
+
+impl Default for EventQueueLimit {
+    fn default() -> Self {
+        EventQueueLimit {
+            capacity: DEFAULT_EVENT_QUEUE_CAPACITY,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// The [`MprisEvent`]s of one player, bounded by an [`EventQueueLimit`].
+#[derive(Debug)]
+pub(crate) struct EventQueue {
+    events: VecDeque<MprisEvent>,
+    limit: EventQueueLimit,
+
+    /// Set when events were dropped with [`OverflowPolicy::DropOldest`].
+    dropped: bool,
+}
+
+/// Queue of bus name changes for a single [`PlayerWatcher`](crate::PlayerWatcher).
+pub(crate) type NameChangeQueue = RefCell<VecDeque<NameChange>>;
+
+/// When each player was last active, by unique bus name, for a single
+/// [`ActivePlayerTracker`](crate::ActivePlayerTracker).
+pub(crate) type ActivityLog = RefCell<HashMap<String, Instant>>;
+
+/// Subscriptions for the signals that relate to players. See [`MprisMessage`] below for details.
+pub(crate) const MATCH_RULES: &[&str] = &[
+    "interface='org.freedesktop.DBus.Properties',member='PropertiesChanged',path='/org/mpris/MediaPlayer2'",
+    "interface='org.mpris.MediaPlayer2.Player',member='Seeked',path='/org/mpris/MediaPlayer2'",
+    "interface='org.mpris.MediaPlayer2.TrackList',path='/org/mpris/MediaPlayer2'",
+    "interface='org.mpris.MediaPlayer2.Playlists',member='PlaylistChanged',path='/org/mpris/MediaPlayer2'",
+    "type='signal',sender='org.freedesktop.DBus',interface='org.freedesktop.DBus',member='NameOwnerChanged'",
+];
+
+/// How many events of a player a single reader can queue up by default.
+pub(crate) const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// How often a [`CancelHandle`] is checked if waiting for it together with the connection fails.
+const CANCEL_CHECK_INTERVAL_MS: u32 = 100;
+
+pub(crate) const GET_NAME_OWNER_TIMEOUT: i32 = 100; // ms
+pub(crate) const NAME_HAS_OWNER_TIMEOUT: i32 = 100; // ms
 
 impl PooledConnection {
     pub(crate) fn new(connection: Connection) -> Self {
-        // Subscribe to events that relate to players. See [`MprisMessage`] below for details.
-        let _ = connection.add_match(
-            "interface='org.freedesktop.DBus.Properties',member='PropertiesChanged',path='/org/mpris/MediaPlayer2'",
-        );
-        let _ = connection.add_match(
-            "interface='org.mpris.MediaPlayer2.Player',member='Seeked',path='/org/mpris/MediaPlayer2'",
-        );
-        let _ = connection.add_match(
-            "interface='org.mpris.MediaPlayer2.TrackList',path='/org/mpris/MediaPlayer2'",
-        );
-        let _ = connection.add_match(
-            "type='signal',sender='org.freedesktop.DBus',interface='org.freedesktop.DBus',member='NameOwnerChanged'",
-        );
+        for rule in MATCH_RULES {
+            let _ = connection.add_match(rule);
+        }
         PooledConnection {
             connection,
-            events: RefCell::new(HashMap::new()),
+            event_queue_limit: Cell::new(EventQueueLimit::default()),
+            event_subscriptions: RefCell::new(Vec::new()),
+            name_watchers: RefCell::new(Vec::new()),
+            property_caches: RefCell::new(Vec::new()),
+            property_caching: Cell::new(true),
+            activity_logs: RefCell::new(Vec::new()),
         }
     }
 
@@ -46,7 +144,7 @@ impl PooledConnection {
         bus_name: BusName<'a>,
         path: Path<'a>,
         timeout_ms: i32,
-    ) -> ConnPath<'_, &'a Connection> {
+    ) -> ConnPath<'a, &'a Connection> {
         self.connection.with_path(bus_name, path, timeout_ms)
     }
 
@@ -86,29 +184,6 @@ impl PooledConnection {
             .and_then(|reply| reply.get1())
     }
 
-    /// Returns [`true`] if the given bus name has any pending events waiting to be processed.
-    ///
-    /// If you want to actually act on the messages, use [`pending_events`](Self::pending_events).
-    pub(crate) fn has_pending_events(&self, bus_name: &str) -> bool {
-        self.events
-            .try_borrow()
-            .ok()
-            .map(|map| map.contains_key(bus_name))
-            .unwrap_or(false)
-    }
-
-    /// Removes all pending events from a bus' queue and returns them.
-    ///
-    /// If you want to non-destructively check if a bus has anything queued, use
-    /// [`has_pending_events`](Self::has_pending_events).
-    pub(crate) fn pending_events(&self, bus_name: &str) -> Vec<MprisEvent> {
-        self.events
-            .try_borrow_mut()
-            .ok()
-            .and_then(|mut events| events.remove(bus_name))
-            .unwrap_or_default()
-    }
-
     /// Process events in a blocking fashion until the deadline/timebox [`Duration`] runs out.
     pub(crate) fn process_events_blocking_for(&self, duration: Duration) {
         let start = Instant::now();
@@ -135,6 +210,24 @@ impl PooledConnection {
         }
     }
 
+    /// Processes the messages that have already been received, without blocking.
+    pub(crate) fn process_pending_messages(&self) {
+        let messages: Vec<MprisMessage> = self
+            .connection
+            .incoming(0)
+            .flat_map(MprisMessage::try_parse)
+            .collect();
+        for message in messages {
+            self.process_message(message);
+        }
+    }
+
+    /// Returns the file descriptors that the connection currently wants to be told about when they
+    /// are ready.
+    pub(crate) fn watch_fds(&self) -> Vec<Watch> {
+        self.connection.watch_fds()
+    }
+
     /// Process events in a blocking fashion until any new event is found.
     pub(crate) fn process_events_blocking_until_received(&self) {
         // Loop will repeat every <internal> milliseconds, just waiting for new events to appear.
@@ -152,94 +245,479 @@ impl PooledConnection {
         }
     }
 
-    /// Takes a message and processes it appropriately. Returns the affected bus name, and a borrow
-    /// to the generated [`MprisEvent`], if applicable.
-    fn process_message(&self, message: MprisMessage) {
-        let mut events = match self.events.try_borrow_mut() {
-            Ok(val) => val,
-            Err(_) => {
-                // Drop the message. This is a better evil than triggering a panic inside a library
-                // like this.
-                return;
+    /// Process events in a blocking fashion until any new event is found, or the timebox
+    /// [`Duration`] runs out. Returns [`true`] if an event was found.
+    pub(crate) fn process_events_blocking_until_received_for(&self, duration: Duration) -> bool {
+        let start = Instant::now();
+
+        loop {
+            let ms_left = duration
+                .checked_sub(start.elapsed())
+                .map(|d| DurationExtensions::as_millis(&d))
+                .unwrap_or(0);
+
+            if let Some(message) = self
+                .connection
+                .incoming(ms_left as u32)
+                .flat_map(MprisMessage::try_parse)
+                .next()
+            {
+                self.process_message(message);
+                return true;
             }
-        };
 
-        match message {
-            MprisMessage::NameOwnerChanged {
-                new_owner,
-                old_owner,
-            } => {
-                // If `new_owner` is empty, then the client has quit.
-                if new_owner.is_empty() {
-                    // Clear out existing events, if any. Then add a "PlayerQuit" event on the
-                    // queue.
-                    events.insert(old_owner, vec![MprisEvent::PlayerQuit]);
+            if ms_left == 0 {
+                return false;
+            }
+        }
+    }
+
+    /// Blocks until a client acquires an MPRIS bus name that `accept` turns into a value, and
+    /// returns that value. Returns `Ok(None)` if the deadline passes first; without a deadline it
+    /// waits forever.
+    ///
+    /// Only names that appear on the queue are considered, so create it with
+    /// [`watch_names`](Self::watch_names) before looking for names that are already taken.
+    pub(crate) fn wait_for_acquired_name<T, E, F>(
+        &self,
+        name_changes: &NameChangeQueue,
+        deadline: Option<Instant>,
+        mut accept: F,
+    ) -> Result<Option<T>, E>
+    where
+        F: FnMut(String) -> Result<Option<T>, E>,
+    {
+        loop {
+            self.process_pending_messages();
+            loop {
+                let change = name_changes.borrow_mut().pop_front();
+                match change {
+                    Some(NameChange::Acquired { bus_name }) => {
+                        if let Some(value) = accept(bus_name)? {
+                            return Ok(Some(value));
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
                 }
             }
-            MprisMessage::PlayerPropertiesChanged { unique_name } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::PlayerPropertiesChanged);
+
+            match deadline.map(|deadline| deadline.checked_duration_since(Instant::now())) {
+                Some(Some(time_left)) => {
+                    self.process_events_blocking_until_received_for(time_left);
+                }
+                Some(None) => return Ok(None),
+                None => self.process_events_blocking_until_received(),
             }
-            MprisMessage::Seeked {
-                unique_name,
-                position_in_us,
-            } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::Seeked { position_in_us });
+        }
+    }
+
+    /// Process events in a blocking fashion until any new event is found, the timebox
+    /// [`Duration`] runs out, or the [`CancelHandle`] is cancelled. Without a timebox, only the
+    /// last two end the wait. Returns [`true`] if an event was found.
+    pub(crate) fn process_events_blocking_until_received_or_cancelled(
+        &self,
+        duration: Option<Duration>,
+        cancel: &CancelHandle,
+    ) -> bool {
+        let deadline = duration.and_then(|duration| Instant::now().checked_add(duration));
+        loop {
+            if cancel.is_cancelled() {
+                return false;
             }
-            MprisMessage::TrackListPropertiesChanged { unique_name } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::TrackListPropertiesChanged);
+
+            // Messages received while waiting for method replies are not announced on the file
+            // descriptors anymore, so always look at those first.
+            let messages: Vec<MprisMessage> = self
+                .connection
+                .incoming(0)
+                .flat_map(MprisMessage::try_parse)
+                .collect();
+            if !messages.is_empty() {
+                for message in messages {
+                    self.process_message(message);
+                }
+                return true;
             }
-            MprisMessage::TrackListReplaced {
-                unique_name, ids, ..
+
+            let time_left = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(time_left) if !time_left.is_zero() => Some(time_left),
+                    _ => return false,
+                },
+                None => None,
+            };
+
+            if cancel
+                .wait(&self.connection.watch_fds(), time_left)
+                .is_err()
+            {
+                // Fall back to checking the handle every now and then.
+                let ms = time_left.map_or(CANCEL_CHECK_INTERVAL_MS, |time_left| {
+                    DurationExtensions::as_millis(&time_left)
+                        .clamp(1, u64::from(CANCEL_CHECK_INTERVAL_MS)) as u32
+                });
+                if let Some(message) = self
+                    .connection
+                    .incoming(ms)
+                    .flat_map(MprisMessage::try_parse)
+                    .next()
+                {
+                    self.process_message(message);
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Process events in a blocking fashion until the timebox [`Duration`] runs out, or the
+    /// [`CancelHandle`] is cancelled.
+    pub(crate) fn process_events_blocking_for_or_cancelled(
+        &self,
+        duration: Duration,
+        cancel: &CancelHandle,
+    ) {
+        // A duration too large for an `Instant` means processing until cancelled.
+        let deadline = Instant::now().checked_add(duration);
+        while !cancel.is_cancelled() {
+            let time_left = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(time_left) if !time_left.is_zero() => Some(time_left),
+                    _ => break,
+                },
+                None => None,
+            };
+            self.process_events_blocking_until_received_or_cancelled(time_left, cancel);
+        }
+    }
+
+    /// Process events in a blocking fashion until the subscription has pending events. Returns
+    /// [`false`] if waiting was cancelled with the subscription's [`CancelHandle`] instead.
+    ///
+    /// Note that this will block forever if the player is not running and there is no
+    /// [`CancelHandle`].
+    pub(crate) fn process_events_blocking_until_queued(
+        &self,
+        subscription: &EventSubscription,
+    ) -> bool {
+        let cancel = subscription.cancel_handle();
+        while !subscription.has_pending_events() {
+            match cancel {
+                Some(ref cancel) => {
+                    if !self.process_events_blocking_until_received_or_cancelled(None, cancel)
+                        && cancel.is_cancelled()
+                    {
+                        return false;
+                    }
+                }
+                None => self.process_events_blocking_until_received(),
+            }
+        }
+        true
+    }
+
+    /// Returns the limit of the event queues of new [`EventSubscription`]s.
+    pub(crate) fn event_queue_limit(&self) -> EventQueueLimit {
+        self.event_queue_limit.get()
+    }
+
+    /// Sets the limit of the event queues of [`EventSubscription`]s created from now on.
+    pub(crate) fn set_event_queue_limit(&self, limit: EventQueueLimit) {
+        self.event_queue_limit.set(limit);
+    }
+
+    /// Returns a new [`EventSubscription`] that all future events of the player with the given
+    /// unique name will be placed on, until it is dropped.
+    ///
+    /// Events are only queued up for players that have a subscription.
+    pub(crate) fn subscribe_events(&self, unique_name: &str) -> Rc<EventSubscription> {
+        let subscription = Rc::new(EventSubscription {
+            unique_name: unique_name.to_string(),
+            events: RefCell::new(EventQueue::new(self.event_queue_limit())),
+            lost: Cell::new(false),
+            cancel: RefCell::new(None),
+        });
+        self.event_subscriptions
+            .borrow_mut()
+            .push(Rc::downgrade(&subscription));
+        subscription
+    }
+
+    /// Returns a new queue that all future changes of MPRIS bus names will be placed on. Changes
+    /// stop being queued once the returned queue is dropped.
+    pub(crate) fn watch_names(&self) -> Rc<NameChangeQueue> {
+        let queue = Rc::new(RefCell::new(VecDeque::new()));
+        self.name_watchers.borrow_mut().push(Rc::downgrade(&queue));
+        queue
+    }
+
+    /// Returns if [`Player`](crate::Player)s created from now on cache their properties.
+    pub(crate) fn property_caching(&self) -> bool {
+        self.property_caching.get()
+    }
+
+    /// Sets if [`Player`](crate::Player)s created from now on cache their properties.
+    pub(crate) fn set_property_caching(&self, enabled: bool) {
+        self.property_caching.set(enabled);
+    }
+
+    /// Returns a new, empty [`PropertyCache`] for the player with the given unique name. It is
+    /// kept up to date with the `PropertiesChanged` signals that the player emits, until it is
+    /// dropped.
+    pub(crate) fn cache_properties(&self, unique_name: &str) -> Rc<PropertyCache> {
+        let cache = Rc::new(PropertyCache::new(unique_name));
+        self.property_caches
+            .borrow_mut()
+            .push(Rc::downgrade(&cache));
+        cache
+    }
+
+    /// Returns a new, empty [`ActivityLog`]. Players are noted in it whenever they change their
+    /// playback status or track, or are sent a command, until it is dropped.
+    pub(crate) fn log_activity(&self) -> Rc<ActivityLog> {
+        let log = Rc::new(RefCell::new(HashMap::new()));
+        self.activity_logs.borrow_mut().push(Rc::downgrade(&log));
+        log
+    }
+
+    /// Notes in every [`ActivityLog`] that the player with the given unique name is active now.
+    pub(crate) fn record_activity(&self, unique_name: &str) {
+        let now = Instant::now();
+        self.update_activity_logs(|log| {
+            log.borrow_mut().insert(unique_name.to_string(), now);
+        });
+    }
+
+    /// Takes a message and queues up the [`MprisEvent`] it represents for the affected player, if
+    /// applicable.
+    fn process_message(&self, message: MprisMessage) {
+        match message {
+            MprisMessage::NameOwnerChanged {
+                ref name,
+                ref old_owner,
+                ref new_owner,
             } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::TrackListReplaced {
-                        ids: ids.into_iter().map(TrackID::from).collect(),
+                self.queue_name_changes(name, old_owner, new_owner);
+                if new_owner.is_empty() {
+                    self.update_property_caches(old_owner, PropertyCache::clear);
+                    self.update_activity_logs(|log| {
+                        log.borrow_mut().remove(old_owner);
                     });
+                }
             }
-            MprisMessage::TrackAdded {
-                unique_name,
-                after_id,
-                metadata,
+            MprisMessage::PropertiesChanged {
+                ref unique_name,
+                ref interface,
+                ref changes,
             } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::TrackAdded {
-                        after_id,
-                        metadata: Metadata::from(metadata),
-                    });
+                self.update_property_caches(unique_name, |cache| {
+                    cache.apply(interface, changes.as_ref())
+                });
+                if interface == "org.mpris.MediaPlayer2.Player"
+                    && changes
+                        .as_ref()
+                        .map_or(false, PropertiesChange::is_activity)
+                {
+                    self.record_activity(unique_name);
+                }
             }
-            MprisMessage::TrackRemoved { unique_name, id } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::TrackRemoved { id });
+            _ => {}
+        }
+
+        if let Some((unique_name, event)) = message.into_event() {
+            self.publish_event(&unique_name, event);
+        }
+    }
+
+    /// Places the event on every subscription for the player with the given unique name.
+    ///
+    /// The list of subscriptions is only borrowed mutably while adding to it or cleaning it up,
+    /// neither of which can happen during this call, so no subscription is ever skipped.
+    fn publish_event(&self, unique_name: &str, event: MprisEvent) {
+        let mut has_dropped = false;
+        for subscription in self.event_subscriptions.borrow().iter() {
+            match subscription.upgrade() {
+                Some(subscription) if subscription.unique_name == unique_name => {
+                    subscription.push(event.clone())
+                }
+                Some(_) => {}
+                None => has_dropped = true,
             }
-            MprisMessage::TrackMetadataChanged {
-                unique_name,
-                old_id,
-                metadata,
-            } => {
-                events
-                    .entry(unique_name)
-                    .or_default()
-                    .push(MprisEvent::TrackMetadataChanged {
-                        old_id,
-                        metadata: Metadata::from(metadata),
-                    });
+        }
+
+        if has_dropped {
+            if let Ok(mut subscriptions) = self.event_subscriptions.try_borrow_mut() {
+                subscriptions.retain(|subscription| subscription.strong_count() > 0);
+            }
+        }
+    }
+
+    fn update_property_caches<F: Fn(&PropertyCache)>(&self, unique_name: &str, update: F) {
+        self.property_caches
+            .borrow_mut()
+            .retain(|cache| match cache.upgrade() {
+                Some(cache) => {
+                    if cache.unique_name() == unique_name {
+                        update(&cache);
+                    }
+                    true
+                }
+                None => false,
+            });
+    }
+
+    fn update_activity_logs<F: Fn(&ActivityLog)>(&self, update: F) {
+        self.activity_logs
+            .borrow_mut()
+            .retain(|log| match log.upgrade() {
+                Some(log) => {
+                    update(&log);
+                    true
+                }
+                None => false,
+            });
+    }
+
+    fn queue_name_changes(&self, name: &str, old_owner: &str, new_owner: &str) {
+        // Watchers only care about players, not about every client on the bus.
+        if !name.starts_with(MPRIS2_PREFIX) {
+            return;
+        }
+
+        let changes = NameChange::from_owners(name, old_owner, new_owner);
+        let mut watchers = self.name_watchers.borrow_mut();
+        watchers.retain(|watcher| match watcher.upgrade() {
+            Some(queue) => {
+                queue.borrow_mut().extend(changes.iter().cloned());
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+/// A change of the owner of a well-known MPRIS bus name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum NameChange {
+    /// A client took the bus name.
+    Acquired { bus_name: String },
+
+    /// The client with the given unique name let go of the bus name, or quit.
+    Lost {
+        bus_name: String,
+        unique_name: String,
+    },
+}
+
+impl NameChange {
+    /// Converts the arguments of a `NameOwnerChanged` signal into the changes they represent. A
+    /// name that moved directly from one client to another is both lost and acquired.
+    pub(crate) fn from_owners(name: &str, old_owner: &str, new_owner: &str) -> Vec<NameChange> {
+        let mut changes = Vec::new();
+        if !old_owner.is_empty() {
+            changes.push(NameChange::Lost {
+                bus_name: name.to_string(),
+                unique_name: old_owner.to_string(),
+            });
+        }
+        if !new_owner.is_empty() {
+            changes.push(NameChange::Acquired {
+                bus_name: name.to_string(),
+            });
+        }
+        changes
+    }
+}
+
+impl EventQueue {
+    /// Creates an empty queue. A capacity of 0 is treated as 1.
+    pub(crate) fn new(limit: EventQueueLimit) -> EventQueue {
+        EventQueue {
+            events: VecDeque::new(),
+            limit: EventQueueLimit {
+                capacity: limit.capacity.max(1),
+                overflow: limit.overflow,
+            },
+            dropped: false,
+        }
+    }
+
+    /// Returns [`true`] if there are no events in the queue.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Adds an event to the end of the queue, making room for it according to the
+    /// [`OverflowPolicy`] if the queue is full.
+    pub(crate) fn push(&mut self, event: MprisEvent) {
+        if let MprisEvent::PlayerQuit = event {
+            // Clear out existing events, if any. They don't matter anymore.
+            self.events.clear();
+            self.dropped = false;
+        } else if self.events.len() >= self.limit.capacity {
+            match self.limit.overflow {
+                OverflowPolicy::DropOldest => {
+                    self.events.pop_front();
+                    self.dropped = true;
+                }
+                OverflowPolicy::Resync => {
+                    // Reading the state again also covers the new event.
+                    self.events.clear();
+                    self.events.push_back(MprisEvent::ResyncNeeded);
+                    return;
+                }
             }
         }
+        self.events.push_back(event);
+    }
+
+    /// Removes all events from the queue and returns them, in the order they were emitted in.
+    /// If events were dropped, the returned events start with [`MprisEvent::EventsDropped`].
+    pub(crate) fn take(&mut self) -> Vec<MprisEvent> {
+        let mut events = Vec::with_capacity(self.events.len() + 1);
+        if self.dropped {
+            self.dropped = false;
+            events.push(MprisEvent::EventsDropped);
+        }
+        events.extend(self.events.drain(..));
+        events
+    }
+}
+
+impl EventSubscription {
+    /// Adds an event to the queue. If the queue is in use, which can only happen if an event is
+    /// published while the events are being read, the reader is told to read everything again
+    /// instead.
+    fn push(&self, event: MprisEvent) {
+        match self.events.try_borrow_mut() {
+            Ok(mut events) => events.push(event),
+            Err(_) => self.lost.set(true),
+        }
+    }
+
+    /// Returns [`true`] if there are any pending events waiting to be processed.
+    pub(crate) fn has_pending_events(&self) -> bool {
+        self.lost.get() || !self.events.borrow().is_empty()
+    }
+
+    /// Returns the handle that cancels waiting for events of the reader, if any.
+    pub(crate) fn cancel_handle(&self) -> Option<CancelHandle> {
+        self.cancel.borrow().clone()
+    }
+
+    /// Sets the handle that cancels waiting for events of the reader.
+    pub(crate) fn set_cancel_handle(&self, cancel: CancelHandle) {
+        *self.cancel.borrow_mut() = Some(cancel);
+    }
+
+    /// Removes all pending events from the queue and returns them, in the order they were
+    /// emitted in.
+    pub(crate) fn take_events(&self) -> Vec<MprisEvent> {
+        let mut events = self.events.borrow_mut().take();
+        if self.lost.replace(false) {
+            events.insert(0, MprisEvent::ResyncNeeded);
+        }
+        events
     }
 }
 
@@ -252,9 +730,10 @@ impl From<Connection> for PooledConnection {
 /// Event that a Player / ProgressTracker / Event iterator should react on. These are read via the
 /// bus and placed on queues for each player. When a component asks for pending events of a player
 /// they will be returned in the same order as they were emitted in.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum MprisEvent {
     PlayerQuit,
+    RootPropertiesChanged,
     PlayerPropertiesChanged,
     Seeked {
         position_in_us: u64,
@@ -274,25 +753,34 @@ pub(crate) enum MprisEvent {
         old_id: TrackID,
         metadata: Metadata,
     },
+    PlaylistsPropertiesChanged,
+    PlaylistChanged {
+        playlist: Playlist,
+    },
+    /// Events were dropped because the reader fell behind. Everything has to be read again.
+    ResyncNeeded,
+    /// The oldest events were dropped because the reader fell behind.
+    EventsDropped,
 }
 
 /// Easier to use representation of supported [`D-Bus message`](Message).
 #[derive(Debug)]
 pub(crate) enum MprisMessage {
     NameOwnerChanged {
+        name: String,
         new_owner: String,
         old_owner: String,
     },
-    PlayerPropertiesChanged {
+    PropertiesChanged {
         unique_name: String,
+        interface: String,
+        /// [`None`] if the changes could not be parsed.
+        changes: Option<PropertiesChange>,
     },
     Seeked {
         unique_name: String,
         position_in_us: u64,
     },
-    TrackListPropertiesChanged {
-        unique_name: String,
-    },
     TrackListReplaced {
         unique_name: String,
         ids: Vec<TrackID>,
@@ -312,16 +800,113 @@ pub(crate) enum MprisMessage {
         old_id: TrackID,
         metadata: HashMap<String, Value>,
     },
+    PlaylistChanged {
+        unique_name: String,
+        playlist: Playlist,
+    },
+}
+
+/// The properties of an interface that changed, as announced by a `PropertiesChanged` signal.
+#[derive(Debug)]
+pub(crate) struct PropertiesChange {
+    /// Properties that changed, with their new values.
+    pub(crate) changed: HashMap<String, Value>,
+
+    /// Properties that changed, but whose new values were not sent along.
+    pub(crate) invalidated: Vec<String>,
+}
+
+impl PropertiesChange {
+    /// Returns [`true`] if the change of the `Player` interface means the user interacted with the
+    /// player, which is when the playback status or the track changes.
+    fn is_activity(&self) -> bool {
+        ["PlaybackStatus", "Metadata"].iter().any(|&name| {
+            self.changed.contains_key(name) || self.invalidated.iter().any(|i| i == name)
+        })
+    }
 }
 
 impl MprisMessage {
     /// Tries to convert the provided [`D-Bus message`](Message) into a MprisMessage; returns [`None`] if the
     /// message was not supported.
-    fn try_parse(message: Message) -> Option<Self> {
+    pub(crate) fn try_parse(message: Message) -> Option<Self> {
         MprisMessage::try_parse_name_owner_changed(&message)
             .or_else(|| MprisMessage::try_parse_mpris_signal(&message))
     }
 
+    /// Converts the message into the [`MprisEvent`] that it represents, together with the unique
+    /// bus name of the player that it applies to.
+    ///
+    /// Returns [`None`] if the message does not represent an event, e.g. a new owner for a bus
+    /// name.
+    pub(crate) fn into_event(self) -> Option<(String, MprisEvent)> {
+        let event = match self {
+            MprisMessage::NameOwnerChanged {
+                new_owner,
+                old_owner,
+                ..
+            } => {
+                // If `new_owner` is empty, then the client has quit.
+                return if new_owner.is_empty() {
+                    Some((old_owner, MprisEvent::PlayerQuit))
+                } else {
+                    None
+                };
+            }
+            MprisMessage::PropertiesChanged {
+                unique_name,
+                interface,
+                ..
+            } => {
+                let event = match interface.as_ref() {
+                    "org.mpris.MediaPlayer2" => MprisEvent::RootPropertiesChanged,
+                    "org.mpris.MediaPlayer2.Player" => MprisEvent::PlayerPropertiesChanged,
+                    "org.mpris.MediaPlayer2.TrackList" => MprisEvent::TrackListPropertiesChanged,
+                    "org.mpris.MediaPlayer2.Playlists" => MprisEvent::PlaylistsPropertiesChanged,
+                    _ => return None,
+                };
+                (unique_name, event)
+            }
+            MprisMessage::Seeked {
+                unique_name,
+                position_in_us,
+            } => (unique_name, MprisEvent::Seeked { position_in_us }),
+            MprisMessage::TrackListReplaced {
+                unique_name, ids, ..
+            } => (unique_name, MprisEvent::TrackListReplaced { ids }),
+            MprisMessage::TrackAdded {
+                unique_name,
+                after_id,
+                metadata,
+            } => (
+                unique_name,
+                MprisEvent::TrackAdded {
+                    after_id,
+                    metadata: Metadata::from(metadata),
+                },
+            ),
+            MprisMessage::TrackRemoved { unique_name, id } => {
+                (unique_name, MprisEvent::TrackRemoved { id })
+            }
+            MprisMessage::TrackMetadataChanged {
+                unique_name,
+                old_id,
+                metadata,
+            } => (
+                unique_name,
+                MprisEvent::TrackMetadataChanged {
+                    old_id,
+                    metadata: Metadata::from(metadata),
+                },
+            ),
+            MprisMessage::PlaylistChanged {
+                unique_name,
+                playlist,
+            } => (unique_name, MprisEvent::PlaylistChanged { playlist }),
+        };
+        Some(event)
+    }
+
     /// Return a [`MprisMessage::NameOwnerChanged`] if the provided D-Bus message is a
     /// org.freedesktop.DBus NameOwnerChanged message.
     fn try_parse_name_owner_changed(message: &Message) -> Option<Self> {
@@ -338,6 +923,7 @@ impl MprisMessage {
                 let old_owner: String = iter.read().ok()?;
                 let new_owner: String = iter.read().ok()?;
                 Some(MprisMessage::NameOwnerChanged {
+                    name,
                     new_owner,
                     old_owner,
                 })
@@ -352,7 +938,7 @@ impl MprisMessage {
                 let member = message
                     .member()
                     .map(|member| member.to_string())
-                    .unwrap_or_else(String::default);
+                    .unwrap_or_default();
                 return match member.as_ref() {
                     "PropertiesChanged" => try_parse_properties_changed(message),
                     "Seeked" => try_parse_seeked(message),
@@ -360,6 +946,7 @@ impl MprisMessage {
                     "TrackAdded" => try_parse_track_added(message),
                     "TrackRemoved" => try_parse_track_removed(message),
                     "TrackMetadataChanged" => try_parse_track_metadata_changed(message),
+                    "PlaylistChanged" => try_parse_playlist_changed(message),
                     _ => None,
                 };
             }
@@ -371,26 +958,37 @@ impl MprisMessage {
 fn try_parse_properties_changed(message: &Message) -> Option<MprisMessage> {
     let unique_name = message.sender().map(|bus_name| bus_name.to_string())?;
     let mut iter = message.iter_init();
-    let interface_name: String = iter.read().ok()?;
-    match interface_name.as_ref() {
-        "org.mpris.MediaPlayer2.Player" => {
-            Some(MprisMessage::PlayerPropertiesChanged { unique_name })
-        }
-        "org.mpris.MediaPlayer2.TrackList" => {
-            Some(MprisMessage::TrackListPropertiesChanged { unique_name })
-        }
-        _ => None,
+    let interface: String = iter.read().ok()?;
+    if interface != "org.mpris.MediaPlayer2" && !interface.starts_with(MPRIS2_PREFIX) {
+        return None;
     }
+
+    let changed: Option<HashMap<String, Value>> = iter.read().ok();
+    let invalidated: Option<Vec<String>> = iter.read().ok();
+    let changes = match (changed, invalidated) {
+        (Some(changed), Some(invalidated)) => Some(PropertiesChange {
+            changed,
+            invalidated,
+        }),
+        _ => None,
+    };
+
+    Some(MprisMessage::PropertiesChanged {
+        unique_name,
+        interface,
+        changes,
+    })
 }
 
 fn try_parse_seeked(message: &Message) -> Option<MprisMessage> {
     let unique_name = message.sender().map(|bus_name| bus_name.to_string())?;
     let mut iter = message.iter_init();
-    let position_in_us: u64 = iter.read().ok()?;
+    // The position is a signed 64-bit integer according to the specification.
+    let position_in_us: i64 = iter.read().ok()?;
 
     Some(MprisMessage::Seeked {
         unique_name,
-        position_in_us,
+        position_in_us: position_in_us.max(0) as u64,
     })
 }
 
@@ -443,3 +1041,167 @@ fn try_parse_track_metadata_changed(message: &Message) -> Option<MprisMessage> {
         metadata,
     })
 }
+
+fn try_parse_playlist_changed(message: &Message) -> Option<MprisMessage> {
+    let unique_name = message.sender().map(|bus_name| bus_name.to_string())?;
+    let mut iter = message.iter_init();
+    let playlist: (Path<'_>, String, String) = iter.read().ok()?;
+
+    Some(MprisMessage::PlaylistChanged {
+        unique_name,
+        playlist: Playlist::from(playlist),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, serve, TestBackend};
+    use crate::{Event, PlaybackStatus, Player, PlayerFinder};
+    use dbus::ffidisp::BusType;
+    use std::thread;
+
+    /// Serves a test player and connects to it, queuing up at most `capacity` events per reader.
+    fn serve_player(name: &str, capacity: usize) -> (Player, thread::JoinHandle<TestBackend>) {
+        let (bus_name, handle) = serve(name);
+        let connection = Connection::get_private(BusType::Session).unwrap();
+        let mut finder = PlayerFinder::for_connection(connection);
+        finder.set_event_queue_capacity(capacity);
+        let player = finder
+            .find_all()
+            .unwrap()
+            .into_iter()
+            .find(|player| player.bus_name() == bus_name)
+            .expect("Could not find server");
+        (player, handle)
+    }
+
+    fn seeked(position_in_us: u64) -> MprisEvent {
+        MprisEvent::Seeked { position_in_us }
+    }
+
+    fn positions(queue: &mut EventQueue) -> Vec<String> {
+        queue
+            .take()
+            .into_iter()
+            .map(|event| match event {
+                MprisEvent::Seeked { position_in_us } => position_in_us.to_string(),
+                MprisEvent::ResyncNeeded => "resync".to_string(),
+                MprisEvent::EventsDropped => "dropped".to_string(),
+                other => panic!("Unexpected event: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_bounds_event_queues() {
+        let mut queue = EventQueue::new(EventQueueLimit {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+        });
+        for position in 1..=3 {
+            queue.push(seeked(position));
+        }
+        assert_eq!(positions(&mut queue), ["dropped", "2", "3"]);
+        assert!(queue.is_empty());
+
+        let mut queue = EventQueue::new(EventQueueLimit {
+            capacity: 2,
+            overflow: OverflowPolicy::Resync,
+        });
+        for position in 1..=3 {
+            queue.push(seeked(position));
+        }
+        assert_eq!(positions(&mut queue), ["resync"]);
+        // Overflowing again coalesces into the same marker.
+        for position in 4..=9 {
+            queue.push(seeked(position));
+        }
+        assert_eq!(positions(&mut queue), ["resync", "9"]);
+
+        queue.push(seeked(10));
+        queue.push(MprisEvent::PlayerQuit);
+        assert!(matches!(queue.take().as_slice(), [MprisEvent::PlayerQuit]));
+
+        let mut queue = EventQueue::new(EventQueueLimit {
+            capacity: 0,
+            overflow: OverflowPolicy::DropOldest,
+        });
+        queue.push(seeked(1));
+        queue.push(seeked(2));
+        assert_eq!(positions(&mut queue), ["dropped", "2"]);
+    }
+
+    #[test]
+    fn it_fans_out_signals() {
+        let (player, handle) = serve_player("mpris_rs_test_fan_out", DEFAULT_EVENT_QUEUE_CAPACITY);
+        let mut first = player.events().unwrap();
+        let mut second = player.events().unwrap();
+        let mut tracker = player.track_progress(60_000).unwrap();
+
+        player.play().unwrap();
+        let tick = tracker.tick_timeout(Duration::from_secs(5));
+        assert!(tick.progress_changed);
+        assert_eq!(tick.progress.playback_status(), PlaybackStatus::Playing);
+        assert!(matches!(first.next(), Some(Ok(Event::Playing))));
+        assert!(matches!(second.next(), Some(Ok(Event::Playing))));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_reports_lost_events() {
+        let (player, handle) = serve_player("mpris_rs_test_lost_events", 1);
+        let mut events = player.events().unwrap();
+
+        player.set_volume(0.5).unwrap();
+        player.play().unwrap();
+        // The reply comes after the signals of the earlier calls.
+        player.get_position().unwrap();
+        player.connection().process_pending_messages();
+
+        assert!(matches!(events.try_next(), Ok(Some(Event::EventsLost))));
+        assert!(matches!(events.try_next(), Ok(Some(Event::Playing))));
+        assert!(matches!(
+            events.try_next(),
+            Ok(Some(Event::VolumeChanged(volume))) if (volume - 0.5).abs() < f64::EPSILON
+        ));
+        assert!(matches!(events.try_next(), Ok(None)));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_dispatches_from_external_loops() {
+        let (player, handle) = serve_player("mpris_rs_test_dispatch", DEFAULT_EVENT_QUEUE_CAPACITY);
+        let mut events = player.events().unwrap();
+        player.dispatch_ready();
+        assert!(matches!(events.try_next(), Ok(None)));
+
+        let watches = player.watch_fds();
+        assert!(watches.iter().any(|watch| watch.readable()));
+
+        // Changed through another connection, so this one is not busy waiting for a reply.
+        connect(player.bus_name().to_string())
+            .set_volume(0.5)
+            .unwrap();
+        let started = Instant::now();
+        let event = loop {
+            player.dispatch_ready();
+            if let Some(event) = events.try_next().unwrap() {
+                break event;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert!(matches!(
+            event,
+            Event::VolumeChanged(volume) if (volume - 0.5).abs() < f64::EPSILON
+        ));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+}
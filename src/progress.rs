@@ -1,11 +1,18 @@
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
 
-use super::{DBusError, LoopStatus, PlaybackStatus, TrackList, TrackListError};
+use super::{
+    CancelHandle, DBusError, LoopStatus, PlaybackStatus, Playlist, TrackList, TrackListError,
+};
+use crate::capabilities::Capabilities;
 use crate::extensions::DurationExtensions;
 use crate::metadata::Metadata;
+use crate::metadata::Value;
 use crate::player::Player;
-use crate::pooled_connection::MprisEvent;
+use crate::pooled_connection::{EventSubscription, MprisEvent};
+use crate::state::PlayerState;
 
 /// Struct containing information about current progress of a [`Player`].
 ///
@@ -27,18 +34,27 @@ pub struct Progress {
     position: Duration,
     rate: f64,
     current_volume: f64,
+
+    /// Only the capabilities of the `org.mpris.MediaPlayer2.Player` interface when read from a
+    /// player.
+    capabilities: Capabilities,
 }
 
-/// Controller for calculating [`Progress`] and maintaining a [`TrackList`] (if supported) for a given [`Player`].
+/// Controller for calculating [`Progress`] and maintaining a [`TrackList`] and active [`Playlist`]
+/// (if supported) for a given [`Player`].
 ///
 /// Call the [`tick`](Self::tick) method to get the most current [`Progress`] data.
 #[derive(Debug)]
 pub struct ProgressTracker<'a> {
     player: &'a Player,
     track_list: Option<TrackList>,
+    active_playlist: Option<Playlist>,
     interval: Duration,
     last_tick: Instant,
     last_progress: Progress,
+
+    /// The events of the player that were not processed yet.
+    events: Rc<EventSubscription>,
 }
 
 /// Return value of [`ProgressTracker::tick`](ProgressTracker::tick), which gives details about the latest refresh.
@@ -70,6 +86,26 @@ pub struct ProgressTick<'a> {
     /// * Metadata changed for a track
     pub track_list_changed: bool,
 
+    /// [`true`] if the player's playlists changed. This will always be [`false`] if player does not
+    /// support playlists.
+    ///
+    /// **Examples:**
+    ///
+    /// * Another playlist was activated
+    /// * A playlist was renamed
+    /// * A playlist was added or removed
+    pub playlists_changed: bool,
+
+    /// [`true`] if the tracker's [`CancelHandle`] was cancelled, so the tick did not wait for the
+    /// interval. The tick still contains the changes found until then.
+    ///
+    /// See [`ProgressTracker::with_cancel_handle`].
+    pub cancelled: bool,
+
+    /// The first error that occurred while refreshing data during this tick, if any. The data
+    /// that could not be refreshed is reused from the last tick.
+    pub refresh_error: Option<ProgressError>,
+
     /// The current [`Progress`] from the [`ProgressTracker`]. [`progress_changed`](Self::progress_changed)
     /// tells you if this was reused from the last tick or if it's a new one.
     pub progress: &'a Progress,
@@ -77,6 +113,10 @@ pub struct ProgressTick<'a> {
     /// The current [`TrackList`] from the [`ProgressTracker`]. [`track_list_changed`](Self::track_list_changed)
     /// tells you if this was changed since the last tick.
     pub track_list: Option<&'a TrackList>,
+
+    /// The active [`Playlist`] of the [`Player`], if any. [`playlists_changed`](Self::playlists_changed)
+    /// tells you if playlists have changed since the last tick.
+    pub active_playlist: Option<&'a Playlist>,
 }
 
 /// Errors that can occur while refreshing progress.
@@ -103,12 +143,16 @@ impl<'a> ProgressTracker<'a> {
     ///
     /// Returns an error in case Player metadata or state retrieval over DBus fails.
     pub fn new(player: &'a Player, interval_ms: u32) -> Result<Self, DBusError> {
+        // Subscribe before reading the state, so no change is missed.
+        let events = player.subscribe_events();
         Ok(ProgressTracker {
             player,
+            events,
             interval: Duration::from_millis(u64::from(interval_ms)),
             last_tick: Instant::now(),
             last_progress: Progress::from_player(player)?,
             track_list: player.checked_get_track_list()?,
+            active_playlist: checked_get_active_playlist(player)?,
         })
     }
 
@@ -121,7 +165,8 @@ impl<'a> ProgressTracker<'a> {
     /// events to determine if something changed (and potentially perform a full refresh of the
     /// data). If there is no time left, then the previous data will be reused.
     ///
-    /// If refreshing failed for some reason the old data will be reused.
+    /// If refreshing failed for some reason the old data will be reused, and the error is returned
+    /// in [`refresh_error`](ProgressTick::refresh_error).
     ///
     /// It is recommended to call this inside a loop to maintain your progress display.
     ///
@@ -211,38 +256,76 @@ impl<'a> ProgressTracker<'a> {
     /// }
     /// ```
     pub fn tick(&mut self) -> ProgressTick<'_> {
+        self.tick_after(self.time_left())
+    }
+
+    /// Makes [`tick`](Self::tick) and [`tick_timeout`](Self::tick_timeout) stop waiting once the
+    /// handle is cancelled, even if they are blocked already. Their ticks then have
+    /// [`cancelled`](ProgressTick::cancelled) set, as have all later ticks.
+    ///
+    /// See [`CancelHandle`].
+    pub fn with_cancel_handle(self, cancel: CancelHandle) -> Self {
+        self.events.set_cancel_handle(cancel);
+        self
+    }
+
+    /// Like [`tick`](Self::tick), but never blocks. Only the signals that the player already sent
+    /// are processed.
+    ///
+    /// Useful for driving the tracker from another loop, like the frame loop of a UI.
+    pub fn try_tick(&mut self) -> ProgressTick<'_> {
+        self.tick_after(Duration::from_millis(0))
+    }
+
+    /// Like [`tick`](Self::tick), but blocks for at most the given timeout, even if the interval
+    /// has not passed yet.
+    pub fn tick_timeout(&mut self, timeout: Duration) -> ProgressTick<'_> {
+        self.tick_after(self.time_left().min(timeout))
+    }
+
+    /// Returns the time left until the interval since the last tick has passed.
+    fn time_left(&self) -> Duration {
+        self.interval
+            .checked_sub(self.last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_millis(0))
+    }
+
+    /// Processes signals for the given duration, and then returns a tick with all the changes.
+    fn tick_after(&mut self, wait: Duration) -> ProgressTick<'_> {
         let mut player_quit = false;
         let mut progress_changed = false;
         let mut track_list_changed = false;
+        let mut playlists_changed = false;
+        let mut refresh_error = None;
         let old_shuffle = self.last_progress.shuffle;
 
-        // Calculate time left until we're expected to return with new data.
-        let time_left = self
-            .interval
-            .checked_sub(self.last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_millis(0));
-
-        // Refresh events if we're not late.
-        if time_left > Duration::from_millis(0) {
-            self.player
-                .connection()
-                .process_events_blocking_for(time_left);
+        let connection = self.player.connection();
+        let cancel = self.events.cancel_handle();
+        match cancel {
+            Some(ref cancel) if wait > Duration::from_millis(0) => {
+                connection.process_events_blocking_for_or_cancelled(wait, cancel);
+            }
+            None if wait > Duration::from_millis(0) => connection.process_events_blocking_for(wait),
+            _ => connection.process_pending_messages(),
         }
+        let cancelled = cancel.map_or(false, |cancel| cancel.is_cancelled());
 
         // Process events that are queued up for us
-        for event in self.player.pending_events().into_iter() {
+        for event in self.events.take_events() {
             match event {
                 MprisEvent::PlayerQuit => {
                     player_quit = true;
                     break;
                 }
+                // None of the properties of the root interface are part of a tick.
+                MprisEvent::RootPropertiesChanged => {}
                 MprisEvent::PlayerPropertiesChanged | MprisEvent::Seeked { .. } => {
                     if !progress_changed {
-                        progress_changed |= self.refresh_player();
+                        progress_changed |= record(self.refresh_player(), &mut refresh_error);
                     }
                 }
                 MprisEvent::TrackListPropertiesChanged => {
-                    track_list_changed |= self.refresh_track_list();
+                    track_list_changed |= record(self.refresh_track_list(), &mut refresh_error);
                 }
                 MprisEvent::TrackListReplaced { ids } => {
                     if let Some(ref mut list) = self.track_list {
@@ -268,6 +351,24 @@ impl<'a> ProgressTracker<'a> {
                     }
                     track_list_changed = true;
                 }
+                MprisEvent::PlaylistsPropertiesChanged => {
+                    playlists_changed |= record(self.refresh_active_playlist(), &mut refresh_error);
+                }
+                MprisEvent::PlaylistChanged { playlist } => {
+                    if let Some(ref mut active) = self.active_playlist {
+                        if active.id() == playlist.id() {
+                            *active = playlist;
+                        }
+                    }
+                    playlists_changed = true;
+                }
+                // Nothing is read again with `OverflowPolicy::DropOldest`.
+                MprisEvent::EventsDropped => {}
+                MprisEvent::ResyncNeeded => {
+                    progress_changed |= record(self.refresh_player(), &mut refresh_error);
+                    track_list_changed |= record(self.refresh_track_list(), &mut refresh_error);
+                    playlists_changed |= record(self.refresh_active_playlist(), &mut refresh_error);
+                }
             }
         }
 
@@ -275,23 +376,27 @@ impl<'a> ProgressTracker<'a> {
             // Shuffle changed, which means that the tracklist is likely to have been changed too.
             // Do a reload, even if track_list_changed was true so the correct order is loaded even
             // if only a in-place change took place before.
-            track_list_changed |= self.refresh_track_list();
+            track_list_changed |= record(self.refresh_track_list(), &mut refresh_error);
         }
 
         self.last_tick = Instant::now();
         ProgressTick {
             progress: &self.last_progress,
             track_list: self.track_list.as_ref(),
+            active_playlist: self.active_playlist.as_ref(),
             player_quit,
             progress_changed,
             track_list_changed,
+            playlists_changed,
+            cancelled,
+            refresh_error,
         }
     }
 
     /// Force a refresh right now.
     ///
     /// This will ignore the interval and perform a refresh anyway. The new [`Progress`] will be
-    /// saved, and the [`TrackList`] will be refreshed.
+    /// saved, and the [`TrackList`] and active [`Playlist`] will be refreshed.
     ///
     /// # Errors
     ///
@@ -301,41 +406,83 @@ impl<'a> ProgressTracker<'a> {
         if let Some(ref mut list) = self.track_list {
             list.reload(self.player)?;
         }
+        self.active_playlist = checked_get_active_playlist(self.player)?;
         Ok(())
     }
 
-    fn refresh_player(&mut self) -> bool {
-        if let Ok(progress) = Progress::from_player(self.player) {
-            self.last_progress = progress;
-            return true;
-        }
-        false
+    fn refresh_player(&mut self) -> Result<bool, ProgressError> {
+        self.last_progress = Progress::from_player(self.player)?;
+        Ok(true)
     }
 
-    fn refresh_track_list(&mut self) -> bool {
+    fn refresh_track_list(&mut self) -> Result<bool, ProgressError> {
         match self.track_list {
-            Some(ref mut list) => list.reload(self.player).is_ok(),
-            None => false,
+            Some(ref mut list) => {
+                list.reload(self.player)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn refresh_active_playlist(&mut self) -> Result<bool, ProgressError> {
+        self.active_playlist = checked_get_active_playlist(self.player)?;
+        Ok(true)
+    }
+}
+
+/// Returns if a refresh changed anything. If it failed, the error is kept in `error` unless an
+/// earlier refresh failed already.
+fn record(result: Result<bool, ProgressError>, error: &mut Option<ProgressError>) -> bool {
+    match result {
+        Ok(changed) => changed,
+        Err(err) => {
+            if error.is_none() {
+                *error = Some(err);
+            }
+            false
+        }
+    }
+}
+
+fn checked_get_active_playlist(player: &Player) -> Result<Option<Playlist>, DBusError> {
+    if player.supports_playlists() {
+        player.get_active_playlist()
+    } else {
+        Ok(None)
+    }
+}
+
+impl From<PlayerState> for Progress {
+    fn from(state: PlayerState) -> Progress {
+        Progress {
+            playback_status: state.playback_status(),
+            shuffle: state.shuffle().unwrap_or(false),
+            loop_status: state.loop_status().unwrap_or(LoopStatus::None),
+            rate: state.playback_rate().unwrap_or(1.0),
+            position: state.position().unwrap_or_else(|| Duration::new(0, 0)),
+            current_volume: state.volume().unwrap_or(1.0),
+            capabilities: state.capabilities(),
+            metadata: state.into_metadata(),
+            instant: Instant::now(),
         }
     }
 }
 
 impl Progress {
+    /// Builds a [`Progress`] with a single `org.freedesktop.DBus.Properties.GetAll` call.
     pub(crate) fn from_player(player: &Player) -> Result<Progress, DBusError> {
-        Ok(Progress {
-            metadata: player.get_metadata()?,
-            playback_status: player.get_playback_status()?,
-            shuffle: player.checked_get_shuffle()?.unwrap_or(false),
-            loop_status: player
-                .checked_get_loop_status()?
-                .unwrap_or(LoopStatus::None),
-            rate: player.checked_get_playback_rate()?.unwrap_or(1.0),
-            position: player
-                .checked_get_position()?
-                .unwrap_or_else(|| Duration::new(0, 0)),
-            current_volume: player.checked_get_volume()?.unwrap_or(1.0),
-            instant: Instant::now(),
-        })
+        player
+            .get_player_properties()
+            .and_then(Progress::from_properties)
+    }
+
+    /// Builds a [`Progress`] from all the properties of the `org.mpris.MediaPlayer2.Player`
+    /// interface, as returned by `org.freedesktop.DBus.Properties.GetAll`.
+    pub(crate) fn from_properties(
+        properties: HashMap<String, Value>,
+    ) -> Result<Progress, DBusError> {
+        PlayerState::from_properties(HashMap::new(), properties).map(Progress::from)
     }
 
     /// The track metadata at the point in time that this Progress was constructed.
@@ -415,6 +562,10 @@ impl Progress {
         self.current_volume
     }
 
+    pub(crate) fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
     fn elapsed(&self) -> Duration {
         let elapsed_ms = match self.playback_status {
             PlaybackStatus::Playing => {
@@ -429,6 +580,8 @@ impl Progress {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::cancel::tests::cancel_later;
+    use crate::server::tests::{connect, serve};
 
     #[test]
     fn it_progresses_position_when_playing_at_microseconds() {
@@ -441,6 +594,7 @@ mod test {
             position: Duration::from_micros_ext(1),
             current_volume: 0.0,
             instant: Instant::now(),
+            capabilities: Capabilities::empty(),
         };
 
         assert_eq!(progress.initial_position(), Duration::from_micros_ext(1));
@@ -458,8 +612,51 @@ mod test {
             position: Duration::from_micros_ext(1336),
             current_volume: 0.0,
             instant: Instant::now() - Duration::from_millis(500),
+            capabilities: Capabilities::empty(),
         };
 
         assert_eq!(progress.position(), progress.initial_position());
     }
+
+    #[test]
+    fn it_ticks_without_blocking() {
+        let (bus_name, handle) = serve("mpris_rs_test_ticks");
+        let player = connect(bus_name);
+        let mut tracker = player.track_progress(60_000).unwrap();
+
+        let started = Instant::now();
+        assert!(!tracker.try_tick().progress_changed);
+        assert!(
+            !tracker
+                .tick_timeout(Duration::from_millis(50))
+                .progress_changed
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        player.play().unwrap();
+        let tick = tracker.tick_timeout(Duration::from_millis(500));
+        assert!(tick.progress_changed);
+        assert!(tick.refresh_error.is_none());
+        assert_eq!(tick.progress.playback_status(), PlaybackStatus::Playing);
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_cancels_ticks() {
+        let (bus_name, handle) = serve("mpris_rs_test_cancel_ticks");
+        let player = connect(bus_name);
+        let mut tracker = player
+            .track_progress(60_000)
+            .unwrap()
+            .with_cancel_handle(cancel_later(Duration::from_millis(100)));
+
+        let started = Instant::now();
+        assert!(tracker.tick().cancelled);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
 }
@@ -0,0 +1,218 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::metadata::Value;
+use crate::player::UNANNOUNCED_PROPERTIES;
+use crate::pooled_connection::PropertiesChange;
+
+/// Property values of a single player, kept up to date with the `PropertiesChanged` signals that
+/// the player emits.
+///
+/// Only properties that are announced with `PropertiesChanged` may be cached.
+#[derive(Debug)]
+pub(crate) struct PropertyCache {
+    unique_name: String,
+
+    /// Property values by name, for every interface.
+    interfaces: RefCell<HashMap<String, HashMap<String, Value>>>,
+
+    /// The properties of every interface that was read with `GetAll`, as long as that is still
+    /// known.
+    listings: RefCell<HashMap<String, Listing>>,
+}
+
+/// All properties that an interface had when it was read with `GetAll`.
+#[derive(Debug)]
+struct Listing {
+    names: HashSet<String>,
+
+    /// The values of the properties that are never announced, which are not kept up to date.
+    unannounced: HashMap<String, Value>,
+}
+
+impl PropertyCache {
+    pub(crate) fn new(unique_name: &str) -> Self {
+        PropertyCache {
+            unique_name: unique_name.to_string(),
+            interfaces: RefCell::new(HashMap::new()),
+            listings: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The unique bus name of the player that the properties belong to.
+    pub(crate) fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+
+    pub(crate) fn get(&self, interface: &str, property: &str) -> Option<Value> {
+        self.interfaces
+            .borrow()
+            .get(interface)
+            .and_then(|properties| properties.get(property))
+            .cloned()
+    }
+
+    pub(crate) fn insert(&self, interface: &str, property: &str, value: Value) {
+        self.interfaces
+            .borrow_mut()
+            .entry(interface.to_string())
+            .or_default()
+            .insert(property.to_string(), value);
+    }
+
+    /// Caches all the given properties of an interface, except for the ones that are never
+    /// announced. Which properties the interface has is remembered as well, for
+    /// [`get_all`](Self::get_all).
+    pub(crate) fn insert_all(&self, interface: &str, properties: &HashMap<String, Value>) {
+        let mut interfaces = self.interfaces.borrow_mut();
+        let cached = interfaces.entry(interface.to_string()).or_default();
+        let mut unannounced = HashMap::new();
+        for (property, value) in properties {
+            if UNANNOUNCED_PROPERTIES.contains(&property.as_str()) {
+                unannounced.insert(property.clone(), value.clone());
+            } else {
+                cached.insert(property.clone(), value.clone());
+            }
+        }
+
+        self.listings.borrow_mut().insert(
+            interface.to_string(),
+            Listing {
+                names: properties.keys().cloned().collect(),
+                unannounced,
+            },
+        );
+    }
+
+    /// Returns all properties of an interface, like `GetAll` would, if all of them are cached.
+    ///
+    /// The values of the properties that are never announced are the ones of the last
+    /// [`insert_all`](Self::insert_all), so they are only good for seeing if the interface has
+    /// them.
+    pub(crate) fn get_all(&self, interface: &str) -> Option<HashMap<String, Value>> {
+        let listings = self.listings.borrow();
+        let listing = listings.get(interface)?;
+        let interfaces = self.interfaces.borrow();
+        let cached = interfaces.get(interface)?;
+
+        let mut properties = listing.unannounced.clone();
+        for name in &listing.names {
+            if !properties.contains_key(name) {
+                properties.insert(name.clone(), cached.get(name)?.clone());
+            }
+        }
+        Some(properties)
+    }
+
+    /// Applies the changes announced by a `PropertiesChanged` signal. If the changes are not
+    /// known, every property of the interface is forgotten.
+    pub(crate) fn apply(&self, interface: &str, changes: Option<&PropertiesChange>) {
+        let mut interfaces = self.interfaces.borrow_mut();
+        match changes {
+            Some(changes) => {
+                let properties = interfaces.entry(interface.to_string()).or_default();
+                let mut listings = self.listings.borrow_mut();
+                let mut listing = listings.get_mut(interface);
+                for (property, value) in &changes.changed {
+                    properties.insert(property.clone(), value.clone());
+                    // The interface gained a property since it was read with `GetAll`.
+                    if let Some(ref mut listing) = listing {
+                        listing.names.insert(property.clone());
+                    }
+                }
+                for property in &changes.invalidated {
+                    properties.remove(property);
+                }
+            }
+            None => {
+                interfaces.remove(interface);
+                self.listings.borrow_mut().remove(interface);
+            }
+        }
+    }
+
+    /// Forgets all properties.
+    pub(crate) fn clear(&self) {
+        self.interfaces.borrow_mut().clear();
+        self.listings.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+    #[test]
+    fn it_applies_changes() {
+        let cache = PropertyCache::new(":1.42");
+        cache.insert(INTERFACE, "Volume", Value::F64(1.0));
+        cache.insert(INTERFACE, "Shuffle", Value::Bool(false));
+
+        let mut changed = HashMap::new();
+        changed.insert(String::from("Volume"), Value::F64(0.5));
+        cache.apply(
+            INTERFACE,
+            Some(&PropertiesChange {
+                changed,
+                invalidated: vec![String::from("Shuffle")],
+            }),
+        );
+
+        assert_eq!(cache.get(INTERFACE, "Volume"), Some(Value::F64(0.5)));
+        assert_eq!(cache.get(INTERFACE, "Shuffle"), None);
+    }
+
+    #[test]
+    fn it_forgets_interfaces_with_unknown_changes() {
+        let cache = PropertyCache::new(":1.42");
+        cache.insert(INTERFACE, "Volume", Value::F64(1.0));
+        cache.insert("org.mpris.MediaPlayer2", "CanQuit", Value::Bool(true));
+
+        cache.apply(INTERFACE, None);
+
+        assert_eq!(cache.get(INTERFACE, "Volume"), None);
+        assert_eq!(
+            cache.get("org.mpris.MediaPlayer2", "CanQuit"),
+            Some(Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn it_lists_complete_interfaces() {
+        let cache = PropertyCache::new(":1.42");
+        cache.insert(INTERFACE, "Volume", Value::F64(1.0));
+        assert_eq!(cache.get_all(INTERFACE), None);
+
+        let mut properties = HashMap::new();
+        properties.insert(String::from("Volume"), Value::F64(1.0));
+        properties.insert(String::from("Position"), Value::I64(42));
+        cache.insert_all(INTERFACE, &properties);
+        assert_eq!(cache.get_all(INTERFACE), Some(properties.clone()));
+        assert_eq!(cache.get(INTERFACE, "Position"), None);
+
+        // Properties that are announced later are added to the listing.
+        let mut changed = HashMap::new();
+        changed.insert(String::from("Rate"), Value::F64(1.0));
+        cache.apply(
+            INTERFACE,
+            Some(&PropertiesChange {
+                changed: changed.clone(),
+                invalidated: Vec::new(),
+            }),
+        );
+        properties.extend(changed);
+        assert_eq!(cache.get_all(INTERFACE), Some(properties));
+
+        // Properties that are invalidated have to be read again.
+        cache.apply(
+            INTERFACE,
+            Some(&PropertiesChange {
+                changed: HashMap::new(),
+                invalidated: vec![String::from("Volume")],
+            }),
+        );
+        assert_eq!(cache.get_all(INTERFACE), None);
+    }
+}
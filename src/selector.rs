@@ -0,0 +1,342 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::DBusError;
+use crate::Player;
+
+/// An expression that chooses which [`Player`] to control, for
+/// [`PlayerFinder::find_by_selector`](crate::PlayerFinder::find_by_selector).
+///
+/// A selector is a comma-separated list of terms, in order of priority. The first term that
+/// matches any running player wins; if it matches several players, the "active" one of them is
+/// picked like [`PlayerFinder::find_active`](crate::PlayerFinder::find_active) does. Terms that
+/// start with `!` exclude all players they match instead, regardless of their position.
+///
+/// | Term               | Matches                                                             |
+/// | ------------------ | ------------------------------------------------------------------- |
+/// | `spotify`          | Players with the [trimmed bus name](Player::bus_name_trimmed) `spotify`, or `spotify.<instance>` |
+/// | `vlc.instance*`    | Players with a trimmed bus name matching the glob; `*` and `?` are wildcards |
+/// | `desktop:firefox`  | Players with a matching [desktop entry](Player::get_desktop_entry), which may be a glob as well |
+/// | `%any`             | Any player                                                          |
+/// | `!firefox`         | Excludes the players that `firefox` would match                     |
+///
+/// All matching is case-insensitive. A selector with only exclusions behaves as if it ended in
+/// `%any`.
+///
+/// ```
+/// use mpris::PlayerSelector;
+///
+/// let selector: PlayerSelector = "spotify,vlc,%any,!firefox".parse().unwrap();
+/// assert_eq!(selector.to_string(), "spotify,vlc,%any,!firefox");
+/// assert!("spotify,,vlc".parse::<PlayerSelector>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSelector {
+    /// Terms in order of priority.
+    include: Vec<Term>,
+    exclude: Vec<Term>,
+}
+
+/// A single term of a [`PlayerSelector`], without the `!` for exclusions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Any,
+    BusName(String),
+    DesktopEntry(String),
+}
+
+/// The reasons that a [`PlayerSelector`] could not be parsed.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InvalidPlayerSelector {
+    /// The selector, or one of its terms, was empty.
+    #[error("Player selector contains an empty term")]
+    EmptyTerm,
+
+    /// A term started with `%`, but was not a known keyword like `%any`. The term is provided.
+    #[error("Unknown keyword in player selector: {0}")]
+    UnknownKeyword(String),
+
+    /// A term started with an unknown prefix, like `foo:`. The term is provided.
+    #[error("Unknown prefix in player selector: {0}")]
+    UnknownPrefix(String),
+}
+
+impl PlayerSelector {
+    /// Returns [`true`] if the player is picked by any term of the selector and not excluded by
+    /// any other.
+    ///
+    /// Returns an [`Err`] if the player's desktop entry had to be read and that failed.
+    pub fn matches(&self, player: &Player) -> Result<bool, DBusError> {
+        for term in &self.exclude {
+            if term.matches(player)? {
+                return Ok(false);
+            }
+        }
+        for term in self.priorities() {
+            if term.matches(player)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Picks the players of the highest priority term that matches any of the given ones, after
+    /// removing the excluded ones.
+    ///
+    /// A player whose desktop entry can't be read does not match terms that need it, but is
+    /// excluded by exclusions that need it, so an exclusion never lets through a player it might
+    /// have been meant for.
+    pub(crate) fn select(&self, players: Vec<Player>) -> Vec<Player> {
+        let mut candidates: Vec<Player> = players
+            .into_iter()
+            .filter(|player| !self.is_excluded(player))
+            .collect();
+
+        for term in self.priorities() {
+            let (matching, rest): (Vec<Player>, Vec<Player>) = candidates
+                .into_iter()
+                .partition(|player| term.matches(player).unwrap_or(false));
+
+            if !matching.is_empty() {
+                return matching;
+            }
+            candidates = rest;
+        }
+
+        Vec::new()
+    }
+
+    fn priorities(&self) -> &[Term] {
+        if self.include.is_empty() {
+            &[Term::Any]
+        } else {
+            &self.include
+        }
+    }
+
+    fn is_excluded(&self, player: &Player) -> bool {
+        self.exclude
+            .iter()
+            .any(|term| term.matches(player).unwrap_or(true))
+    }
+}
+
+impl Term {
+    fn matches(&self, player: &Player) -> Result<bool, DBusError> {
+        match self {
+            Term::DesktopEntry(_) => {
+                let desktop_entry = player.get_desktop_entry()?;
+                Ok(self.matches_names(player.bus_name_trimmed(), desktop_entry.as_deref()))
+            }
+            _ => Ok(self.matches_names(player.bus_name_trimmed(), None)),
+        }
+    }
+
+    fn matches_names(&self, bus_name_trimmed: &str, desktop_entry: Option<&str>) -> bool {
+        match self {
+            Term::Any => true,
+            Term::BusName(pattern) => {
+                let bus_name = bus_name_trimmed.to_lowercase();
+                if is_glob(pattern) {
+                    glob_matches(pattern, &bus_name)
+                } else {
+                    // Without a wildcard, every instance of the player matches.
+                    bus_name == *pattern
+                        || bus_name
+                            .strip_prefix(pattern.as_str())
+                            .map_or(false, |instance| instance.starts_with('.'))
+                }
+            }
+            Term::DesktopEntry(pattern) => desktop_entry
+                .map(|entry| glob_matches(pattern, &entry.to_lowercase()))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl FromStr for Term {
+    type Err = InvalidPlayerSelector;
+
+    fn from_str(term: &str) -> Result<Self, Self::Err> {
+        if term.is_empty() {
+            return Err(InvalidPlayerSelector::EmptyTerm);
+        }
+
+        if let Some(keyword) = term.strip_prefix('%') {
+            return match keyword {
+                "any" => Ok(Term::Any),
+                _ => Err(InvalidPlayerSelector::UnknownKeyword(term.to_string())),
+            };
+        }
+
+        match term.split_once(':') {
+            Some(("desktop", "")) => Err(InvalidPlayerSelector::EmptyTerm),
+            Some(("desktop", pattern)) => Ok(Term::DesktopEntry(pattern.to_lowercase())),
+            Some(_) => Err(InvalidPlayerSelector::UnknownPrefix(term.to_string())),
+            None => Ok(Term::BusName(term.to_lowercase())),
+        }
+    }
+}
+
+impl FromStr for PlayerSelector {
+    type Err = InvalidPlayerSelector;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+
+        for term in selector.split(',').map(str::trim) {
+            match term.strip_prefix('!') {
+                Some(term) => exclude.push(term.trim().parse()?),
+                None => include.push(term.parse()?),
+            }
+        }
+
+        Ok(PlayerSelector { include, exclude })
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Any => f.write_str("%any"),
+            Term::BusName(pattern) => f.write_str(pattern),
+            Term::DesktopEntry(pattern) => write!(f, "desktop:{}", pattern),
+        }
+    }
+}
+
+impl fmt::Display for PlayerSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let included = self.include.iter().map(|term| term.to_string());
+        let excluded = self.exclude.iter().map(|term| format!("!{}", term));
+        let terms: Vec<String> = included.chain(excluded).collect();
+        f.write_str(&terms.join(","))
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(|c| c == '*' || c == '?')
+}
+
+/// Matches a string against a glob where `*` matches any number of characters and `?` matches a
+/// single one.
+fn glob_matches(pattern: &str, string: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let string: Vec<char> = string.chars().collect();
+
+    // Position to continue from when the last `*` has to match one more character.
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut s) = (0, 0);
+    while s < string.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, s));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == string[s] => {
+                p += 1;
+                s += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    s = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(term: &str, bus_name_trimmed: &str, desktop_entry: Option<&str>) -> bool {
+        term.parse::<Term>()
+            .unwrap()
+            .matches_names(bus_name_trimmed, desktop_entry)
+    }
+
+    #[test]
+    fn it_parses_selectors() {
+        let selector: PlayerSelector = " spotify, VLC ,!firefox,desktop:mpv,%any".parse().unwrap();
+        assert_eq!(
+            selector.include,
+            vec![
+                Term::BusName("spotify".to_string()),
+                Term::BusName("vlc".to_string()),
+                Term::DesktopEntry("mpv".to_string()),
+                Term::Any,
+            ]
+        );
+        assert_eq!(selector.exclude, vec![Term::BusName("firefox".to_string())]);
+        assert_eq!(
+            selector.to_string(),
+            "spotify,vlc,desktop:mpv,%any,!firefox"
+        );
+
+        assert_eq!(
+            "".parse::<PlayerSelector>(),
+            Err(InvalidPlayerSelector::EmptyTerm)
+        );
+        assert_eq!(
+            "vlc,!".parse::<PlayerSelector>(),
+            Err(InvalidPlayerSelector::EmptyTerm)
+        );
+        assert_eq!(
+            "%all".parse::<PlayerSelector>(),
+            Err(InvalidPlayerSelector::UnknownKeyword("%all".to_string()))
+        );
+        assert_eq!(
+            "identity:vlc".parse::<PlayerSelector>(),
+            Err(InvalidPlayerSelector::UnknownPrefix(
+                "identity:vlc".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_matches_bus_names_and_instances() {
+        assert!(matches("vlc", "vlc", None));
+        assert!(matches("VLC", "vlc.instance1234", None));
+        assert!(!matches("vlc", "vlcx", None));
+        assert!(!matches("vlc.instance1", "vlc", None));
+        assert!(matches("vlc.instance*", "vlc.instance1234", None));
+        assert!(!matches("vlc.instance*", "vlc", None));
+        assert!(matches("chromium.instance?", "chromium.instance7", None));
+        assert!(matches("*", "anything", None));
+    }
+
+    #[test]
+    fn it_matches_desktop_entries() {
+        assert!(matches(
+            "desktop:firefox",
+            "firefox.instance1",
+            Some("firefox")
+        ));
+        assert!(matches(
+            "desktop:org.gnome.*",
+            "lollypop",
+            Some("org.gnome.Lollypop")
+        ));
+        assert!(!matches("desktop:firefox", "firefox", None));
+        assert!(matches("%any", "firefox", None));
+    }
+
+    #[test]
+    fn it_matches_globs() {
+        assert!(glob_matches("a*b*c", "aXXbYYc"));
+        assert!(glob_matches("a*c", "abcbc"));
+        assert!(!glob_matches("a*c", "abcb"));
+        assert!(glob_matches("", ""));
+        assert!(!glob_matches("", "a"));
+        assert!(glob_matches("**", ""));
+    }
+}
@@ -0,0 +1,1040 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use dbus::arg::{Append, IterAppend, Variant};
+use dbus::ffidisp::{BusType, Connection, NameFlag, RequestNameReply};
+use dbus::message::{MessageType, SignalArgs};
+use dbus::{Message, MethodErr, Path};
+
+use super::{DBusError, LoopStatus, PlaybackStatus, TrackID};
+use crate::extensions::DurationExtensions;
+use crate::generated::OrgMprisMediaPlayer2PlayerSeeked;
+use crate::metadata::Metadata;
+use crate::player::{MPRIS2_PATH, MPRIS2_PREFIX, UNANNOUNCED_PROPERTIES};
+
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+const INTROSPECTABLE_INTERFACE: &str = "org.freedesktop.DBus.Introspectable";
+
+const INTROSPECTION_XML: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg name="xml_data" type="s" direction="out"/>
+    </method>
+  </interface>
+  <interface name="org.freedesktop.DBus.Properties">
+    <method name="Get">
+      <arg name="interface_name" type="s" direction="in"/>
+      <arg name="property_name" type="s" direction="in"/>
+      <arg name="value" type="v" direction="out"/>
+    </method>
+    <method name="GetAll">
+      <arg name="interface_name" type="s" direction="in"/>
+      <arg name="properties" type="a{sv}" direction="out"/>
+    </method>
+    <method name="Set">
+      <arg name="interface_name" type="s" direction="in"/>
+      <arg name="property_name" type="s" direction="in"/>
+      <arg name="value" type="v" direction="in"/>
+    </method>
+    <signal name="PropertiesChanged">
+      <arg name="interface_name" type="s"/>
+      <arg name="changed_properties" type="a{sv}"/>
+      <arg name="invalidated_properties" type="as"/>
+    </signal>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="Fullscreen" type="b" access="readwrite"/>
+    <property name="CanSetFullscreen" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="DesktopEntry" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek">
+      <arg name="Offset" type="x" direction="in"/>
+    </method>
+    <method name="SetPosition">
+      <arg name="TrackId" type="o" direction="in"/>
+      <arg name="Position" type="x" direction="in"/>
+    </method>
+    <method name="OpenUri">
+      <arg name="Uri" type="s" direction="in"/>
+    </method>
+    <signal name="Seeked">
+      <arg name="Position" type="x"/>
+    </signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="LoopStatus" type="s" access="readwrite"/>
+    <property name="Rate" type="d" access="readwrite"/>
+    <property name="Shuffle" type="b" access="readwrite"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="readwrite"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>
+"#;
+
+/// The application side of an [`MprisServer`].
+///
+/// [`MprisServer`] calls these methods to answer property queries and to forward commands from
+/// MPRIS clients. Only [`identity`](Self::identity) and
+/// [`playback_status`](Self::playback_status) are required; everything else has a default that
+/// describes a player that does not support the feature.
+///
+/// Commands are only forwarded if the matching capability is reported (for example
+/// [`next`](Self::next) is only called when [`can_go_next`](Self::can_go_next) returns
+/// [`true`]), so implementations do not need to check that themselves.
+///
+/// **See:** [MPRIS2 MediaPlayer2 Specification][root] and [MPRIS2 MediaPlayer2.Player
+/// Specification][player].
+///
+/// [root]: https://specifications.freedesktop.org/mpris-spec/latest/Media_Player.html
+/// [player]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html
+pub trait PlayerBackend {
+    /// A friendly name to identify the media player to users, like "VLC media player".
+    fn identity(&self) -> String;
+
+    /// The current playback status.
+    fn playback_status(&self) -> PlaybackStatus;
+
+    /// The basename of an installed `.desktop` file for the application, without the `.desktop`
+    /// extension.
+    fn desktop_entry(&self) -> Option<String> {
+        None
+    }
+
+    /// The URI schemes supported by [`open_uri`](Self::open_uri).
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The MIME types supported by [`open_uri`](Self::open_uri).
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// If [`quit`](Self::quit) can be called.
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    /// Close the media player.
+    fn quit(&mut self) {}
+
+    /// If [`raise`](Self::raise) can be called.
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    /// Bring the media player's user interface to the front.
+    fn raise(&mut self) {}
+
+    /// Whether the media player is occupying the fullscreen, or [`None`] if the player has no
+    /// fullscreen mode.
+    fn fullscreen(&self) -> Option<bool> {
+        None
+    }
+
+    /// If [`set_fullscreen`](Self::set_fullscreen) can be called.
+    fn can_set_fullscreen(&self) -> bool {
+        false
+    }
+
+    /// Enter or leave fullscreen mode.
+    fn set_fullscreen(&mut self, _fullscreen: bool) {}
+
+    /// The metadata of the current track.
+    fn metadata(&self) -> Metadata {
+        Metadata::default()
+    }
+
+    /// The current track position.
+    fn position(&self) -> Duration {
+        Duration::from_secs(0)
+    }
+
+    /// The current loop status, or [`None`] if the player does not support looping.
+    fn loop_status(&self) -> Option<LoopStatus> {
+        None
+    }
+
+    /// Change the loop status.
+    fn set_loop_status(&mut self, _status: LoopStatus) {}
+
+    /// Whether playback is shuffled, or [`None`] if the player does not support shuffling.
+    fn shuffle(&self) -> Option<bool> {
+        None
+    }
+
+    /// Turn shuffling on or off.
+    fn set_shuffle(&mut self, _shuffle: bool) {}
+
+    /// The current playback rate.
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    /// The minimum value that [`rate`](Self::rate) can take.
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    /// The maximum value that [`rate`](Self::rate) can take.
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    /// Change the playback rate. Only called with values within
+    /// [`minimum_rate`](Self::minimum_rate) and [`maximum_rate`](Self::maximum_rate).
+    fn set_rate(&mut self, _rate: f64) {}
+
+    /// The volume level, where 1.0 is a sensible maximum.
+    fn volume(&self) -> f64 {
+        1.0
+    }
+
+    /// Change the volume. Never called with negative values.
+    fn set_volume(&mut self, _volume: f64) {}
+
+    /// Whether the player may be controlled at all. When this is [`false`], every other `can_*`
+    /// capability of the player is reported as [`false`] too.
+    fn can_control(&self) -> bool {
+        false
+    }
+
+    /// If [`next`](Self::next) can be called.
+    fn can_go_next(&self) -> bool {
+        false
+    }
+
+    /// If [`previous`](Self::previous) can be called.
+    fn can_go_previous(&self) -> bool {
+        false
+    }
+
+    /// If [`play`](Self::play) can be called.
+    fn can_play(&self) -> bool {
+        false
+    }
+
+    /// If [`pause`](Self::pause) and [`play_pause`](Self::play_pause) can be called.
+    fn can_pause(&self) -> bool {
+        false
+    }
+
+    /// If [`seek`](Self::seek) and [`set_position`](Self::set_position) can be called.
+    fn can_seek(&self) -> bool {
+        false
+    }
+
+    /// Skip to the next track.
+    fn next(&mut self) {}
+
+    /// Skip to the previous track.
+    fn previous(&mut self) {}
+
+    /// Pause playback.
+    fn pause(&mut self) {}
+
+    /// Pause playback if playing, otherwise start or resume it.
+    fn play_pause(&mut self) {}
+
+    /// Stop playback.
+    fn stop(&mut self) {}
+
+    /// Start or resume playback.
+    fn play(&mut self) {}
+
+    /// Seek forwards (positive offset) or backwards (negative offset) in the current track.
+    fn seek(&mut self, _offset_in_microseconds: i64) {}
+
+    /// Move to a position in the current track. Only called if `track_id` is the ID of the
+    /// current track.
+    fn set_position(&mut self, _track_id: &TrackID, _position: Duration) {}
+
+    /// Open the given URI.
+    fn open_uri(&mut self, _uri: &str) {}
+}
+
+/// A property value of one of the served interfaces.
+#[derive(Debug, Clone, PartialEq)]
+enum PropertyValue {
+    Bool(bool),
+    Double(f64),
+    Int64(i64),
+    String(String),
+    Strings(Vec<String>),
+    Metadata(Metadata),
+}
+
+impl Append for PropertyValue {
+    fn append_by_ref(&self, i: &mut IterAppend<'_>) {
+        match self {
+            PropertyValue::Bool(value) => i.append(Variant(*value)),
+            PropertyValue::Double(value) => i.append(Variant(*value)),
+            PropertyValue::Int64(value) => i.append(Variant(*value)),
+            PropertyValue::String(value) => i.append(Variant(value.as_str())),
+            PropertyValue::Strings(values) => i.append_variant(&"as".into(), |i| {
+                i.append_array(&"s".into(), |i| {
+                    for value in values {
+                        i.append(value.as_str());
+                    }
+                })
+            }),
+            PropertyValue::Metadata(metadata) => {
+                i.append_variant(&"a{sv}".into(), |i| metadata.append_by_ref(i))
+            }
+        }
+    }
+}
+
+type Properties = BTreeMap<&'static str, PropertyValue>;
+
+fn append_properties(properties: &Properties, i: &mut IterAppend<'_>) {
+    i.append_dict(&"s".into(), &"v".into(), |i| {
+        for (name, value) in properties {
+            i.append_dict_entry(|i| {
+                i.append(*name);
+                value.append_by_ref(i);
+            });
+        }
+    });
+}
+
+/// Publishes an application as an MPRIS2 media player on the D-Bus.
+///
+/// The server owns the bus name `org.mpris.MediaPlayer2.<name>` and serves the
+/// `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player` interfaces on
+/// `/org/mpris/MediaPlayer2`, backed by a [`PlayerBackend`].
+///
+/// The server does not spawn any threads; call [`process`](Self::process) regularly (for example
+/// from your main loop) to answer incoming calls. When the state of the application changes
+/// without a client asking for it, call
+/// [`notify_properties_changed`](Self::notify_properties_changed) or
+/// [`notify_seeked`](Self::notify_seeked) so that clients are told about it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mpris::{MprisServer, PlaybackStatus, PlayerBackend};
+///
+/// struct Radio {
+///     playing: bool,
+/// }
+///
+/// impl PlayerBackend for Radio {
+///     fn identity(&self) -> String {
+///         String::from("Radio")
+///     }
+///
+///     fn playback_status(&self) -> PlaybackStatus {
+///         if self.playing {
+///             PlaybackStatus::Playing
+///         } else {
+///             PlaybackStatus::Stopped
+///         }
+///     }
+///
+///     fn can_control(&self) -> bool {
+///         true
+///     }
+///
+///     fn can_play(&self) -> bool {
+///         true
+///     }
+///
+///     fn play(&mut self) {
+///         self.playing = true;
+///     }
+/// }
+///
+/// let mut server = MprisServer::new("radio", Radio { playing: false }).unwrap();
+/// loop {
+///     server.process(1000).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MprisServer<B> {
+    connection: Connection,
+    bus_name: String,
+    backend: B,
+    root_properties: Properties,
+    player_properties: Properties,
+    seeked: bool,
+}
+
+impl<B: PlayerBackend> MprisServer<B> {
+    /// Creates a new [`MprisServer`] on a new session bus connection, and registers it as
+    /// `org.mpris.MediaPlayer2.<name>`.
+    ///
+    /// Use [`for_connection`](Self::for_connection) if you want to provide the D-Bus connection
+    /// yourself.
+    pub fn new(name: &str, backend: B) -> Result<Self, DBusError> {
+        MprisServer::for_connection(Connection::get_private(BusType::Session)?, name, backend)
+    }
+
+    /// Creates a new [`MprisServer`] on the given connection, and registers it as
+    /// `org.mpris.MediaPlayer2.<name>`.
+    ///
+    /// Fails if the bus name is already owned by another connection.
+    pub fn for_connection(
+        connection: Connection,
+        name: &str,
+        backend: B,
+    ) -> Result<Self, DBusError> {
+        let bus_name = format!("{}{}", MPRIS2_PREFIX, name);
+        match connection.register_name(&bus_name, NameFlag::DoNotQueue.value())? {
+            RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {}
+            _ => {
+                return Err(DBusError::Miscellaneous(format!(
+                    "Bus name {} is already owned by another connection",
+                    bus_name
+                )))
+            }
+        }
+        connection.register_object_path(MPRIS2_PATH)?;
+
+        let root_properties = root_properties(&backend);
+        let player_properties = player_properties(&backend);
+
+        Ok(MprisServer {
+            connection,
+            bus_name,
+            backend,
+            root_properties,
+            player_properties,
+            seeked: false,
+        })
+    }
+
+    /// Returns the server's D-Bus bus name.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// Returns the [`PlayerBackend`].
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the [`PlayerBackend`] mutably.
+    ///
+    /// Remember to call [`notify_properties_changed`](Self::notify_properties_changed) after
+    /// changing the backend's state.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Waits up to `timeout_ms` for incoming calls and handles them, along with any other calls
+    /// that are already waiting.
+    ///
+    /// After each handled call, changed properties are announced with `PropertiesChanged` and a
+    /// successful `Seek` or `SetPosition` is announced with `Seeked`.
+    pub fn process(&mut self, timeout_ms: u32) -> Result<(), DBusError> {
+        let mut timeout_ms = timeout_ms;
+        while let Some(message) = self.connection.incoming(timeout_ms).next() {
+            timeout_ms = 0;
+
+            // Calls to other paths are answered by libdbus itself.
+            if message.msg_type() != MessageType::MethodCall
+                || message.path().as_deref() != Some(MPRIS2_PATH)
+            {
+                continue;
+            }
+
+            let reply = self
+                .handle_call(&message)
+                .unwrap_or_else(|error| error.to_message(&message));
+            self.send(reply)?;
+
+            if self.seeked {
+                self.seeked = false;
+                self.notify_seeked(self.backend.position())?;
+            }
+            self.notify_properties_changed()?;
+        }
+        Ok(())
+    }
+
+    /// Compares the backend's properties with the last announced ones, and emits
+    /// `PropertiesChanged` signals for the ones that changed.
+    ///
+    /// `Position` is never announced this way, as required by the MPRIS2 specification. Use
+    /// [`notify_seeked`](Self::notify_seeked) for that.
+    pub fn notify_properties_changed(&mut self) -> Result<(), DBusError> {
+        let root_properties = root_properties(&self.backend);
+        let player_properties = player_properties(&self.backend);
+
+        self.emit_properties_changed(ROOT_INTERFACE, &self.root_properties, &root_properties)?;
+        self.emit_properties_changed(
+            PLAYER_INTERFACE,
+            &self.player_properties,
+            &player_properties,
+        )?;
+
+        self.root_properties = root_properties;
+        self.player_properties = player_properties;
+        Ok(())
+    }
+
+    /// Emits the `Seeked` signal, telling clients that the track position changed in a way that
+    /// was not caused by normal playback (like seeking or restarting the track).
+    ///
+    /// See: [MPRIS2 specification about `Seeked`][seeked]
+    ///
+    /// [seeked]: https://specifications.freedesktop.org/mpris-spec/latest/Player_Interface.html#Signal:Seeked
+    pub fn notify_seeked(&self, position: Duration) -> Result<(), DBusError> {
+        let signal = OrgMprisMediaPlayer2PlayerSeeked {
+            position: DurationExtensions::as_micros(&position) as i64,
+        };
+        self.send(signal.to_emit_message(&MPRIS2_PATH.into()))
+    }
+
+    fn emit_properties_changed(
+        &self,
+        interface: &str,
+        old: &Properties,
+        new: &Properties,
+    ) -> Result<(), DBusError> {
+        let changed: Properties = new
+            .iter()
+            .filter(|(name, value)| {
+                !UNANNOUNCED_PROPERTIES.contains(*name) && old.get(*name) != Some(*value)
+            })
+            .map(|(name, value)| (*name, value.clone()))
+            .collect();
+        let invalidated: Vec<&str> = old
+            .keys()
+            .filter(|name| !UNANNOUNCED_PROPERTIES.contains(*name) && !new.contains_key(*name))
+            .cloned()
+            .collect();
+
+        if changed.is_empty() && invalidated.is_empty() {
+            return Ok(());
+        }
+
+        let mut signal = Message::signal(
+            &MPRIS2_PATH.into(),
+            &PROPERTIES_INTERFACE.into(),
+            &"PropertiesChanged".into(),
+        );
+        {
+            let mut i = IterAppend::new(&mut signal);
+            i.append(interface);
+            append_properties(&changed, &mut i);
+            i.append(invalidated);
+        }
+        self.send(signal)
+    }
+
+    fn send(&self, message: Message) -> Result<(), DBusError> {
+        self.connection
+            .send(message)
+            .map(|_| ())
+            .map_err(|_| DBusError::Miscellaneous(String::from("Could not send D-Bus message")))
+    }
+
+    fn properties(&self, interface: &str) -> Result<Properties, MethodErr> {
+        match interface {
+            ROOT_INTERFACE => Ok(root_properties(&self.backend)),
+            PLAYER_INTERFACE => Ok(player_properties(&self.backend)),
+            other => Err(MethodErr::no_interface(other)),
+        }
+    }
+
+    fn handle_call(&mut self, call: &Message) -> Result<Message, MethodErr> {
+        let interface = call.interface().map(|i| i.to_string()).unwrap_or_default();
+        let member = call.member().map(|m| m.to_string()).unwrap_or_default();
+
+        match (interface.as_str(), member.as_str()) {
+            (INTROSPECTABLE_INTERFACE, "Introspect") => {
+                Ok(call.method_return().append1(INTROSPECTION_XML))
+            }
+            (PROPERTIES_INTERFACE, "Get") => {
+                let (interface, name): (&str, &str) = call.read2()?;
+                let properties = self.properties(interface)?;
+                let value = properties.get(name).ok_or_else(|| unknown_property(name))?;
+                Ok(call.method_return().append1(value))
+            }
+            (PROPERTIES_INTERFACE, "GetAll") => {
+                let interface: &str = call.read1()?;
+                let properties = self.properties(interface)?;
+                let mut reply = call.method_return();
+                append_properties(&properties, &mut IterAppend::new(&mut reply));
+                Ok(reply)
+            }
+            (PROPERTIES_INTERFACE, "Set") => {
+                self.set_property(call)?;
+                Ok(call.method_return())
+            }
+            (ROOT_INTERFACE, "Raise") => {
+                if self.backend.can_raise() {
+                    self.backend.raise();
+                }
+                Ok(call.method_return())
+            }
+            (ROOT_INTERFACE, "Quit") => {
+                if self.backend.can_quit() {
+                    self.backend.quit();
+                }
+                Ok(call.method_return())
+            }
+            (PLAYER_INTERFACE, _) => self.handle_player_call(call, &member),
+            _ => Err(MethodErr::no_method(&member)),
+        }
+    }
+
+    fn handle_player_call(&mut self, call: &Message, member: &str) -> Result<Message, MethodErr> {
+        let can_control = self.backend.can_control();
+
+        match member {
+            "Next" if can_control && self.backend.can_go_next() => self.backend.next(),
+            "Previous" if can_control && self.backend.can_go_previous() => self.backend.previous(),
+            "Pause" if can_control && self.backend.can_pause() => self.backend.pause(),
+            "Play" if can_control && self.backend.can_play() => self.backend.play(),
+            "Next" | "Previous" | "Pause" | "Play" => {}
+            "PlayPause" if can_control && self.backend.can_pause() => self.backend.play_pause(),
+            "PlayPause" => return Err(MethodErr::failed("Player cannot pause")),
+            "Stop" if can_control => self.backend.stop(),
+            "Stop" => return Err(MethodErr::failed("Player cannot be controlled")),
+            "Seek" => {
+                let offset: i64 = call.read1()?;
+                if can_control && self.backend.can_seek() {
+                    self.backend.seek(offset);
+                    self.seeked = true;
+                }
+            }
+            "SetPosition" => {
+                let (track_id, position): (Path<'_>, i64) = call.read2()?;
+                let track_id = TrackID::from(track_id);
+                let metadata = self.backend.metadata();
+                let is_current_track = metadata.track_id().as_ref() == Some(&track_id);
+                let in_track = position >= 0
+                    && metadata
+                        .length_in_microseconds()
+                        .map_or(true, |length| position as u64 <= length);
+
+                if can_control && self.backend.can_seek() && is_current_track && in_track {
+                    self.backend
+                        .set_position(&track_id, Duration::from_micros_ext(position as u64));
+                    self.seeked = true;
+                }
+            }
+            "OpenUri" => {
+                let uri: &str = call.read1()?;
+                self.backend.open_uri(uri);
+            }
+            other => return Err(MethodErr::no_method(other)),
+        }
+
+        Ok(call.method_return())
+    }
+
+    fn set_property(&mut self, call: &Message) -> Result<(), MethodErr> {
+        let mut args = call.iter_init();
+        let interface: &str = args.read()?;
+        let name: &str = args.read()?;
+
+        let properties = self.properties(interface)?;
+        if !properties.contains_key(name) {
+            return Err(unknown_property(name));
+        }
+
+        let can_control = self.backend.can_control();
+        match (interface, name) {
+            (ROOT_INTERFACE, "Fullscreen") => {
+                let Variant(fullscreen) = args.read::<Variant<bool>>().map_err(invalid_value)?;
+                if self.backend.can_set_fullscreen() {
+                    self.backend.set_fullscreen(fullscreen);
+                }
+            }
+            (PLAYER_INTERFACE, "LoopStatus") => {
+                let Variant(status) = args.read::<Variant<&str>>().map_err(invalid_value)?;
+                let status = status
+                    .parse::<LoopStatus>()
+                    .map_err(|error| MethodErr::invalid_arg(&error.to_string()))?;
+                if can_control {
+                    self.backend.set_loop_status(status);
+                }
+            }
+            (PLAYER_INTERFACE, "Shuffle") => {
+                let Variant(shuffle) = args.read::<Variant<bool>>().map_err(invalid_value)?;
+                if can_control {
+                    self.backend.set_shuffle(shuffle);
+                }
+            }
+            (PLAYER_INTERFACE, "Rate") => {
+                let Variant(rate) = args.read::<Variant<f64>>().map_err(invalid_value)?;
+                let valid = rate != 0.0
+                    && rate >= self.backend.minimum_rate()
+                    && rate <= self.backend.maximum_rate();
+                if can_control && valid {
+                    self.backend.set_rate(rate);
+                }
+            }
+            (PLAYER_INTERFACE, "Volume") => {
+                let Variant(volume) = args.read::<Variant<f64>>().map_err(invalid_value)?;
+                if can_control {
+                    self.backend.set_volume(volume.max(0.0));
+                }
+            }
+            _ => return Err(MethodErr::ro_property(name)),
+        }
+        Ok(())
+    }
+}
+
+/// Optional properties that the backend does not support are reported with `InvalidArgs` instead
+/// of `UnknownProperty`. This is what GDBus does, and what [`Player`](crate::Player) expects.
+fn unknown_property(name: &str) -> MethodErr {
+    (
+        "org.freedesktop.DBus.Error.InvalidArgs",
+        format!("No such property \"{}\"", name),
+    )
+        .into()
+}
+
+fn invalid_value(error: dbus::arg::TypeMismatchError) -> MethodErr {
+    MethodErr::invalid_arg(&error.to_string())
+}
+
+fn root_properties<B: PlayerBackend>(backend: &B) -> Properties {
+    let mut properties = Properties::new();
+    properties.insert("CanQuit", PropertyValue::Bool(backend.can_quit()));
+    properties.insert("CanRaise", PropertyValue::Bool(backend.can_raise()));
+    if let Some(fullscreen) = backend.fullscreen() {
+        properties.insert("Fullscreen", PropertyValue::Bool(fullscreen));
+        properties.insert(
+            "CanSetFullscreen",
+            PropertyValue::Bool(backend.can_set_fullscreen()),
+        );
+    }
+    properties.insert("HasTrackList", PropertyValue::Bool(false));
+    properties.insert("Identity", PropertyValue::String(backend.identity()));
+    if let Some(desktop_entry) = backend.desktop_entry() {
+        properties.insert("DesktopEntry", PropertyValue::String(desktop_entry));
+    }
+    properties.insert(
+        "SupportedUriSchemes",
+        PropertyValue::Strings(backend.supported_uri_schemes()),
+    );
+    properties.insert(
+        "SupportedMimeTypes",
+        PropertyValue::Strings(backend.supported_mime_types()),
+    );
+    properties
+}
+
+fn player_properties<B: PlayerBackend>(backend: &B) -> Properties {
+    let can_control = backend.can_control();
+    let position = DurationExtensions::as_micros(&backend.position()) as i64;
+
+    let mut properties = Properties::new();
+    properties.insert(
+        "PlaybackStatus",
+        PropertyValue::String(backend.playback_status().dbus_value()),
+    );
+    if let Some(loop_status) = backend.loop_status() {
+        properties.insert(
+            "LoopStatus",
+            PropertyValue::String(loop_status.dbus_value()),
+        );
+    }
+    properties.insert("Rate", PropertyValue::Double(backend.rate()));
+    if let Some(shuffle) = backend.shuffle() {
+        properties.insert("Shuffle", PropertyValue::Bool(shuffle));
+    }
+    properties.insert("Metadata", PropertyValue::Metadata(backend.metadata()));
+    properties.insert("Volume", PropertyValue::Double(backend.volume()));
+    properties.insert("Position", PropertyValue::Int64(position));
+    properties.insert("MinimumRate", PropertyValue::Double(backend.minimum_rate()));
+    properties.insert("MaximumRate", PropertyValue::Double(backend.maximum_rate()));
+    properties.insert(
+        "CanGoNext",
+        PropertyValue::Bool(can_control && backend.can_go_next()),
+    );
+    properties.insert(
+        "CanGoPrevious",
+        PropertyValue::Bool(can_control && backend.can_go_previous()),
+    );
+    properties.insert(
+        "CanPlay",
+        PropertyValue::Bool(can_control && backend.can_play()),
+    );
+    properties.insert(
+        "CanPause",
+        PropertyValue::Bool(can_control && backend.can_pause()),
+    );
+    properties.insert(
+        "CanSeek",
+        PropertyValue::Bool(can_control && backend.can_seek()),
+    );
+    properties.insert("CanControl", PropertyValue::Bool(can_control));
+    properties
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{Event, Player};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[derive(Debug)]
+    pub(crate) struct TestBackend {
+        pub(crate) playing: bool,
+        pub(crate) volume: f64,
+        pub(crate) position: Duration,
+        pub(crate) opened_uris: Vec<String>,
+        pub(crate) quit: bool,
+    }
+
+    impl PlayerBackend for TestBackend {
+        fn identity(&self) -> String {
+            String::from("Test Player")
+        }
+
+        fn playback_status(&self) -> PlaybackStatus {
+            if self.playing {
+                PlaybackStatus::Playing
+            } else {
+                PlaybackStatus::Paused
+            }
+        }
+
+        fn supported_uri_schemes(&self) -> Vec<String> {
+            vec![String::from("file")]
+        }
+
+        fn can_quit(&self) -> bool {
+            true
+        }
+
+        fn quit(&mut self) {
+            self.quit = true;
+        }
+
+        fn metadata(&self) -> Metadata {
+            let mut values = std::collections::HashMap::new();
+            values.insert(
+                String::from("mpris:trackid"),
+                crate::MetadataValue::from("/org/example/Track/1"),
+            );
+            values.insert(
+                String::from("xesam:title"),
+                crate::MetadataValue::from("Riverside"),
+            );
+            values.insert(
+                String::from("mpris:length"),
+                crate::MetadataValue::I64(60_000_000),
+            );
+            Metadata::from(values)
+        }
+
+        fn position(&self) -> Duration {
+            self.position
+        }
+
+        fn volume(&self) -> f64 {
+            self.volume
+        }
+
+        fn set_volume(&mut self, volume: f64) {
+            self.volume = volume;
+        }
+
+        fn can_control(&self) -> bool {
+            true
+        }
+
+        fn can_play(&self) -> bool {
+            true
+        }
+
+        fn can_seek(&self) -> bool {
+            true
+        }
+
+        fn play(&mut self) {
+            self.playing = true;
+        }
+
+        fn seek(&mut self, offset_in_microseconds: i64) {
+            let offset = Duration::from_micros_ext(offset_in_microseconds.unsigned_abs());
+            if offset_in_microseconds >= 0 {
+                self.position += offset;
+            } else {
+                self.position = self.position.checked_sub(offset).unwrap_or_default();
+            }
+        }
+
+        fn set_position(&mut self, _track_id: &TrackID, position: Duration) {
+            self.position = position;
+        }
+
+        fn open_uri(&mut self, uri: &str) {
+            self.opened_uris.push(uri.to_string());
+        }
+    }
+
+    /// Runs a server with a [`TestBackend`] on another thread until a client calls `Quit`. Returns
+    /// the server's bus name and a handle that gives back the backend.
+    pub(crate) fn serve(name: &str) -> (String, thread::JoinHandle<TestBackend>) {
+        let name = format!("{}.instance{}", name, std::process::id());
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let backend = TestBackend {
+                playing: false,
+                volume: 1.0,
+                position: Duration::from_secs(0),
+                opened_uris: Vec::new(),
+                quit: false,
+            };
+            let mut server = MprisServer::new(&name, backend).expect("Could not start server");
+            sender.send(server.bus_name().to_string()).unwrap();
+
+            while !server.backend().quit {
+                server.process(50).expect("Could not process calls");
+            }
+            server.backend
+        });
+
+        (receiver.recv().unwrap(), handle)
+    }
+
+    pub(crate) fn connect(bus_name: String) -> Player {
+        let connection = Connection::get_private(BusType::Session)
+            .expect("Could not open a D-Bus session connection");
+        Player::new(connection, bus_name, 500).expect("Could not connect to server")
+    }
+
+    #[test]
+    fn it_serves_properties() {
+        let (bus_name, handle) = serve("mpris_rs_test_properties");
+        let player = connect(bus_name);
+
+        assert_eq!(player.identity(), "Test Player");
+        assert_eq!(
+            player.get_playback_status().unwrap(),
+            PlaybackStatus::Paused
+        );
+        assert_eq!(player.get_metadata().unwrap().title(), Some("Riverside"));
+        assert_eq!(
+            player.get_metadata().unwrap().track_id(),
+            Some(TrackID::new("/org/example/Track/1").unwrap())
+        );
+        assert_eq!(
+            player.get_supported_uri_schemes().unwrap(),
+            vec![String::from("file")]
+        );
+        assert_eq!(player.checked_get_shuffle().unwrap(), None);
+        assert_eq!(player.checked_get_loop_status().unwrap(), None);
+        assert!(player.can_play().unwrap());
+        assert!(!player.can_go_next().unwrap());
+        assert!(!player.supports_track_lists());
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_forwards_commands() {
+        let (bus_name, handle) = serve("mpris_rs_test_commands");
+        let player = connect(bus_name);
+
+        player.play().unwrap();
+        player.set_volume(-0.5).unwrap();
+        player.checked_open_uri("file:///music/a.mp3").unwrap();
+        player
+            .set_position(
+                TrackID::new("/org/example/Track/1").unwrap(),
+                &Duration::from_secs(10),
+            )
+            .unwrap();
+        // Positions outside of the current track are ignored.
+        player
+            .set_position(
+                TrackID::new("/org/example/Track/2").unwrap(),
+                &Duration::from_secs(20),
+            )
+            .unwrap();
+        assert!(player.set_loop_status(LoopStatus::Track).is_err());
+        assert_eq!(
+            player.get_playback_status().unwrap(),
+            PlaybackStatus::Playing
+        );
+
+        player.quit().unwrap();
+        let backend = handle.join().unwrap();
+        assert!(backend.playing);
+        assert_eq!(backend.volume, 0.0);
+        assert_eq!(backend.position, Duration::from_secs(10));
+        assert_eq!(
+            backend.opened_uris,
+            vec![String::from("file:///music/a.mp3")]
+        );
+    }
+
+    #[test]
+    fn it_seeks_backwards() {
+        let (bus_name, handle) = serve("mpris_rs_test_seek");
+        let player = connect(bus_name);
+
+        player.seek_forwards(&Duration::from_secs(10)).unwrap();
+        player.seek_backwards(&Duration::from_secs(3)).unwrap();
+        assert_eq!(player.get_position().unwrap(), Duration::from_secs(7));
+        // Seeking before the start of the track stops at the start.
+        player.seek_backwards(&Duration::from_secs(60)).unwrap();
+        assert_eq!(player.get_position().unwrap(), Duration::from_secs(0));
+
+        player.quit().unwrap();
+        let backend = handle.join().unwrap();
+        assert_eq!(backend.position, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn it_emits_signals() {
+        let (bus_name, handle) = serve("mpris_rs_test_signals");
+        let player = connect(bus_name);
+        let mut events = player.events().unwrap();
+
+        player.play().unwrap();
+        assert!(matches!(events.next(), Some(Ok(Event::Playing))));
+
+        player.seek_forwards(&Duration::from_secs(5)).unwrap();
+        assert!(matches!(
+            events.next(),
+            Some(Ok(Event::Seeked {
+                position_in_us: 5_000_000
+            }))
+        ));
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+}
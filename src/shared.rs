@@ -0,0 +1,14 @@
+//! Thread-safe versions of [`PlayerFinder`](crate::PlayerFinder), [`Player`](crate::Player) and
+//! [`PlayerEvents`](crate::PlayerEvents).
+//!
+//! These types share a single [`SyncConnection`](dbus::blocking::SyncConnection) and a
+//! mutex-guarded event queue, so they are all [`Send`] and [`Sync`].
+
+mod connection;
+mod event;
+mod find;
+mod player;
+
+pub use self::event::SharedPlayerEvents;
+pub use self::find::{SharedPlayerFinder, SharedPlayerIter};
+pub use self::player::SharedPlayer;
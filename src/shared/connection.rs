@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::{Duration, Instant};
+
+use dbus::blocking::{BlockingSender, Proxy, SyncConnection};
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::{MatchRule, MessageType};
+use dbus::Message;
+
+use crate::player::MPRIS2_PATH;
+use crate::pooled_connection::{
+    EventQueue, EventQueueLimit, MprisEvent, MprisMessage, GET_NAME_OWNER_TIMEOUT, MATCH_RULES,
+    NAME_HAS_OWNER_TIMEOUT,
+};
+
+/// How long a thread reads messages from the connection before giving other threads a chance.
+const PROCESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Messages read from the connection that are waiting to be picked up by some thread.
+#[derive(Debug, Default)]
+struct Inbox {
+    /// The pending events of every [`SharedEventSubscription`], keyed by its id. Only players
+    /// with subscribers have their events queued up.
+    subscriptions: HashMap<u64, SubscriberQueue>,
+
+    /// The id of the next [`SharedEventSubscription`].
+    next_subscription_id: u64,
+
+    /// The limit of event queues created from now on.
+    event_queue_limit: EventQueueLimit,
+
+    /// Serials of the method calls that threads are currently waiting on a reply for.
+    awaiting_reply: HashSet<u32>,
+
+    /// Replies to the method calls in `awaiting_reply`, keyed by serial.
+    replies: HashMap<u32, Message>,
+}
+
+impl Inbox {
+    fn receive(&mut self, message: Message) {
+        match message.msg_type() {
+            MessageType::MethodReturn | MessageType::Error => {
+                if let Some(serial) = message.get_reply_serial() {
+                    if self.awaiting_reply.contains(&serial) {
+                        self.replies.insert(serial, message);
+                    }
+                }
+            }
+            _ => {
+                let event = MprisMessage::try_parse(message).and_then(MprisMessage::into_event);
+                if let Some((unique_name, event)) = event {
+                    for subscriber in self.subscriptions.values_mut() {
+                        if subscriber.unique_name == unique_name {
+                            subscriber.events.push(event.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The events of one player queued up for one [`SharedEventSubscription`].
+#[derive(Debug)]
+struct SubscriberQueue {
+    unique_name: String,
+    events: EventQueue,
+}
+
+/// Keeps the events of a player queued up on a [`SharedConnection`] until it is dropped.
+///
+/// Every subscription has its own queue, so each reader sees every event of the player.
+#[derive(Debug)]
+pub(crate) struct SharedEventSubscription {
+    inbox: Arc<Mutex<Inbox>>,
+    id: u64,
+}
+
+impl Drop for SharedEventSubscription {
+    fn drop(&mut self) {
+        lock(&self.inbox).subscriptions.remove(&self.id);
+    }
+}
+
+/// Thread-safe version of [`PooledConnection`](crate::pooled_connection::PooledConnection).
+///
+/// [`SyncConnection`] cannot read messages on one thread while another thread waits for a method
+/// reply, as the reading thread would take the reply for itself. Because of that all method calls
+/// go through [`SharedConnection`] (it is a [`BlockingSender`]), and only one thread at a time
+/// reads messages from the connection. That thread hands out replies and events to the other
+/// threads, which sleep until it is done with a round of reading and then look at the inbox again.
+pub(crate) struct SharedConnection {
+    connection: SyncConnection,
+    inbox: Arc<Mutex<Inbox>>,
+
+    /// Held by the thread that is currently reading messages from the connection.
+    processing: Mutex<()>,
+
+    /// Notified every time a thread is done with a round of reading messages.
+    processed: Condvar,
+}
+
+impl SharedConnection {
+    pub(crate) fn new(connection: SyncConnection) -> Self {
+        for rule in MATCH_RULES {
+            let _ = connection.add_match_no_cb(rule);
+        }
+
+        let inbox = Arc::new(Mutex::new(Inbox::default()));
+        let receiving_inbox = inbox.clone();
+        connection.start_receive(
+            MatchRule::new(),
+            Box::new(move |message, _| {
+                lock(&receiving_inbox).receive(message);
+                true
+            }),
+        );
+
+        SharedConnection {
+            connection,
+            inbox,
+            processing: Mutex::new(()),
+            processed: Condvar::new(),
+        }
+    }
+
+    pub(crate) fn with_path<'a>(
+        &'a self,
+        bus_name: &'a str,
+        timeout: Duration,
+    ) -> Proxy<'a, &'a Self> {
+        Proxy::new(bus_name, MPRIS2_PATH, timeout, self)
+    }
+
+    pub(crate) fn bus_proxy(&self, timeout: Duration) -> Proxy<'_, &Self> {
+        Proxy::new("org.freedesktop.DBus", "/", timeout, self)
+    }
+
+    pub(crate) fn determine_unique_name(&self, bus_name: &str) -> Option<String> {
+        self.bus_proxy(Duration::from_millis(GET_NAME_OWNER_TIMEOUT as u64))
+            .method_call("org.freedesktop.DBus", "GetNameOwner", (bus_name,))
+            .ok()
+            .map(|(unique_name,)| unique_name)
+    }
+
+    pub(crate) fn name_has_owner(&self, bus_name: &str) -> Option<bool> {
+        self.bus_proxy(Duration::from_millis(NAME_HAS_OWNER_TIMEOUT as u64))
+            .method_call("org.freedesktop.DBus", "NameHasOwner", (bus_name,))
+            .ok()
+            .map(|(has_owner,)| has_owner)
+    }
+
+    /// Returns the limit of the event queues of new subscriptions.
+    pub(crate) fn event_queue_limit(&self) -> EventQueueLimit {
+        lock(&self.inbox).event_queue_limit
+    }
+
+    /// Sets the limit of the event queues of subscriptions created from now on.
+    pub(crate) fn set_event_queue_limit(&self, limit: EventQueueLimit) {
+        lock(&self.inbox).event_queue_limit = limit;
+    }
+
+    /// Starts queuing up the events of the player with the given unique name, until the returned
+    /// subscription is dropped.
+    pub(crate) fn subscribe_events(&self, unique_name: &str) -> SharedEventSubscription {
+        let mut inbox = lock(&self.inbox);
+        let id = inbox.next_subscription_id;
+        inbox.next_subscription_id += 1;
+        let events = EventQueue::new(inbox.event_queue_limit);
+        inbox.subscriptions.insert(
+            id,
+            SubscriberQueue {
+                unique_name: unique_name.to_string(),
+                events,
+            },
+        );
+        SharedEventSubscription {
+            inbox: self.inbox.clone(),
+            id,
+        }
+    }
+
+    /// Removes all pending events from a subscription's queue and returns them.
+    pub(crate) fn pending_events(&self, subscription: &SharedEventSubscription) -> Vec<MprisEvent> {
+        lock(&self.inbox)
+            .subscriptions
+            .get_mut(&subscription.id)
+            .map(|subscriber| subscriber.events.take())
+            .unwrap_or_default()
+    }
+
+    /// Process events in a blocking fashion until the given subscription has pending events.
+    pub(crate) fn process_events_blocking_until_received(
+        &self,
+        subscription: &SharedEventSubscription,
+    ) {
+        let inbox = lock(&self.inbox);
+        self.wait_for(inbox, None, |inbox| {
+            match inbox.subscriptions.get(&subscription.id) {
+                Some(subscriber) if subscriber.events.is_empty() => None,
+                _ => Some(()),
+            }
+        });
+    }
+
+    /// Blocks until `check` finds what it is looking for in the inbox, or until the timeout
+    /// expires.
+    ///
+    /// If no other thread is reading messages from the connection, this thread does it.
+    /// Otherwise it waits for the reading thread to finish its round before checking again.
+    fn wait_for<'a, T, F>(
+        &'a self,
+        mut inbox: MutexGuard<'a, Inbox>,
+        timeout: Option<Duration>,
+        mut check: F,
+    ) -> Option<T>
+    where
+        F: FnMut(&mut Inbox) -> Option<T>,
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if let Some(found) = check(&mut inbox) {
+                return Some(found);
+            }
+
+            let interval = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    (deadline - now).min(PROCESS_INTERVAL)
+                }
+                None => PROCESS_INTERVAL,
+            };
+
+            let processing = match self.processing.try_lock() {
+                Ok(guard) => Some(guard),
+                Err(TryLockError::Poisoned(error)) => Some(error.into_inner()),
+                Err(TryLockError::WouldBlock) => None,
+            };
+
+            match processing {
+                Some(processing) => {
+                    // The message callback needs the inbox while reading.
+                    drop(inbox);
+                    let _ = self.connection.process(interval);
+                    // Stop processing while holding the inbox, so that no waiting thread can miss
+                    // the notification below.
+                    inbox = lock(&self.inbox);
+                    drop(processing);
+                    self.processed.notify_all();
+                }
+                None => {
+                    inbox = self
+                        .processed
+                        .wait_timeout(inbox, interval)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+            }
+        }
+    }
+}
+
+impl BlockingSender for SharedConnection {
+    fn send_with_reply_and_block(
+        &self,
+        message: Message,
+        timeout: Duration,
+    ) -> Result<Message, dbus::Error> {
+        // Hold the inbox while sending so that the reply cannot be read before the serial is
+        // registered.
+        let mut inbox = lock(&self.inbox);
+        let serial = self
+            .connection
+            .send(message)
+            .map_err(|_| dbus::Error::new_failed("Failed to send message"))?;
+        inbox.awaiting_reply.insert(serial);
+
+        // If another thread is blocked reading messages, libdbus only writes the message once
+        // that thread's round is over. Don't count that against the timeout.
+        let timeout = timeout + PROCESS_INTERVAL;
+        let reply = self.wait_for(inbox, Some(timeout), |inbox| inbox.replies.remove(&serial));
+        let mut inbox = lock(&self.inbox);
+        inbox.awaiting_reply.remove(&serial);
+        // A reply that came in after the timeout would never be picked up.
+        inbox.replies.remove(&serial);
+        drop(inbox);
+
+        match reply {
+            Some(mut reply) => {
+                reply.as_result()?;
+                Ok(reply)
+            }
+            None => Err(dbus::Error::new_custom(
+                "org.freedesktop.DBus.Error.NoReply",
+                "Did not receive a reply in time",
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for SharedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedConnection")
+            .field("inbox", &self.inbox)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Locks the inbox, ignoring poisoning. A panic on another thread doesn't leave the inbox in an
+/// inconsistent state, and panicking inside a library like this is a bigger evil.
+fn lock(inbox: &Mutex<Inbox>) -> MutexGuard<'_, Inbox> {
+    inbox.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,56 @@
+use super::connection::SharedEventSubscription;
+use super::SharedPlayer;
+use crate::event::EventState;
+use crate::{DBusError, Event, EventError, TrackList};
+
+/// Thread-safe version of [`PlayerEvents`](crate::PlayerEvents): an iterator that blocks forever
+/// until a [`SharedPlayer`] has an [`Event`].
+///
+/// Unlike [`PlayerEvents`](crate::PlayerEvents) it owns its (cheap) clone of the player, so it can
+/// be moved to another thread. Several threads can iterate over the events of different players
+/// from the same [`SharedPlayerFinder`](super::SharedPlayerFinder) at the same time.
+///
+/// Iteration will stop if player stops running. If the player was running before this iterator
+/// blocks, one last [`Event::PlayerShutDown`] event will be emitted before stopping iteration.
+#[derive(Debug)]
+pub struct SharedPlayerEvents {
+    /// [`SharedPlayer`] to watch.
+    player: SharedPlayer,
+
+    /// State used to find events.
+    state: EventState,
+
+    /// Keeps the events of the player queued up.
+    events: SharedEventSubscription,
+}
+
+impl SharedPlayerEvents {
+    pub(crate) fn new(player: SharedPlayer) -> Result<SharedPlayerEvents, DBusError> {
+        // Subscribe before reading the state, so no change is missed.
+        let events = player.subscribe_events();
+        let state = EventState::new(&player)?;
+        Ok(SharedPlayerEvents {
+            player,
+            state,
+            events,
+        })
+    }
+
+    /// The player that events are read from.
+    pub fn player(&self) -> &SharedPlayer {
+        &self.player
+    }
+
+    /// Current tracklist of the player. Will be kept up to date.
+    pub fn track_list(&self) -> Option<&TrackList> {
+        self.state.track_list()
+    }
+}
+
+impl Iterator for SharedPlayerEvents {
+    type Item = Result<Event, EventError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.state.next(&self.player, &self.events)
+    }
+}
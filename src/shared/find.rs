@@ -0,0 +1,188 @@
+use std::iter::FusedIterator;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::blocking::SyncConnection;
+
+use super::connection::SharedConnection;
+use super::SharedPlayer;
+use crate::find::{find_active_player, FindingError, LIST_NAMES_TIMEOUT_MS};
+use crate::player::{DEFAULT_TIMEOUT_MS, MPRIS2_PREFIX};
+use crate::{DBusError, OverflowPolicy};
+
+/// Thread-safe version of [`PlayerFinder`](crate::PlayerFinder).
+///
+/// All found [`SharedPlayer`]s share the D-Bus connection of the finder, no matter which thread
+/// they end up on. The finder itself can be cloned and shared between threads as well.
+#[derive(Debug, Clone)]
+pub struct SharedPlayerFinder {
+    connection: Arc<SharedConnection>,
+    player_timeout_ms: i32,
+}
+
+impl SharedPlayerFinder {
+    /// Creates a new [`SharedPlayerFinder`] connected to the session bus.
+    pub fn new() -> Result<Self, DBusError> {
+        Ok(SharedPlayerFinder::for_connection(
+            SyncConnection::new_session()?,
+        ))
+    }
+
+    /// Create a new [`SharedPlayerFinder`] with the given connection.
+    pub fn for_connection(connection: SyncConnection) -> Self {
+        SharedPlayerFinder {
+            connection: Arc::new(SharedConnection::new(connection)),
+            player_timeout_ms: DEFAULT_TIMEOUT_MS,
+        }
+    }
+
+    /// Get the D-Bus communication timeout used for found [`SharedPlayer`]s.
+    pub fn player_timeout_ms(&self) -> i32 {
+        self.player_timeout_ms
+    }
+
+    /// Set the D-Bus communication timeout used for found [`SharedPlayer`]s.
+    pub fn set_player_timeout_ms(&mut self, timeout_ms: i32) {
+        self.player_timeout_ms = timeout_ms;
+    }
+
+    /// Get the number of events that a single reader, like a [`SharedPlayerEvents`](super::SharedPlayerEvents) iterator, can queue up for a
+    /// player before the [`OverflowPolicy`] applies.
+    ///
+    /// Can be set with [`set_event_queue_capacity`][Self::set_event_queue_capacity]
+    pub fn event_queue_capacity(&self) -> usize {
+        self.connection.event_queue_limit().capacity
+    }
+
+    /// Set the number of events that a single reader can queue up for a player. Only readers
+    /// created afterwards are affected. The default is 1024.
+    ///
+    /// Events are only queued up for players that somebody reads the events of. A capacity of 0 is
+    /// treated as 1.
+    pub fn set_event_queue_capacity(&mut self, capacity: usize) {
+        let mut limit = self.connection.event_queue_limit();
+        limit.capacity = capacity;
+        self.connection.set_event_queue_limit(limit);
+    }
+
+    /// Get what happens when a reader does not keep up with the events of a player.
+    ///
+    /// Can be set with [`set_event_overflow_policy`][Self::set_event_overflow_policy]
+    pub fn event_overflow_policy(&self) -> OverflowPolicy {
+        self.connection.event_queue_limit().overflow
+    }
+
+    /// Set what happens when a reader does not keep up with the events of a player. Only readers
+    /// created afterwards are affected.
+    pub fn set_event_overflow_policy(&mut self, overflow: OverflowPolicy) {
+        let mut limit = self.connection.event_queue_limit();
+        limit.overflow = overflow;
+        self.connection.set_event_queue_limit(limit);
+    }
+
+    /// Find all available [`SharedPlayer`]s in the connection.
+    ///
+    /// See [`PlayerFinder::find_all`](crate::PlayerFinder::find_all).
+    pub fn find_all(&self) -> Result<Vec<SharedPlayer>, FindingError> {
+        self.iter_players()?
+            .map(|x| x.map_err(FindingError::from))
+            .collect()
+    }
+
+    /// Try to find the first available [`SharedPlayer`] in the connection.
+    ///
+    /// See [`PlayerFinder::find_first`](crate::PlayerFinder::find_first).
+    pub fn find_first(&self) -> Result<SharedPlayer, FindingError> {
+        if let Some(player) = self.iter_players()?.next() {
+            player.map_err(FindingError::from)
+        } else {
+            Err(FindingError::NoPlayerFound)
+        }
+    }
+
+    /// Try to find the "active" [`SharedPlayer`] in the connection.
+    ///
+    /// See [`PlayerFinder::find_active`](crate::PlayerFinder::find_active).
+    pub fn find_active(&self) -> Result<SharedPlayer, FindingError> {
+        let players = self.iter_players()?;
+
+        match find_active_player(
+            players,
+            SharedPlayer::get_playback_status,
+            SharedPlayer::get_metadata,
+        )? {
+            Some(player) => Ok(player),
+            None => Err(FindingError::NoPlayerFound),
+        }
+    }
+
+    /// Try to find a [`SharedPlayer`] by its MPRIS `Identity`, compared case-insensitively.
+    ///
+    /// See [`PlayerFinder::find_by_name`](crate::PlayerFinder::find_by_name).
+    pub fn find_by_name(&self, name: &str) -> Result<SharedPlayer, FindingError> {
+        for player_result in self.iter_players()? {
+            let player = player_result?;
+            if player.identity().to_lowercase() == name.to_lowercase() {
+                return Ok(player);
+            }
+        }
+        Err(FindingError::NoPlayerFound)
+    }
+
+    /// Returns a [`SharedPlayerIter`] that lazily iterates over all of the found players.
+    ///
+    /// See [`PlayerFinder::iter_players`](crate::PlayerFinder::iter_players).
+    pub fn iter_players(&self) -> Result<SharedPlayerIter, DBusError> {
+        let buses = self.all_player_buses()?;
+        Ok(SharedPlayerIter {
+            buses: buses.into_iter(),
+            connection: self.connection.clone(),
+            timeout_ms: self.player_timeout_ms,
+        })
+    }
+
+    fn all_player_buses(&self) -> Result<Vec<String>, DBusError> {
+        let (names,): (Vec<String>,) = self
+            .connection
+            .bus_proxy(Duration::from_millis(LIST_NAMES_TIMEOUT_MS as u64))
+            .method_call("org.freedesktop.DBus", "ListNames", ())?;
+
+        let mut all_busses = names
+            .into_iter()
+            .filter(|name| name.starts_with(MPRIS2_PREFIX))
+            .collect::<Vec<String>>();
+        all_busses.sort_by_key(|a| a.to_lowercase());
+        Ok(all_busses)
+    }
+}
+
+/// Thread-safe version of [`PlayerIter`](crate::PlayerIter), created by calling
+/// [`SharedPlayerFinder::iter_players`].
+#[derive(Debug)]
+pub struct SharedPlayerIter {
+    buses: std::vec::IntoIter<String>,
+    connection: Arc<SharedConnection>,
+    timeout_ms: i32,
+}
+
+impl Iterator for SharedPlayerIter {
+    type Item = Result<SharedPlayer, DBusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bus = self.buses.next()?;
+        Some(SharedPlayer::for_shared_connection(
+            self.connection.clone(),
+            bus,
+            self.timeout_ms,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.buses.len();
+        (size, Some(size))
+    }
+}
+
+impl ExactSizeIterator for SharedPlayerIter {}
+
+impl FusedIterator for SharedPlayerIter {}
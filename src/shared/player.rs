@@ -0,0 +1,1033 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dbus::arg::{Append, AppendAll, Arg, Get, ReadAll};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::{Proxy, SyncConnection};
+use dbus::strings::Path;
+
+use super::connection::{SharedConnection, SharedEventSubscription};
+use super::SharedPlayerEvents;
+use crate::event::{EventSource, PlaylistsState};
+use crate::extensions::DurationExtensions;
+use crate::metadata::Value;
+use crate::player::{handle_optional_property, MPRIS2_PREFIX};
+use crate::pooled_connection::MprisEvent;
+use crate::uri::{check_uri, OpenUriError};
+use crate::{
+    Capabilities, DBusError, EventError, LoopStatus, Metadata, PlaybackStatus, Playlist,
+    PlaylistOrdering, Progress, TrackID, TrackList,
+};
+
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_LIST_INTERFACE: &str = "org.mpris.MediaPlayer2.TrackList";
+const PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+
+/// Thread-safe version of [`Player`](crate::Player).
+///
+/// A [`SharedPlayer`] is [`Send`] and [`Sync`], so it can be handed to other threads or be kept in
+/// shared application state. It is also cheap to clone; all clones, and all other players found
+/// by the same [`SharedPlayerFinder`](super::SharedPlayerFinder), share one D-Bus connection.
+///
+/// The methods behave exactly like their namesakes on [`Player`](crate::Player), so see the
+/// documentation over there for the details.
+#[derive(Clone)]
+pub struct SharedPlayer {
+    connection: Arc<SharedConnection>,
+    bus_name: String,
+    unique_name: String,
+    identity: String,
+    timeout_ms: i32,
+    has_tracklist_interface: bool,
+    has_playlists_interface: bool,
+}
+
+impl SharedPlayer {
+    /// Create a new [`SharedPlayer`] using a D-Bus connection and an address.
+    ///
+    /// Most likely you want to use [`SharedPlayerFinder`](super::SharedPlayerFinder) instead.
+    pub fn new(
+        connection: SyncConnection,
+        bus_name: String,
+        timeout_ms: i32,
+    ) -> Result<SharedPlayer, DBusError> {
+        SharedPlayer::for_shared_connection(
+            Arc::new(SharedConnection::new(connection)),
+            bus_name,
+            timeout_ms,
+        )
+    }
+
+    pub(crate) fn for_shared_connection(
+        connection: Arc<SharedConnection>,
+        bus_name: String,
+        timeout_ms: i32,
+    ) -> Result<SharedPlayer, DBusError> {
+        let mut player = SharedPlayer {
+            connection,
+            bus_name,
+            unique_name: String::new(),
+            identity: String::new(),
+            timeout_ms,
+            has_tracklist_interface: false,
+            has_playlists_interface: false,
+        };
+
+        player.identity = player.get(ROOT_INTERFACE, "Identity")?;
+
+        player.unique_name = player
+            .connection
+            .determine_unique_name(&player.bus_name)
+            .ok_or_else(|| {
+                DBusError::Miscellaneous(String::from(
+                    "Could not determine player's unique name. Did it exit during initialization?",
+                ))
+            })?;
+
+        // See `introspect` in the `player` module for why this is a substring search.
+        let interfaces: Result<(String,), _> =
+            player.call("org.freedesktop.DBus.Introspectable", "Introspect", ());
+        let interfaces = interfaces.map(|(xml,)| xml).unwrap_or_default();
+        player.has_tracklist_interface = interfaces.contains(TRACK_LIST_INTERFACE);
+        player.has_playlists_interface = interfaces.contains(PLAYLISTS_INTERFACE);
+
+        Ok(player)
+    }
+
+    /// Returns the current D-Bus communication timeout (in milliseconds).
+    pub fn dbus_timeout_ms(&self) -> i32 {
+        self.timeout_ms
+    }
+
+    /// Change the D-Bus communication timeout.
+    pub fn set_dbus_timeout_ms(&mut self, timeout_ms: i32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Returns the player's D-Bus bus name.
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// Returns the player's bus name without the MPRIS2 prefix.
+    ///
+    /// See [`Player::bus_name_trimmed`](crate::Player::bus_name_trimmed).
+    pub fn bus_name_trimmed(&self) -> &str {
+        self.bus_name().trim_start_matches(MPRIS2_PREFIX)
+    }
+
+    /// Returns the player's unique D-Bus bus name (usually something like `:1.1337`).
+    pub fn unique_name(&self) -> &str {
+        &self.unique_name
+    }
+
+    /// Returns the player's MPRIS `Identity`.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Returns [`true`] if the player implements the `org.mpris.MediaPlayer2.TrackList`
+    /// interface.
+    pub fn supports_track_lists(&self) -> bool {
+        self.has_tracklist_interface
+    }
+
+    /// Returns [`true`] if the player implements the `org.mpris.MediaPlayer2.Playlists`
+    /// interface.
+    pub fn supports_playlists(&self) -> bool {
+        self.has_playlists_interface
+    }
+
+    /// Returns an iterator that blocks until the player has an [`Event`](crate::Event).
+    ///
+    /// See [`SharedPlayerEvents`].
+    pub fn events(&self) -> Result<SharedPlayerEvents, DBusError> {
+        SharedPlayerEvents::new(self.clone())
+    }
+
+    /// Checks if the player is still connected.
+    pub fn is_running(&self) -> bool {
+        self.connection
+            .name_has_owner(&self.bus_name)
+            .unwrap_or(false)
+    }
+
+    /// See [`Player::get_capabilities`](crate::Player::get_capabilities).
+    pub fn get_capabilities(&self) -> Result<Capabilities, DBusError> {
+        let player = self.get_all_properties(PLAYER_INTERFACE)?;
+        Ok(self.get_root_capabilities()? | Capabilities::from_player_properties(&player))
+    }
+
+    //
+    // org.mpris.MediaPlayer2
+    //
+
+    /// See [`Player::get_desktop_entry`](crate::Player::get_desktop_entry).
+    pub fn get_desktop_entry(&self) -> Result<Option<String>, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "DesktopEntry")
+    }
+
+    /// See [`Player::get_supported_mime_types`](crate::Player::get_supported_mime_types).
+    pub fn get_supported_mime_types(&self) -> Result<Vec<String>, DBusError> {
+        self.get(ROOT_INTERFACE, "SupportedMimeTypes")
+    }
+
+    /// See [`Player::get_supported_uri_schemes`](crate::Player::get_supported_uri_schemes).
+    pub fn get_supported_uri_schemes(&self) -> Result<Vec<String>, DBusError> {
+        self.get(ROOT_INTERFACE, "SupportedUriSchemes")
+    }
+
+    /// See [`Player::get_has_track_list`](crate::Player::get_has_track_list).
+    pub fn get_has_track_list(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "HasTrackList")
+    }
+
+    /// See [`Player::can_raise`](crate::Player::can_raise).
+    pub fn can_raise(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "CanRaise")
+    }
+
+    /// See [`Player::can_quit`](crate::Player::can_quit).
+    pub fn can_quit(&self) -> Result<bool, DBusError> {
+        self.get(ROOT_INTERFACE, "CanQuit")
+    }
+
+    /// See [`Player::can_set_fullscreen`](crate::Player::can_set_fullscreen).
+    pub fn can_set_fullscreen(&self) -> Result<bool, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "CanSetFullscreen")
+            .map(|o| o.unwrap_or(false))
+    }
+
+    /// See [`Player::get_fullscreen`](crate::Player::get_fullscreen).
+    pub fn get_fullscreen(&self) -> Result<Option<bool>, DBusError> {
+        self.get_optional(ROOT_INTERFACE, "Fullscreen")
+    }
+
+    /// See [`Player::set_fullscreen`](crate::Player::set_fullscreen).
+    pub fn set_fullscreen(&self, new_state: bool) -> Result<bool, DBusError> {
+        handle_optional_property(self.set(ROOT_INTERFACE, "Fullscreen", new_state))
+            .map(|o| o.is_some())
+    }
+
+    /// See [`Player::raise`](crate::Player::raise).
+    pub fn raise(&self) -> Result<(), DBusError> {
+        self.call(ROOT_INTERFACE, "Raise", ())
+    }
+
+    /// See [`Player::checked_raise`](crate::Player::checked_raise).
+    pub fn checked_raise(&self) -> Result<bool, DBusError> {
+        if self.can_raise()? {
+            self.raise().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::quit`](crate::Player::quit).
+    pub fn quit(&self) -> Result<(), DBusError> {
+        self.call(ROOT_INTERFACE, "Quit", ())
+    }
+
+    /// See [`Player::checked_quit`](crate::Player::checked_quit).
+    pub fn checked_quit(&self) -> Result<bool, DBusError> {
+        if self.can_quit()? {
+            self.quit().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    //
+    // org.mpris.MediaPlayer2.Player
+    //
+
+    /// See [`Player::get_playback_status`](crate::Player::get_playback_status).
+    pub fn get_playback_status(&self) -> Result<PlaybackStatus, DBusError> {
+        self.get::<String>(PLAYER_INTERFACE, "PlaybackStatus")?
+            .parse()
+            .map_err(DBusError::from)
+    }
+
+    /// See [`Player::get_metadata`](crate::Player::get_metadata).
+    pub fn get_metadata(&self) -> Result<Metadata, DBusError> {
+        self.get::<HashMap<String, Value>>(PLAYER_INTERFACE, "Metadata")
+            .map(Metadata::from)
+    }
+
+    /// See [`Player::get_position`](crate::Player::get_position).
+    pub fn get_position(&self) -> Result<Duration, DBusError> {
+        self.get_position_in_microseconds()
+            .map(Duration::from_micros_ext)
+    }
+
+    /// See [`Player::checked_get_position`](crate::Player::checked_get_position).
+    pub fn checked_get_position(&self) -> Result<Option<Duration>, DBusError> {
+        if self.has_position()? {
+            Ok(Some(self.get_position()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::get_position_in_microseconds`](crate::Player::get_position_in_microseconds).
+    pub fn get_position_in_microseconds(&self) -> Result<u64, DBusError> {
+        self.get::<i64>(PLAYER_INTERFACE, "Position")
+            .map(|p| p as u64)
+    }
+
+    /// See [`Player::set_position`](crate::Player::set_position).
+    pub fn set_position(&self, track_id: TrackID, position: &Duration) -> Result<(), DBusError> {
+        self.set_position_in_microseconds(track_id, DurationExtensions::as_micros(position))
+    }
+
+    /// See [`Player::checked_set_position`](crate::Player::checked_set_position).
+    pub fn checked_set_position(
+        &self,
+        track_id: TrackID,
+        position: &Duration,
+    ) -> Result<bool, DBusError> {
+        if self.can_control()? && self.has_position()? {
+            self.set_position(track_id, position).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::set_position_in_microseconds`](crate::Player::set_position_in_microseconds).
+    pub fn set_position_in_microseconds(
+        &self,
+        track_id: TrackID,
+        position_in_us: u64,
+    ) -> Result<(), DBusError> {
+        self.call(
+            PLAYER_INTERFACE,
+            "SetPosition",
+            (track_id.as_path(), position_in_us as i64),
+        )
+    }
+
+    /// See [`Player::get_playback_rate`](crate::Player::get_playback_rate).
+    pub fn get_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "Rate")
+    }
+
+    /// See [`Player::checked_get_playback_rate`](crate::Player::checked_get_playback_rate).
+    pub fn checked_get_playback_rate(&self) -> Result<Option<f64>, DBusError> {
+        if self.has_playback_rate()? {
+            Ok(Some(self.get_playback_rate()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_playback_rate`](crate::Player::set_playback_rate).
+    pub fn set_playback_rate(&self, rate: f64) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Rate", rate)
+    }
+
+    /// See [`Player::checked_set_playback_rate`](crate::Player::checked_set_playback_rate).
+    pub fn checked_set_playback_rate(&self, rate: f64) -> Result<bool, DBusError> {
+        if self.can_control()? && self.has_playback_rate()? {
+            self.set_playback_rate(rate).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_minimum_playback_rate`](crate::Player::get_minimum_playback_rate).
+    pub fn get_minimum_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "MinimumRate")
+    }
+
+    /// See [`Player::get_maximum_playback_rate`](crate::Player::get_maximum_playback_rate).
+    pub fn get_maximum_playback_rate(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "MaximumRate")
+    }
+
+    /// See [`Player::get_valid_playback_rate_range`](crate::Player::get_valid_playback_rate_range).
+    pub fn get_valid_playback_rate_range(&self) -> Result<Range<f64>, DBusError> {
+        let min = self.get_minimum_playback_rate()?;
+        let max = self.get_maximum_playback_rate()?;
+        Ok(min..max)
+    }
+
+    /// See [`Player::get_shuffle`](crate::Player::get_shuffle).
+    pub fn get_shuffle(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "Shuffle")
+    }
+
+    /// See [`Player::checked_get_shuffle`](crate::Player::checked_get_shuffle).
+    pub fn checked_get_shuffle(&self) -> Result<Option<bool>, DBusError> {
+        if self.can_shuffle()? {
+            Ok(Some(self.get_shuffle()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_shuffle`](crate::Player::set_shuffle).
+    pub fn set_shuffle(&self, state: bool) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Shuffle", state)
+    }
+
+    /// See [`Player::checked_set_shuffle`](crate::Player::checked_set_shuffle).
+    pub fn checked_set_shuffle(&self, state: bool) -> Result<bool, DBusError> {
+        if self.can_control()? && self.can_shuffle()? {
+            self.set_shuffle(state).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_loop_status`](crate::Player::get_loop_status).
+    pub fn get_loop_status(&self) -> Result<LoopStatus, DBusError> {
+        self.get::<String>(PLAYER_INTERFACE, "LoopStatus")?
+            .parse()
+            .map_err(DBusError::from)
+    }
+
+    /// See [`Player::checked_get_loop_status`](crate::Player::checked_get_loop_status).
+    pub fn checked_get_loop_status(&self) -> Result<Option<LoopStatus>, DBusError> {
+        if self.can_loop()? {
+            Ok(Some(self.get_loop_status()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_loop_status`](crate::Player::set_loop_status).
+    pub fn set_loop_status(&self, status: LoopStatus) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "LoopStatus", status.dbus_value())
+    }
+
+    /// See [`Player::checked_set_loop_status`](crate::Player::checked_set_loop_status).
+    pub fn checked_set_loop_status(&self, status: LoopStatus) -> Result<bool, DBusError> {
+        if self.can_control()? && self.can_loop()? {
+            self.set_loop_status(status).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::get_volume`](crate::Player::get_volume).
+    pub fn get_volume(&self) -> Result<f64, DBusError> {
+        self.get(PLAYER_INTERFACE, "Volume")
+    }
+
+    /// See [`Player::checked_get_volume`](crate::Player::checked_get_volume).
+    pub fn checked_get_volume(&self) -> Result<Option<f64>, DBusError> {
+        if self.has_volume()? {
+            Ok(Some(self.get_volume()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::set_volume`](crate::Player::set_volume).
+    pub fn set_volume(&self, value: f64) -> Result<(), DBusError> {
+        self.set(PLAYER_INTERFACE, "Volume", value.max(0.0))
+    }
+
+    /// See [`Player::checked_set_volume`](crate::Player::checked_set_volume).
+    pub fn checked_set_volume(&self, volume: f64) -> Result<bool, DBusError> {
+        if self.can_control()? && self.has_volume()? {
+            self.set_volume(volume).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::can_control`](crate::Player::can_control).
+    pub fn can_control(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanControl")
+    }
+
+    /// See [`Player::can_go_next`](crate::Player::can_go_next).
+    pub fn can_go_next(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanGoNext")
+    }
+
+    /// See [`Player::can_go_previous`](crate::Player::can_go_previous).
+    pub fn can_go_previous(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanGoPrevious")
+    }
+
+    /// See [`Player::can_pause`](crate::Player::can_pause).
+    pub fn can_pause(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanPause")
+    }
+
+    /// See [`Player::can_play`](crate::Player::can_play).
+    pub fn can_play(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanPlay")
+    }
+
+    /// See [`Player::can_seek`](crate::Player::can_seek).
+    pub fn can_seek(&self) -> Result<bool, DBusError> {
+        self.get(PLAYER_INTERFACE, "CanSeek")
+    }
+
+    /// See [`Player::can_stop`](crate::Player::can_stop).
+    pub fn can_stop(&self) -> Result<bool, DBusError> {
+        self.can_control()
+    }
+
+    /// See [`Player::can_set_playback_rate`](crate::Player::can_set_playback_rate).
+    pub fn can_set_playback_rate(&self) -> Result<bool, DBusError> {
+        self.get_valid_playback_rate_range()
+            .map(|range| range.start < 1.0 || range.end > 1.0)
+    }
+
+    /// See [`Player::can_shuffle`](crate::Player::can_shuffle).
+    pub fn can_shuffle(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Shuffle")
+    }
+
+    /// See [`Player::can_loop`](crate::Player::can_loop).
+    pub fn can_loop(&self) -> Result<bool, DBusError> {
+        self.has_player_property("LoopStatus")
+    }
+
+    /// See [`Player::has_playback_rate`](crate::Player::has_playback_rate).
+    pub fn has_playback_rate(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Rate")
+    }
+
+    /// See [`Player::has_position`](crate::Player::has_position).
+    pub fn has_position(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Position")
+    }
+
+    /// See [`Player::has_volume`](crate::Player::has_volume).
+    pub fn has_volume(&self) -> Result<bool, DBusError> {
+        self.has_player_property("Volume")
+    }
+
+    /// See [`Player::play_pause`](crate::Player::play_pause).
+    pub fn play_pause(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "PlayPause", ())
+    }
+
+    /// See [`Player::checked_play_pause`](crate::Player::checked_play_pause).
+    pub fn checked_play_pause(&self) -> Result<bool, DBusError> {
+        if self.can_pause()? {
+            self.play_pause().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::play`](crate::Player::play).
+    pub fn play(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Play", ())
+    }
+
+    /// See [`Player::checked_play`](crate::Player::checked_play).
+    pub fn checked_play(&self) -> Result<bool, DBusError> {
+        if self.can_play()? {
+            self.play().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::pause`](crate::Player::pause).
+    pub fn pause(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Pause", ())
+    }
+
+    /// See [`Player::checked_pause`](crate::Player::checked_pause).
+    pub fn checked_pause(&self) -> Result<bool, DBusError> {
+        if self.can_pause()? {
+            self.pause().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::stop`](crate::Player::stop).
+    pub fn stop(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Stop", ())
+    }
+
+    /// See [`Player::checked_stop`](crate::Player::checked_stop).
+    pub fn checked_stop(&self) -> Result<bool, DBusError> {
+        if self.can_stop()? {
+            self.stop().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::next`](crate::Player::next).
+    pub fn next(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Next", ())
+    }
+
+    /// See [`Player::checked_next`](crate::Player::checked_next).
+    pub fn checked_next(&self) -> Result<bool, DBusError> {
+        if self.can_go_next()? {
+            self.next().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::previous`](crate::Player::previous).
+    pub fn previous(&self) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Previous", ())
+    }
+
+    /// See [`Player::checked_previous`](crate::Player::checked_previous).
+    pub fn checked_previous(&self) -> Result<bool, DBusError> {
+        if self.can_go_previous()? {
+            self.previous().map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek`](crate::Player::seek).
+    pub fn seek(&self, offset_in_microseconds: i64) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "Seek", (offset_in_microseconds,))
+    }
+
+    /// See [`Player::checked_seek`](crate::Player::checked_seek).
+    pub fn checked_seek(&self, offset_in_microseconds: i64) -> Result<bool, DBusError> {
+        if self.can_seek()? {
+            self.seek(offset_in_microseconds).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek_forwards`](crate::Player::seek_forwards).
+    pub fn seek_forwards(&self, offset: &Duration) -> Result<(), DBusError> {
+        self.seek(DurationExtensions::as_micros(offset) as i64)
+    }
+
+    /// See [`Player::checked_seek_forwards`](crate::Player::checked_seek_forwards).
+    pub fn checked_seek_forwards(&self, offset: &Duration) -> Result<bool, DBusError> {
+        if self.can_seek()? {
+            self.seek_forwards(offset).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::seek_backwards`](crate::Player::seek_backwards).
+    pub fn seek_backwards(&self, offset: &Duration) -> Result<(), DBusError> {
+        self.seek(-(DurationExtensions::as_micros(offset) as i64))
+    }
+
+    /// See [`Player::checked_seek_backwards`](crate::Player::checked_seek_backwards).
+    pub fn checked_seek_backwards(&self, offset: &Duration) -> Result<bool, DBusError> {
+        if self.can_seek()? {
+            self.seek_backwards(offset).map(|_| true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// See [`Player::open_uri`](crate::Player::open_uri).
+    pub fn open_uri(&self, uri: &str) -> Result<(), DBusError> {
+        self.call(PLAYER_INTERFACE, "OpenUri", (uri,))
+    }
+
+    /// See [`Player::checked_open_uri`](crate::Player::checked_open_uri).
+    pub fn checked_open_uri(&self, uri: &str) -> Result<(), OpenUriError> {
+        check_uri(
+            uri,
+            &self.get_supported_uri_schemes()?,
+            &self.get_supported_mime_types()?,
+        )?;
+        self.open_uri(uri).map_err(OpenUriError::from)
+    }
+
+    //
+    // org.mpris.MediaPlayer2.TrackList
+    //
+
+    /// See [`Player::get_track_list`](crate::Player::get_track_list).
+    pub fn get_track_list(&self) -> Result<TrackList, DBusError> {
+        self.get::<Vec<Path<'static>>>(TRACK_LIST_INTERFACE, "Tracks")
+            .map(TrackList::from)
+    }
+
+    /// See [`Player::checked_get_track_list`](crate::Player::checked_get_track_list).
+    pub fn checked_get_track_list(&self) -> Result<Option<TrackList>, DBusError> {
+        if self.supports_track_lists() && self.get_has_track_list()? {
+            self.get_track_list().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// See [`Player::can_edit_tracks`](crate::Player::can_edit_tracks).
+    pub fn can_edit_tracks(&self) -> Result<bool, DBusError> {
+        self.get(TRACK_LIST_INTERFACE, "CanEditTracks")
+    }
+
+    /// See [`Player::checked_can_edit_tracks`](crate::Player::checked_can_edit_tracks).
+    pub fn checked_can_edit_tracks(&self) -> bool {
+        if self.supports_track_lists() {
+            self.can_edit_tracks().unwrap_or(false)
+        } else {
+            false
+        }
+    }
+
+    /// See [`Player::get_tracks_metadata`](crate::Player::get_tracks_metadata).
+    pub fn get_tracks_metadata(&self, track_ids: &[TrackID]) -> Result<Vec<Metadata>, DBusError> {
+        let paths: Vec<Path<'_>> = track_ids.iter().map(TrackID::as_path).collect();
+        let (metadata,): (Vec<HashMap<String, Value>>,) =
+            self.call(TRACK_LIST_INTERFACE, "GetTracksMetadata", (paths,))?;
+
+        if metadata.len() == track_ids.len() {
+            Ok(metadata.into_iter().map(Metadata::from).collect())
+        } else {
+            Err(DBusError::Miscellaneous(format!(
+                "Expected {} tracks, but got {} tracks returned.",
+                track_ids.len(),
+                metadata.len()
+            )))
+        }
+    }
+
+    /// See [`Player::get_track_metadata`](crate::Player::get_track_metadata).
+    pub fn get_track_metadata(&self, track_id: &TrackID) -> Result<Metadata, DBusError> {
+        let mut result = self.get_tracks_metadata(std::slice::from_ref(track_id))?;
+        result.pop().ok_or_else(|| {
+            DBusError::Miscellaneous(format!("Player gave no Metadata for {}", track_id))
+        })
+    }
+
+    /// See [`Player::go_to`](crate::Player::go_to).
+    pub fn go_to(&self, track_id: &TrackID) -> Result<(), DBusError> {
+        self.call(TRACK_LIST_INTERFACE, "GoTo", (track_id.as_path(),))
+    }
+
+    /// See [`Player::add_track`](crate::Player::add_track).
+    pub fn add_track(
+        &self,
+        uri: &str,
+        after: &TrackID,
+        set_as_current: bool,
+    ) -> Result<(), DBusError> {
+        self.call(
+            TRACK_LIST_INTERFACE,
+            "AddTrack",
+            (uri, after.as_path(), set_as_current),
+        )
+    }
+
+    /// See [`Player::add_track_at_start`](crate::Player::add_track_at_start).
+    pub fn add_track_at_start(&self, uri: &str, set_as_current: bool) -> Result<(), DBusError> {
+        let no_track = Path::from(crate::track_list::NO_TRACK);
+        self.call(
+            TRACK_LIST_INTERFACE,
+            "AddTrack",
+            (uri, no_track, set_as_current),
+        )
+    }
+
+    /// See [`Player::remove_track`](crate::Player::remove_track).
+    pub fn remove_track(&self, track_id: &TrackID) -> Result<(), DBusError> {
+        self.call(TRACK_LIST_INTERFACE, "RemoveTrack", (track_id.as_path(),))
+    }
+
+    //
+    // org.mpris.MediaPlayer2.Playlists
+    //
+
+    /// See [`Player::get_playlists`](crate::Player::get_playlists).
+    pub fn get_playlists(
+        &self,
+        index: u32,
+        max_count: u32,
+        ordering: PlaylistOrdering,
+        reverse: bool,
+    ) -> Result<Vec<Playlist>, DBusError> {
+        let (playlists,): (Vec<(Path<'static>, String, String)>,) = self.call(
+            PLAYLISTS_INTERFACE,
+            "GetPlaylists",
+            (index, max_count, ordering.dbus_value(), reverse),
+        )?;
+        Ok(playlists.into_iter().map(Playlist::from).collect())
+    }
+
+    /// See [`Player::activate_playlist`](crate::Player::activate_playlist).
+    pub fn activate_playlist(&self, playlist: &Playlist) -> Result<(), DBusError> {
+        self.call(
+            PLAYLISTS_INTERFACE,
+            "ActivatePlaylist",
+            (playlist.as_path(),),
+        )
+    }
+
+    /// See [`Player::get_playlist_count`](crate::Player::get_playlist_count).
+    pub fn get_playlist_count(&self) -> Result<u32, DBusError> {
+        self.get(PLAYLISTS_INTERFACE, "PlaylistCount")
+    }
+
+    /// See [`Player::get_playlist_orderings`](crate::Player::get_playlist_orderings).
+    pub fn get_playlist_orderings(&self) -> Result<Vec<PlaylistOrdering>, DBusError> {
+        self.get::<Vec<String>>(PLAYLISTS_INTERFACE, "Orderings")?
+            .iter()
+            .map(|ordering| ordering.parse().map_err(DBusError::from))
+            .collect()
+    }
+
+    /// See [`Player::get_active_playlist`](crate::Player::get_active_playlist).
+    pub fn get_active_playlist(&self) -> Result<Option<Playlist>, DBusError> {
+        let (valid, playlist) = self.get::<(bool, (Path<'static>, String, String))>(
+            PLAYLISTS_INTERFACE,
+            "ActivePlaylist",
+        )?;
+        if valid {
+            Ok(Some(Playlist::from(playlist)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    //
+    // Internals
+    //
+
+    fn get_all_properties(&self, interface: &str) -> Result<HashMap<String, Value>, DBusError> {
+        let (properties,): (HashMap<String, Value>,) =
+            self.call("org.freedesktop.DBus.Properties", "GetAll", (interface,))?;
+        Ok(properties)
+    }
+
+    fn get_progress(&self) -> Result<Progress, DBusError> {
+        self.get_all_properties(PLAYER_INTERFACE)
+            .and_then(Progress::from_properties)
+    }
+
+    fn get_root_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_all_properties(ROOT_INTERFACE)
+            .map(|properties| Capabilities::from_root_properties(&properties))
+    }
+
+    /// Starts queuing up the events of this player on the connection.
+    pub(crate) fn subscribe_events(&self) -> SharedEventSubscription {
+        self.connection.subscribe_events(&self.unique_name)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.max(0) as u64)
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SharedConnection> {
+        self.connection.with_path(&self.bus_name, self.timeout())
+    }
+
+    fn call<A, R>(&self, interface: &str, method: &str, args: A) -> Result<R, DBusError>
+    where
+        A: AppendAll,
+        R: ReadAll + 'static,
+    {
+        self.proxy()
+            .method_call(interface, method, args)
+            .map_err(|e| DBusError::from_call(e, method))
+    }
+
+    fn get<T>(&self, interface: &str, property: &str) -> Result<T, DBusError>
+    where
+        T: for<'b> Get<'b> + 'static,
+    {
+        self.proxy()
+            .get(interface, property)
+            .map_err(|e| DBusError::from_call(e, property))
+    }
+
+    fn get_optional<T>(&self, interface: &str, property: &str) -> Result<Option<T>, DBusError>
+    where
+        T: for<'b> Get<'b> + 'static,
+    {
+        handle_optional_property(self.get(interface, property))
+    }
+
+    fn set<T>(&self, interface: &str, property: &str, value: T) -> Result<(), DBusError>
+    where
+        T: Arg + Append,
+    {
+        self.proxy()
+            .set(interface, property, value)
+            .map_err(|e| DBusError::from_call(e, property))
+    }
+
+    fn has_player_property(&self, property: &str) -> Result<bool, DBusError> {
+        self.proxy()
+            .get_all(PLAYER_INTERFACE)
+            .map(|props| props.contains_key(property))
+            .map_err(|e| DBusError::from_call(e, "GetAll"))
+    }
+}
+
+impl fmt::Debug for SharedPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedPlayer")
+            .field("bus_name", &self.bus_name)
+            .field("unique_name", &self.unique_name)
+            .field("identity", &self.identity)
+            .field("timeout_ms", &self.timeout_ms)
+            .field("has_tracklist_interface", &self.has_tracklist_interface)
+            .field("has_playlists_interface", &self.has_playlists_interface)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventSource for SharedPlayer {
+    /// Events are queued up per reader on the connection.
+    type Subscription = SharedEventSubscription;
+
+    fn is_running(&self) -> bool {
+        SharedPlayer::is_running(self)
+    }
+
+    fn wait_for_events(
+        &self,
+        subscription: &Self::Subscription,
+    ) -> Result<Vec<MprisEvent>, EventError> {
+        self.connection
+            .process_events_blocking_until_received(subscription);
+        Ok(self.connection.pending_events(subscription))
+    }
+
+    fn progress(&self) -> Result<Progress, DBusError> {
+        self.get_progress()
+    }
+
+    fn root_capabilities(&self) -> Result<Capabilities, DBusError> {
+        self.get_root_capabilities()
+    }
+
+    fn checked_get_track_list(&self) -> Result<Option<TrackList>, DBusError> {
+        SharedPlayer::checked_get_track_list(self)
+    }
+
+    fn playlists_state(&self) -> Result<Option<PlaylistsState>, DBusError> {
+        if !self.supports_playlists() {
+            return Ok(None);
+        }
+
+        Ok(Some(PlaylistsState {
+            active_playlist: self.get_active_playlist()?,
+            playlist_count: self.get_playlist_count()?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::serve;
+    use crate::{Event, SharedPlayerFinder, SharedPlayerIter};
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn it_is_send_and_sync() {
+        assert_send_sync::<SharedPlayer>();
+        assert_send_sync::<SharedPlayerFinder>();
+        assert_send_sync::<SharedPlayerIter>();
+        fn assert_send<T: Send>() {}
+        assert_send::<SharedPlayerEvents>();
+    }
+
+    #[test]
+    fn it_shares_players_between_threads() {
+        let (bus_name, handle) = serve("mpris_rs_test_shared");
+
+        let finder = SharedPlayerFinder::new().expect("Could not connect to D-Bus");
+        let player = finder
+            .find_all()
+            .unwrap()
+            .into_iter()
+            .find(|player| player.bus_name() == bus_name)
+            .expect("Could not find server");
+        assert_eq!(player.identity(), "Test Player");
+
+        let mut events = player.events().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let listener = thread::spawn(move || {
+            for event in &mut events {
+                match event {
+                    Ok(event) => sender.send(event).unwrap(),
+                    // The player can quit while the state for a late signal is being read.
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let controller = {
+            let player = player.clone();
+            thread::spawn(move || {
+                player.play().unwrap();
+                player.set_volume(0.5).unwrap();
+            })
+        };
+        controller.join().unwrap();
+
+        match receiver.recv().unwrap() {
+            Event::Playing => {}
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        match receiver.recv().unwrap() {
+            Event::VolumeChanged(volume) => assert!((volume - 0.5).abs() < f64::EPSILON),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        assert!(player.checked_quit().unwrap());
+        listener.join().unwrap();
+        for event in receiver.try_iter() {
+            match event {
+                Event::PlayerShutDown => {}
+                other => panic!("Unexpected event: {:?}", other),
+            }
+        }
+
+        let backend = handle.join().unwrap();
+        assert!(backend.playing);
+    }
+
+    #[test]
+    fn it_fans_out_events_to_every_reader() {
+        let (bus_name, handle) = serve("mpris_rs_test_shared_fan_out");
+
+        let finder = SharedPlayerFinder::new().expect("Could not connect to D-Bus");
+        let player = finder
+            .find_all()
+            .unwrap()
+            .into_iter()
+            .find(|player| player.bus_name() == bus_name)
+            .expect("Could not find server");
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let mut events = player.events().unwrap();
+                thread::spawn(move || events.next().unwrap().unwrap())
+            })
+            .collect();
+
+        player.play().unwrap();
+        for reader in readers {
+            match reader.join().unwrap() {
+                Event::Playing => {}
+                other => panic!("Unexpected event: {:?}", other),
+            }
+        }
+
+        player.quit().unwrap();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::extensions::DurationExtensions;
+use crate::metadata::Value;
+use crate::{Capabilities, DBusError, LoopStatus, Metadata, PlaybackStatus};
+
+/// All properties of a [`Player`](crate::Player) at a single point in time.
+///
+/// Created by calling [`Player::snapshot`](crate::Player::snapshot), which reads every property
+/// of the `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player` interfaces with only two
+/// round trips over the D-Bus.
+///
+/// Optional properties that the player does not implement are [`None`]. Properties that were
+/// added in later versions of the MPRIS2 specification fall back to the same defaults as the
+/// getters on [`Player`](crate::Player).
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    identity: String,
+    desktop_entry: Option<String>,
+    can_quit: bool,
+    can_raise: bool,
+    fullscreen: Option<bool>,
+    can_set_fullscreen: bool,
+    has_track_list: bool,
+    supported_uri_schemes: Vec<String>,
+    supported_mime_types: Vec<String>,
+
+    playback_status: PlaybackStatus,
+    loop_status: Option<LoopStatus>,
+    rate: Option<f64>,
+    minimum_rate: f64,
+    maximum_rate: f64,
+    shuffle: Option<bool>,
+    metadata: Metadata,
+    volume: Option<f64>,
+    position: Option<Duration>,
+    can_go_next: bool,
+    can_go_previous: bool,
+    can_play: bool,
+    can_pause: bool,
+    can_seek: bool,
+    can_control: bool,
+
+    capabilities: Capabilities,
+}
+
+impl PlayerState {
+    /// Builds a [`PlayerState`] from all the properties of the `org.mpris.MediaPlayer2` and
+    /// `org.mpris.MediaPlayer2.Player` interfaces, as returned by
+    /// `org.freedesktop.DBus.Properties.GetAll`.
+    pub(crate) fn from_properties(
+        mut root: HashMap<String, Value>,
+        mut player: HashMap<String, Value>,
+    ) -> Result<PlayerState, DBusError> {
+        let playback_status = player
+            .get("PlaybackStatus")
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                DBusError::Miscellaneous(String::from("Player has no PlaybackStatus property"))
+            })?
+            .parse()?;
+        let loop_status = match player.get("LoopStatus").and_then(Value::as_str) {
+            Some(loop_status) => Some(loop_status.parse()?),
+            None => None,
+        };
+
+        let capabilities = Capabilities::from_root_properties(&root)
+            | Capabilities::from_player_properties(&player);
+
+        Ok(PlayerState {
+            identity: take_string(&mut root, "Identity").unwrap_or_default(),
+            desktop_entry: take_string(&mut root, "DesktopEntry"),
+            can_quit: get_bool(&root, "CanQuit"),
+            can_raise: get_bool(&root, "CanRaise"),
+            fullscreen: root.get("Fullscreen").and_then(Value::as_bool),
+            can_set_fullscreen: get_bool(&root, "CanSetFullscreen"),
+            has_track_list: get_bool(&root, "HasTrackList"),
+            supported_uri_schemes: take_strings(&mut root, "SupportedUriSchemes"),
+            supported_mime_types: take_strings(&mut root, "SupportedMimeTypes"),
+
+            playback_status,
+            loop_status,
+            rate: player.get("Rate").and_then(Value::as_f64),
+            minimum_rate: player
+                .get("MinimumRate")
+                .and_then(Value::as_f64)
+                .unwrap_or(1.0),
+            maximum_rate: player
+                .get("MaximumRate")
+                .and_then(Value::as_f64)
+                .unwrap_or(1.0),
+            shuffle: player.get("Shuffle").and_then(Value::as_bool),
+            metadata: player
+                .remove("Metadata")
+                .and_then(Value::into_map)
+                .map(Metadata::from)
+                .unwrap_or_default(),
+            volume: player.get("Volume").and_then(Value::as_f64),
+            position: player
+                .get("Position")
+                .and_then(Value::as_i64)
+                .map(|position| Duration::from_micros_ext(position.max(0) as u64)),
+            can_go_next: get_bool(&player, "CanGoNext"),
+            can_go_previous: get_bool(&player, "CanGoPrevious"),
+            can_play: get_bool(&player, "CanPlay"),
+            can_pause: get_bool(&player, "CanPause"),
+            can_seek: get_bool(&player, "CanSeek"),
+            can_control: get_bool(&player, "CanControl"),
+
+            capabilities,
+        })
+    }
+
+    /// The player's `Identity`, like `Spotify`.
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// The player's `DesktopEntry`, if it has one.
+    pub fn desktop_entry(&self) -> Option<&str> {
+        self.desktop_entry.as_deref()
+    }
+
+    /// If the player can be asked to quit.
+    pub fn can_quit(&self) -> bool {
+        self.can_quit
+    }
+
+    /// If the player can be raised.
+    pub fn can_raise(&self) -> bool {
+        self.can_raise
+    }
+
+    /// If the player is in fullscreen mode, or [`None`] if the player does not support
+    /// fullscreen.
+    pub fn fullscreen(&self) -> Option<bool> {
+        self.fullscreen
+    }
+
+    /// If the player can be asked to enter or leave fullscreen.
+    pub fn can_set_fullscreen(&self) -> bool {
+        self.can_set_fullscreen
+    }
+
+    /// The player's `HasTrackList` property.
+    pub fn has_track_list(&self) -> bool {
+        self.has_track_list
+    }
+
+    /// The URI schemes that the player supports opening.
+    pub fn supported_uri_schemes(&self) -> &[String] {
+        &self.supported_uri_schemes
+    }
+
+    /// The MIME types that the player supports opening.
+    pub fn supported_mime_types(&self) -> &[String] {
+        &self.supported_mime_types
+    }
+
+    /// The playback status.
+    pub fn playback_status(&self) -> PlaybackStatus {
+        self.playback_status
+    }
+
+    /// The loop status, or [`None`] if the player does not support looping.
+    pub fn loop_status(&self) -> Option<LoopStatus> {
+        self.loop_status
+    }
+
+    /// The playback rate, or [`None`] if the player does not support it.
+    pub fn playback_rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// The minimum-maximum allowed value range for the playback rate.
+    pub fn valid_playback_rate_range(&self) -> Range<f64> {
+        self.minimum_rate..self.maximum_rate
+    }
+
+    /// The shuffle status, or [`None`] if the player does not support shuffling.
+    pub fn shuffle(&self) -> Option<bool> {
+        self.shuffle
+    }
+
+    /// The metadata of the current track.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Consumes the state, returning the metadata of the current track.
+    pub fn into_metadata(self) -> Metadata {
+        self.metadata
+    }
+
+    /// The volume, or [`None`] if the player does not support it.
+    pub fn volume(&self) -> Option<f64> {
+        self.volume
+    }
+
+    /// The position in the current track, or [`None`] if the player does not support it.
+    pub fn position(&self) -> Option<Duration> {
+        self.position
+    }
+
+    /// If the player can go to the next track.
+    pub fn can_go_next(&self) -> bool {
+        self.can_go_next
+    }
+
+    /// If the player can go to the previous track.
+    pub fn can_go_previous(&self) -> bool {
+        self.can_go_previous
+    }
+
+    /// If the player can start playing.
+    pub fn can_play(&self) -> bool {
+        self.can_play
+    }
+
+    /// If the player can pause.
+    pub fn can_pause(&self) -> bool {
+        self.can_pause
+    }
+
+    /// If the player can seek within the current track.
+    pub fn can_seek(&self) -> bool {
+        self.can_seek
+    }
+
+    /// If the player can be controlled at all.
+    pub fn can_control(&self) -> bool {
+        self.can_control
+    }
+
+    /// Everything that the player can do, or supports.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+}
+
+fn get_bool(properties: &HashMap<String, Value>, name: &str) -> bool {
+    properties
+        .get(name)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn take_string(properties: &mut HashMap<String, Value>, name: &str) -> Option<String> {
+    properties.remove(name).and_then(Value::into_string)
+}
+
+fn take_strings(properties: &mut HashMap<String, Value>, name: &str) -> Vec<String> {
+    properties
+        .remove(name)
+        .and_then(Value::into_array)
+        .map(|values| values.into_iter().flat_map(Value::into_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_properties() {
+        let mut root = HashMap::new();
+        root.insert(String::from("Identity"), Value::from("Test Player"));
+        root.insert(String::from("CanQuit"), Value::Bool(true));
+        root.insert(
+            String::from("SupportedUriSchemes"),
+            Value::Array(vec![Value::from("file")]),
+        );
+
+        let mut player = HashMap::new();
+        player.insert(String::from("PlaybackStatus"), Value::from("Playing"));
+        player.insert(String::from("Volume"), Value::F64(0.5));
+        player.insert(String::from("Position"), Value::I64(2_000_000));
+
+        let state = PlayerState::from_properties(root, player).unwrap();
+        assert_eq!(state.identity(), "Test Player");
+        assert!(state.can_quit());
+        assert!(!state.can_raise());
+        assert_eq!(state.supported_uri_schemes(), &[String::from("file")]);
+        assert_eq!(state.playback_status(), PlaybackStatus::Playing);
+        assert_eq!(state.volume(), Some(0.5));
+        assert_eq!(state.position(), Some(Duration::from_secs(2)));
+        assert_eq!(state.shuffle(), None);
+        assert_eq!(state.loop_status(), None);
+        assert_eq!(
+            state.capabilities(),
+            Capabilities::QUIT | Capabilities::VOLUME | Capabilities::POSITION
+        );
+    }
+
+    #[test]
+    fn it_requires_a_playback_status() {
+        assert!(PlayerState::from_properties(HashMap::new(), HashMap::new()).is_err());
+    }
+}
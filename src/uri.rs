@@ -0,0 +1,239 @@
+use thiserror::Error;
+
+use super::DBusError;
+
+/// Reasons for [`Player::checked_open_uri`](crate::Player::checked_open_uri) to not open a URI.
+#[derive(Debug, Error)]
+pub enum OpenUriError {
+    /// The URI did not start with a valid scheme (like `file:` or `https:`), so it could not be
+    /// checked against the player's supported URI schemes.
+    #[error("URI has no valid scheme: {0}")]
+    InvalidUri(String),
+
+    /// The player does not list the URI's scheme in its `SupportedUriSchemes`. The (lowercased)
+    /// scheme is provided.
+    #[error("Player does not support the URI scheme \"{0}\"")]
+    UnsupportedScheme(String),
+
+    /// The player does not list the MIME type guessed from the URI in its `SupportedMimeTypes`.
+    /// The guessed MIME type is provided.
+    #[error("Player does not support the MIME type \"{0}\"")]
+    UnsupportedMimeType(String),
+
+    /// Checking the player's capabilities or opening the URI failed due to an underlying
+    /// [`DBusError`].
+    #[error("{0}")]
+    DBusError(#[from] DBusError),
+}
+
+/// Known file extensions and the MIME types that players use to advertise support for them. The
+/// first MIME type is the canonical one; the rest are aliases seen in the wild.
+const MIME_TYPES: &[(&str, &[&str])] = &[
+    ("aac", &["audio/aac", "audio/x-aac"]),
+    ("aiff", &["audio/x-aiff", "audio/aiff"]),
+    ("ape", &["audio/x-ape", "audio/ape"]),
+    ("avi", &["video/x-msvideo", "video/avi", "video/msvideo"]),
+    ("flac", &["audio/flac", "audio/x-flac"]),
+    ("flv", &["video/x-flv", "video/flv"]),
+    ("m3u", &["audio/x-mpegurl", "audio/mpegurl"]),
+    (
+        "m3u8",
+        &["application/vnd.apple.mpegurl", "audio/x-mpegurl"],
+    ),
+    ("m4a", &["audio/mp4", "audio/x-m4a", "audio/m4a"]),
+    ("m4v", &["video/x-m4v", "video/mp4"]),
+    ("mid", &["audio/midi", "audio/x-midi"]),
+    ("midi", &["audio/midi", "audio/x-midi"]),
+    ("mka", &["audio/x-matroska", "audio/matroska"]),
+    ("mkv", &["video/x-matroska", "video/matroska"]),
+    ("mov", &["video/quicktime"]),
+    ("mp2", &["audio/mpeg", "audio/x-mp2", "audio/mp2"]),
+    (
+        "mp3",
+        &["audio/mpeg", "audio/mp3", "audio/x-mp3", "audio/x-mpeg"],
+    ),
+    ("mp4", &["video/mp4", "audio/mp4"]),
+    ("mpeg", &["video/mpeg"]),
+    ("mpg", &["video/mpeg"]),
+    (
+        "oga",
+        &["audio/ogg", "audio/x-vorbis+ogg", "application/ogg"],
+    ),
+    (
+        "ogg",
+        &["audio/ogg", "audio/x-vorbis+ogg", "application/ogg"],
+    ),
+    ("ogv", &["video/ogg", "application/ogg"]),
+    ("opus", &["audio/ogg", "audio/opus", "audio/x-opus+ogg"]),
+    ("pls", &["audio/x-scpls", "audio/scpls"]),
+    ("spx", &["audio/x-speex", "audio/ogg", "audio/x-speex+ogg"]),
+    ("wav", &["audio/x-wav", "audio/wav", "audio/vnd.wave"]),
+    ("webm", &["video/webm", "audio/webm"]),
+    ("wma", &["audio/x-ms-wma"]),
+    ("wmv", &["video/x-ms-wmv"]),
+    ("wv", &["audio/x-wavpack", "audio/wavpack"]),
+    ("xspf", &["application/xspf+xml"]),
+];
+
+/// Returns the (lowercased) scheme part of a URI, e.g. `https` for `https://example.com/`.
+///
+/// Returns [`None`] if the URI does not start with a valid scheme followed by a `:`.
+pub(crate) fn uri_scheme(uri: &str) -> Option<String> {
+    let (scheme, _) = uri.split_at(uri.find(':')?);
+    let mut chars = scheme.chars();
+
+    // See RFC 3986, section 3.1.
+    let valid = chars.next()?.is_ascii_alphabetic()
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+
+    if valid {
+        Some(scheme.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Guesses the MIME types that a URI points to based on its file extension. The first entry is
+/// the canonical MIME type.
+///
+/// Returns [`None`] if the URI has no file extension, or if the file extension is unknown.
+pub(crate) fn guess_mime_types(uri: &str) -> Option<&'static [&'static str]> {
+    let path = uri.split(|c| c == '?' || c == '#').next()?;
+    let file_name = path.rsplit('/').next()?;
+    let (_, extension) = file_name.split_at(file_name.rfind('.')? + 1);
+    let extension = extension.to_ascii_lowercase();
+
+    MIME_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, mime_types)| *mime_types)
+}
+
+/// Checks a URI against a player's supported URI schemes and MIME types.
+///
+/// The MIME type check is skipped if the type cannot be guessed from the URI, or if the player
+/// does not advertise any MIME types at all.
+pub(crate) fn check_uri(
+    uri: &str,
+    supported_schemes: &[String],
+    supported_mime_types: &[String],
+) -> Result<(), OpenUriError> {
+    let scheme = uri_scheme(uri).ok_or_else(|| OpenUriError::InvalidUri(uri.to_string()))?;
+    if !supported_schemes
+        .iter()
+        .any(|supported| supported.eq_ignore_ascii_case(&scheme))
+    {
+        return Err(OpenUriError::UnsupportedScheme(scheme));
+    }
+
+    if supported_mime_types.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(mime_types) = guess_mime_types(uri) {
+        let supported = mime_types.iter().any(|mime_type| {
+            supported_mime_types
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(mime_type))
+        });
+        if !supported {
+            return Err(OpenUriError::UnsupportedMimeType(mime_types[0].to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn it_parses_schemes() {
+        assert_eq!(
+            uri_scheme("file:///music/a.mp3"),
+            Some(String::from("file"))
+        );
+        assert_eq!(
+            uri_scheme("HTTPS://example.com"),
+            Some(String::from("https"))
+        );
+        assert_eq!(
+            uri_scheme("spotify:track:1234"),
+            Some(String::from("spotify"))
+        );
+        assert_eq!(uri_scheme("/music/a.mp3"), None);
+        assert_eq!(uri_scheme("1http://example.com"), None);
+        assert_eq!(uri_scheme(":foo"), None);
+    }
+
+    #[test]
+    fn it_guesses_mime_types() {
+        assert_eq!(
+            guess_mime_types("file:///music/a.MP3").map(|types| types[0]),
+            Some("audio/mpeg")
+        );
+        assert_eq!(
+            guess_mime_types("https://example.com/a.flac?token=a.b#c.d").map(|types| types[0]),
+            Some("audio/flac")
+        );
+        assert_eq!(guess_mime_types("https://example.com/stream"), None);
+        assert_eq!(guess_mime_types("https://example.com.au/"), None);
+        assert_eq!(guess_mime_types("spotify:track:1234"), None);
+    }
+
+    #[test]
+    fn it_rejects_unsupported_schemes() {
+        let result = check_uri("smb://server/a.mp3", &strings(&["file", "http"]), &[]);
+        match result {
+            Err(OpenUriError::UnsupportedScheme(scheme)) => assert_eq!(scheme, "smb"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_rejects_unsupported_mime_types() {
+        let result = check_uri(
+            "file:///music/a.flac",
+            &strings(&["file"]),
+            &strings(&["audio/mpeg"]),
+        );
+        match result {
+            Err(OpenUriError::UnsupportedMimeType(mime)) => assert_eq!(mime, "audio/flac"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_accepts_mime_type_aliases() {
+        assert!(check_uri(
+            "file:///music/a.flac",
+            &strings(&["file"]),
+            &strings(&["audio/x-flac"]),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_skips_mime_check_when_unknown() {
+        assert!(check_uri(
+            "spotify:track:1234",
+            &strings(&["spotify"]),
+            &strings(&["audio/mpeg"]),
+        )
+        .is_ok());
+        assert!(check_uri("file:///music/a.flac", &strings(&["file"]), &[]).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_uris_without_scheme() {
+        assert!(matches!(
+            check_uri("/music/a.mp3", &strings(&["file"]), &[]),
+            Err(OpenUriError::InvalidUri(_))
+        ));
+    }
+}
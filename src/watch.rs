@@ -0,0 +1,147 @@
+use std::rc::Rc;
+
+use crate::player::Player;
+use crate::pooled_connection::{NameChange, NameChangeQueue, PooledConnection};
+use crate::DBusError;
+
+/// A change in the set of [`Player`]s on the bus, as reported by a [`PlayerWatcher`].
+#[derive(Debug)]
+pub enum PlayerChange {
+    /// A new player appeared on the bus.
+    PlayerAppeared(Player),
+
+    /// A player disappeared from the bus, most likely because it quit.
+    PlayerVanished {
+        /// The well-known bus name that the player had, e.g. `org.mpris.MediaPlayer2.vlc`.
+        bus_name: String,
+
+        /// The unique bus name that the player had, e.g. `:1.1337`. Compare it with
+        /// [`Player::unique_name`] to find out which of your players vanished.
+        unique_name: String,
+    },
+}
+
+/// An iterator that blocks until a [`Player`] appears on, or disappears from, the bus.
+///
+/// Created by calling [`PlayerFinder::watch`](crate::PlayerFinder::watch).
+///
+/// Only changes that happen after the watcher was created are reported. To get a complete
+/// picture, create the watcher first and then use [`PlayerFinder::find_all`](crate::PlayerFinder::find_all)
+/// to find the players that were already running. A player that appears in between can then be
+/// reported by both.
+///
+/// Players that appear are created with the same connection and timeout as the ones found by the
+/// [`PlayerFinder`](crate::PlayerFinder). If a player could not be created, for example because it
+/// quit again right away, an [`Err`] is returned instead.
+///
+/// The iteration never ends on its own.
+#[derive(Debug)]
+pub struct PlayerWatcher {
+    connection: Rc<PooledConnection>,
+    changes: Rc<NameChangeQueue>,
+    timeout_ms: i32,
+}
+
+impl PlayerWatcher {
+    pub(crate) fn new(connection: Rc<PooledConnection>, timeout_ms: i32) -> Self {
+        PlayerWatcher {
+            changes: connection.watch_names(),
+            connection,
+            timeout_ms,
+        }
+    }
+
+    fn next_change(&self) -> NameChange {
+        loop {
+            if let Some(change) = self.changes.borrow_mut().pop_front() {
+                return change;
+            }
+            self.connection.process_events_blocking_until_received();
+        }
+    }
+}
+
+impl Iterator for PlayerWatcher {
+    type Item = Result<PlayerChange, DBusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_change() {
+            NameChange::Acquired { bus_name } => Some(
+                Player::for_pooled_connection(self.connection.clone(), bus_name, self.timeout_ms)
+                    .map(PlayerChange::PlayerAppeared),
+            ),
+            NameChange::Lost {
+                bus_name,
+                unique_name,
+            } => Some(Ok(PlayerChange::PlayerVanished {
+                bus_name,
+                unique_name,
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::serve;
+    use crate::PlayerFinder;
+
+    #[test]
+    fn it_watches_players_appear_and_vanish() {
+        let finder = PlayerFinder::new().expect("Could not connect to D-Bus");
+        let mut watcher = finder.watch();
+
+        let (bus_name, handle) = serve("mpris_rs_test_watch");
+
+        let player = loop {
+            match watcher.next().unwrap() {
+                Ok(PlayerChange::PlayerAppeared(player)) if player.bus_name() == bus_name => {
+                    break player
+                }
+                // Other tests start and stop players on the same bus.
+                _ => {}
+            }
+        };
+        assert_eq!(player.identity(), "Test Player");
+
+        player.quit().unwrap();
+        let unique_name = player.unique_name().to_string();
+        loop {
+            match watcher.next().unwrap() {
+                Ok(PlayerChange::PlayerVanished {
+                    bus_name: vanished,
+                    unique_name: vanished_unique,
+                }) if vanished == bus_name => {
+                    assert_eq!(vanished_unique, unique_name);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn it_splits_owner_changes() {
+        assert_eq!(
+            NameChange::from_owners("org.mpris.MediaPlayer2.a", "", ":1.2"),
+            vec![NameChange::Acquired {
+                bus_name: "org.mpris.MediaPlayer2.a".to_string()
+            }]
+        );
+        assert_eq!(
+            NameChange::from_owners("org.mpris.MediaPlayer2.a", ":1.1", ":1.2"),
+            vec![
+                NameChange::Lost {
+                    bus_name: "org.mpris.MediaPlayer2.a".to_string(),
+                    unique_name: ":1.1".to_string(),
+                },
+                NameChange::Acquired {
+                    bus_name: "org.mpris.MediaPlayer2.a".to_string()
+                },
+            ]
+        );
+    }
+}